@@ -24,6 +24,7 @@ fn main() -> Result<(), Box<dyn Error + 'static>> {
         "google/rpc/error_details.proto",
         "google/rpc/code.proto",
         "google/rpc/status.proto",
+        "bomboni/request/v1/query.proto",
     ]
     .into_iter()
     .map(|proto_path| root_path.join(proto_path))
@@ -61,6 +62,9 @@ fn main() -> Result<(), Box<dyn Error + 'static>> {
     compile_config
         .external_paths
         .insert(".google", "crate::google");
+    compile_config
+        .external_paths
+        .insert(".bomboni", "crate::bomboni");
     compile(compile_config)?;
 
     Ok(())
@@ -51,6 +51,28 @@ pub mod google {
     }
 }
 
+#[allow(
+    unused_qualifications,
+    missing_docs,
+    clippy::all,
+    clippy::pedantic,
+    clippy::nursery,
+    rustdoc::broken_intra_doc_links,
+    rustdoc::invalid_html_tags
+)]
+/// Generated Bomboni types.
+pub mod bomboni {
+    /// Generated request types.
+    pub mod request {
+        /// Generated representation of parsed queries, such as filters and orderings.
+        #[allow(rustdoc::broken_intra_doc_links, rustdoc::invalid_html_tags)]
+        pub mod v1 {
+            crate::include_proto!("bomboni.request.v1");
+            crate::include_proto!("bomboni.request.v1.plus");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use google::rpc::BadRequest;
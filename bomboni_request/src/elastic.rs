@@ -0,0 +1,890 @@
+//! # Elasticsearch query DSL.
+//!
+//! Utility for turning search queries into Elasticsearch and OpenSearch search requests.
+
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::{
+    filter::{
+        Filter, FilterComparator,
+        error::{FilterError, FilterResult},
+        split_wildcard,
+    },
+    ordering::{Ordering, OrderingDirection},
+    query::{
+        error::{QueryError, QueryResult},
+        search::SearchQuery,
+    },
+    schema::{FieldMemberSchema, MemberSchema, Schema},
+    sql::SqlRenameMap,
+    value::{Value, duration_nanos_i64},
+};
+
+/// Builder for Elasticsearch search request bodies.
+///
+/// The query string becomes a `multi_match` query over the query fields,
+/// and the filter becomes `filter` clauses of a `bool` query, using `term`, `terms`, `range`, `exists` and `wildcard` queries.
+/// Restrictions on fields of repeated resources become `nested` queries.
+///
+/// The ordering becomes `sort`, and the page token becomes another `filter` clause.
+/// Page filters are inclusive, since page tokens are built from the first item of the next page,
+/// see [`crate::query::page_token::PageTokenBuilder::build_next`].
+pub struct ElasticQueryBuilder<'a> {
+    schema: &'a Schema,
+    rename_map: Option<&'a SqlRenameMap>,
+    query_fields: Vec<String>,
+}
+
+impl<'a> ElasticQueryBuilder<'a> {
+    /// Creates a new Elasticsearch query builder.
+    pub const fn new(schema: &'a Schema) -> Self {
+        Self {
+            schema,
+            rename_map: None,
+            query_fields: Vec::new(),
+        }
+    }
+
+    /// Sets the rename map.
+    pub const fn set_rename_map(&mut self, rename_map: &'a SqlRenameMap) -> &mut Self {
+        self.rename_map = Some(rename_map);
+        self
+    }
+
+    /// Sets the fields that the query string is matched against, such as `title^2`.
+    ///
+    /// Without query fields, the query string is matched against the default fields of the index.
+    pub fn set_query_fields<I, S>(&mut self, query_fields: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.query_fields = query_fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Builds a search request body.
+    ///
+    /// The filter and the ordering are validated against the schema first.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`QueryError::FilterError`] if the filter is invalid, or cannot be expressed as a query.
+    /// Will return [`QueryError::OrderingError`] if the ordering is invalid.
+    /// Will return [`QueryError::InvalidPageToken`] if the page filter is invalid.
+    pub fn build(&self, query: &SearchQuery) -> QueryResult<JsonValue> {
+        query.filter.validate(self.schema, None)?;
+        query.ordering.validate(self.schema)?;
+
+        let mut bool_query = Map::new();
+        if !query.query.is_empty() {
+            let mut multi_match = Map::new();
+            multi_match.insert("query".into(), query.query.clone().into());
+            if !self.query_fields.is_empty() {
+                multi_match.insert("fields".into(), self.query_fields.clone().into());
+            }
+            bool_query.insert("must".into(), json!([{ "multi_match": multi_match }]));
+        }
+        let mut clauses = match &query.filter {
+            Filter::Conjunction(parts) => self.build_filters(parts)?,
+            filter => vec![self.build_filter(filter)?],
+        };
+        if let Some(page_token) = &query.page_token {
+            let page_filter = page_token.filter.resolve(self.schema);
+            clauses.push(
+                page_filter
+                    .validate(self.schema, None)
+                    .and_then(|()| self.build_filter(&page_filter))
+                    .map_err(|_| QueryError::InvalidPageToken)?,
+            );
+        }
+        if !clauses.is_empty() {
+            bool_query.insert("filter".into(), clauses.into());
+        }
+
+        let mut body = Map::new();
+        body.insert(
+            "query".into(),
+            if bool_query.is_empty() {
+                json!({ "match_all": {} })
+            } else {
+                get_document("bool", bool_query)
+            },
+        );
+        body.insert("size".into(), query.page_size.into());
+        if !query.ordering.is_empty() {
+            body.insert("sort".into(), self.build_sort(&query.ordering).into());
+        }
+        Ok(body.into())
+    }
+
+    /// Builds a query clause from a filter.
+    ///
+    /// Functions have no Elasticsearch equivalent, and are reported as unknown.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FilterError::UnknownFunction`] if the filter contains a function call.
+    /// Will return [`FilterError::UnsuitableComparator`] if a repeated field is compared with `!=`,
+    /// since elements of arrays cannot be matched individually.
+    pub fn build_filter(&self, filter: &Filter) -> FilterResult<JsonValue> {
+        match filter {
+            Filter::Conjunction(parts) => Ok(get_bool_query("filter", self.build_filters(parts)?)),
+            Filter::Disjunction(parts) => Ok(get_should_query(self.build_filters(parts)?)),
+            Filter::Negate(tree) => self.build_negation(tree),
+            Filter::Restriction(comparable, comparator, argument) => {
+                self.build_restriction(comparable, *comparator, argument, false)
+            }
+            Filter::Composite(tree) => self.build_filter(tree),
+            // Boolean members
+            Filter::Name(name) => Ok(get_term_query(self.get_field_name(name), true)),
+            Filter::Value(Value::Boolean(true)) => Ok(json!({ "match_all": {} })),
+            Filter::Value(_) => Ok(json!({ "match_none": {} })),
+            Filter::Function(name, _) => Err(FilterError::UnknownFunction(name.clone())),
+        }
+    }
+
+    /// Builds a query clause matching documents for which the filter is false.
+    ///
+    /// Restrictions of null members are unknown, as in [`Filter::evaluate`], and neither they nor their negations match.
+    /// Since `must_not` also matches documents without the fields,
+    /// negations of restrictions of nullable members also require the members to exist.
+    fn build_negation(&self, filter: &Filter) -> FilterResult<JsonValue> {
+        if !self.has_nullable_restrictions(filter) {
+            return Ok(get_bool_query("must_not", vec![self.build_filter(filter)?]));
+        }
+        match filter {
+            // Negations of connectives are connectives of negations
+            Filter::Conjunction(parts) => Ok(get_should_query(self.build_negations(parts)?)),
+            Filter::Disjunction(parts) => {
+                Ok(get_bool_query("filter", self.build_negations(parts)?))
+            }
+            Filter::Negate(tree) => self.build_filter(tree),
+            Filter::Composite(tree) => self.build_negation(tree),
+            filter => Ok(get_must_not_query(
+                vec![self.build_filter(filter)?],
+                self.get_nullable_names(filter)
+                    .into_iter()
+                    .map(|name| self.get_field_name(name))
+                    .collect(),
+            )),
+        }
+    }
+
+    fn build_negations(&self, filters: &[Filter]) -> FilterResult<Vec<JsonValue>> {
+        filters
+            .iter()
+            .map(|filter| self.build_negation(filter))
+            .collect()
+    }
+
+    fn has_nullable_restrictions(&self, filter: &Filter) -> bool {
+        match filter {
+            Filter::Conjunction(parts) | Filter::Disjunction(parts) => parts
+                .iter()
+                .any(|part| self.has_nullable_restrictions(part)),
+            Filter::Negate(tree) | Filter::Composite(tree) => self.has_nullable_restrictions(tree),
+            filter => !self.get_nullable_names(filter).is_empty(),
+        }
+    }
+
+    /// Gets names of nullable members that a restriction is unknown for, if they are null.
+    fn get_nullable_names<'f>(&self, filter: &'f Filter) -> Vec<&'f str> {
+        let operands = match filter {
+            // Presence and null checks are never unknown
+            Filter::Restriction(_, _, argument)
+                if matches!(argument.as_ref(), Filter::Value(Value::Any | Value::Null)) =>
+            {
+                return Vec::new();
+            }
+            Filter::Restriction(comparable, _, argument) => {
+                vec![comparable.as_ref(), argument.as_ref()]
+            }
+            Filter::Name(_) => vec![filter],
+            _ => return Vec::new(),
+        };
+        operands
+            .into_iter()
+            .filter_map(|operand| match operand {
+                Filter::Name(name)
+                    if self.schema.split_repeated_resource(name).is_none()
+                        && self
+                            .schema
+                            .get_field(name)
+                            .is_some_and(|field| field.nullable) =>
+                {
+                    Some(name.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build_filters(&self, filters: &[Filter]) -> FilterResult<Vec<JsonValue>> {
+        filters
+            .iter()
+            .map(|filter| self.build_filter(filter))
+            .collect()
+    }
+
+    fn build_restriction(
+        &self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+        nested: bool,
+    ) -> FilterResult<JsonValue> {
+        let Filter::Name(name) = comparable else {
+            return self.build_script(comparable, comparator, argument);
+        };
+
+        if !nested && let Some((resource_name, _)) = self.schema.split_repeated_resource(name) {
+            return Ok(get_document(
+                "nested",
+                json!({
+                    "path": self.get_field_name(resource_name),
+                    "query": self.build_restriction(comparable, comparator, argument, true)?,
+                }),
+            ));
+        }
+
+        if let Some(MemberSchema::Map(_)) = self.schema.get_member(name) {
+            return self.build_map_key_presence(name, argument);
+        }
+
+        let field = self.schema.get_field(name);
+        let repeated = field.is_some_and(|field| field.repeated);
+        // Inequalities of null fields are unknown, whereas `must_not` matches them
+        let nullable = field.is_some_and(|field| field.nullable);
+
+        if let Some((parts, disjunction)) = argument.get_composite_argument() {
+            if comparator == FilterComparator::Equal
+                && disjunction
+                && parts.iter().all(|part| match part {
+                    Filter::Value(Value::String(value)) => split_wildcard(value).is_none(),
+                    Filter::Value(value) => !matches!(value, Value::Any | Value::Null),
+                    _ => false,
+                })
+            {
+                let values = parts
+                    .iter()
+                    .map(|part| match part {
+                        Filter::Value(value) => Self::build_value(value, field),
+                        _ => unreachable!(),
+                    })
+                    .collect::<FilterResult<Vec<_>>>()?;
+                return Ok(get_document(
+                    "terms",
+                    get_document(self.get_field_name(name), values),
+                ));
+            }
+
+            // Expand into separate restrictions, such that `a:(x OR y)` is `a:x OR a:y`
+            let clauses = parts
+                .iter()
+                .map(|part| self.build_restriction(comparable, comparator, part, nested))
+                .collect::<FilterResult<Vec<_>>>()?;
+            return Ok(if disjunction {
+                get_should_query(clauses)
+            } else {
+                get_bool_query("filter", clauses)
+            });
+        }
+
+        let Filter::Value(value) = argument else {
+            return self.build_script(comparable, comparator, argument);
+        };
+
+        let field_name = self.get_field_name(name);
+        let exists = || json!({ "exists": { "field": field_name } });
+        let required_field_names = || {
+            if nullable {
+                vec![field_name.clone()]
+            } else {
+                Vec::new()
+            }
+        };
+        match (comparator, value) {
+            // Presence and null checks apply to the field as a whole
+            (FilterComparator::Has, Value::Any) | (FilterComparator::NotEqual, Value::Null) => {
+                Ok(exists())
+            }
+            (FilterComparator::Equal, Value::Null) => {
+                Ok(get_bool_query("must_not", vec![exists()]))
+            }
+            // Arrays match if any element matches, which cannot be negated per element
+            (FilterComparator::NotEqual, _) if repeated => {
+                Err(FilterError::UnsuitableComparator(comparator))
+            }
+            (FilterComparator::Equal | FilterComparator::NotEqual, Value::String(value))
+                if split_wildcard(value).is_some() =>
+            {
+                let (leading, literal, trailing) = split_wildcard(value).unwrap();
+                let query = get_wildcard_query(
+                    field_name.clone(),
+                    format!(
+                        "{}{}{}",
+                        if leading { "*" } else { "" },
+                        escape_wildcard_pattern(literal),
+                        if trailing { "*" } else { "" },
+                    ),
+                );
+                Ok(if comparator == FilterComparator::NotEqual {
+                    get_must_not_query(vec![query], required_field_names())
+                } else {
+                    query
+                })
+            }
+            (FilterComparator::Has, Value::String(value)) if !repeated => Ok(get_wildcard_query(
+                field_name.clone(),
+                format!("*{}*", escape_wildcard_pattern(value)),
+            )),
+            (FilterComparator::Equal | FilterComparator::Has, value) => Ok(get_term_query(
+                field_name.clone(),
+                Self::build_value(value, field)?,
+            )),
+            (FilterComparator::NotEqual, value) => Ok(get_must_not_query(
+                vec![get_term_query(
+                    field_name.clone(),
+                    Self::build_value(value, field)?,
+                )],
+                required_field_names(),
+            )),
+            (comparator, value) => {
+                let operator = match comparator {
+                    FilterComparator::Less => "lt",
+                    FilterComparator::LessOrEqual => "lte",
+                    FilterComparator::Greater => "gt",
+                    _ => "gte",
+                };
+                Ok(get_document(
+                    "range",
+                    get_document(
+                        field_name.clone(),
+                        get_document(operator, Self::build_value(value, field)?),
+                    ),
+                ))
+            }
+        }
+    }
+
+    fn build_map_key_presence(&self, name: &str, key: &Filter) -> FilterResult<JsonValue> {
+        if let Some((parts, disjunction)) = key.get_composite_argument() {
+            let clauses = parts
+                .iter()
+                .map(|part| self.build_map_key_presence(name, part))
+                .collect::<FilterResult<Vec<_>>>()?;
+            return Ok(if disjunction {
+                get_should_query(clauses)
+            } else {
+                get_bool_query("filter", clauses)
+            });
+        }
+
+        let field_name = self.get_field_name(name);
+        let field_name = match key {
+            // Presence of the map itself
+            Filter::Value(Value::Any) => field_name,
+            Filter::Name(key) | Filter::Value(Value::String(key)) => format!("{field_name}.{key}"),
+            Filter::Value(key) => format!("{field_name}.{key}"),
+            _ => return Err(FilterError::InvalidResultValueType),
+        };
+        Ok(json!({ "exists": { "field": field_name } }))
+    }
+
+    /// Builds a script query comparing expressions, such as members compared with each other.
+    fn build_script(
+        &self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<JsonValue> {
+        let operator = match comparator {
+            FilterComparator::Less => "<",
+            FilterComparator::LessOrEqual => "<=",
+            FilterComparator::Greater => ">",
+            FilterComparator::GreaterOrEqual => ">=",
+            FilterComparator::Equal => "==",
+            FilterComparator::NotEqual => "!=",
+            FilterComparator::Has => return Err(FilterError::UnsuitableComparator(comparator)),
+        };
+        let mut params = Map::new();
+        let source = format!(
+            "{} {operator} {}",
+            self.build_script_operand(comparable, &mut params)?,
+            self.build_script_operand(argument, &mut params)?,
+        );
+        Ok(get_document(
+            "script",
+            get_document("script", json!({ "source": source, "params": params })),
+        ))
+    }
+
+    fn build_script_operand(
+        &self,
+        operand: &Filter,
+        params: &mut Map<String, JsonValue>,
+    ) -> FilterResult<String> {
+        match operand {
+            Filter::Name(name) => Ok(format!("doc['{}'].value", self.get_field_name(name))),
+            // Literals are passed as parameters
+            Filter::Value(value) => {
+                let param = format!("p{}", params.len());
+                params.insert(param.clone(), Self::build_value(value, None)?);
+                Ok(format!("params.{param}"))
+            }
+            Filter::Composite(tree) => self.build_script_operand(tree, params),
+            _ => Err(FilterError::InvalidResultValueType),
+        }
+    }
+
+    fn build_sort(&self, ordering: &Ordering) -> Vec<JsonValue> {
+        ordering
+            .iter()
+            .map(|term| {
+                get_document(
+                    self.get_field_name(&term.name),
+                    match term.direction {
+                        OrderingDirection::Ascending => "asc",
+                        OrderingDirection::Descending => "desc",
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Converts a literal to the type of the field, and enums to their numeric values, if any.
+    fn build_value(value: &Value, field: Option<&FieldMemberSchema>) -> FilterResult<JsonValue> {
+        let value = match field {
+            Some(field)
+                if value.value_type().is_some_and(|literal_type| {
+                    literal_type != field.value_type
+                        && literal_type.is_literal_coercible(field.value_type)
+                }) =>
+            {
+                value.coerce_literal(field.value_type)?
+            }
+            _ => value.clone(),
+        };
+        Ok(match value {
+            Value::Enum(name) => field
+                .and_then(|field| field.enum_schema.as_ref())
+                .and_then(|enum_schema| enum_schema.get_number(&name))
+                .map_or_else(|| name.into(), Into::into),
+            value => get_json_value(value)?,
+        })
+    }
+
+    fn get_field_name(&self, name: &str) -> String {
+        if let Some((map_name, key, _)) = self.schema.split_map_entry(name) {
+            return format!("{}.{key}", self.get_field_name(map_name));
+        }
+        self.rename_map
+            .map_or_else(|| name.into(), |rename_map| rename_map.rename_member(name))
+    }
+}
+
+fn get_document<K, V>(key: K, value: V) -> JsonValue
+where
+    K: Into<String>,
+    V: Into<JsonValue>,
+{
+    let mut document = Map::new();
+    document.insert(key.into(), value.into());
+    document.into()
+}
+
+fn get_bool_query(occurrence: &str, clauses: Vec<JsonValue>) -> JsonValue {
+    get_document("bool", get_document(occurrence, clauses))
+}
+
+/// Gets a query matching documents that match none of the clauses, and have all of the fields.
+fn get_must_not_query(clauses: Vec<JsonValue>, field_names: Vec<String>) -> JsonValue {
+    if field_names.is_empty() {
+        return get_bool_query("must_not", clauses);
+    }
+    let exists: Vec<_> = field_names
+        .into_iter()
+        .map(|field_name| json!({ "exists": { "field": field_name } }))
+        .collect();
+    json!({ "bool": { "must_not": clauses, "filter": exists } })
+}
+
+fn get_should_query(clauses: Vec<JsonValue>) -> JsonValue {
+    json!({ "bool": { "should": clauses, "minimum_should_match": 1 } })
+}
+
+fn get_term_query<V: Into<JsonValue>>(field_name: String, value: V) -> JsonValue {
+    get_document("term", get_document(field_name, value))
+}
+
+fn get_wildcard_query(field_name: String, pattern: String) -> JsonValue {
+    get_document(
+        "wildcard",
+        get_document(field_name, json!({ "value": pattern })),
+    )
+}
+
+/// Escapes wildcard query patterns, using backslash as the escape character.
+fn escape_wildcard_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '*' | '?') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn get_json_value(value: Value) -> FilterResult<JsonValue> {
+    Ok(match value {
+        Value::Integer(value) => value.into(),
+        Value::Float(value) => value.into(),
+        Value::Boolean(value) => value.into(),
+        Value::String(value) | Value::Enum(value) => value.into(),
+        Value::Timestamp(value) => value.format_rfc3339().unwrap().into(),
+        // Durations are stored as nanoseconds
+        Value::Duration(value) => duration_nanos_i64(&value)?.into(),
+        Value::Repeated(values) => values
+            .into_iter()
+            .map(get_json_value)
+            .collect::<FilterResult<_>>()?,
+        Value::Map(entries) => entries
+            .into_iter()
+            .map(|(key, value)| Ok((key, get_json_value(value)?)))
+            .collect::<FilterResult<Map<_, _>>>()?
+            .into(),
+        Value::Null | Value::Any => JsonValue::Null,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+    use bomboni_proto::google::protobuf::Duration;
+
+    use crate::{
+        ordering::error::OrderingError,
+        query::{
+            memory::MemoryQueryExecutor,
+            page_token::{FilterPageToken, PageTokenBuilder, plain::PlainPageTokenBuilder},
+        },
+        schema::{EnumSchema, MapMemberSchema, ResourceMemberSchema, ValueType},
+        testing::schema::UserItem,
+    };
+
+    use super::*;
+
+    fn get_query(query: &str, filter: &str, ordering: &str) -> SearchQuery {
+        SearchQuery {
+            query: query.into(),
+            page_size: 20,
+            page_token: None,
+            filter: Filter::parse(filter).unwrap(),
+            ordering: Ordering::parse(ordering).unwrap(),
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        let schema = UserItem::get_schema();
+        let mut builder = ElasticQueryBuilder::new(&schema);
+        builder.set_query_fields(["displayName^2", "bio"]);
+
+        let mut query = get_query(
+            "john",
+            r#"age >= 18 AND displayName = "J*""#,
+            "age desc, id",
+        );
+        query.page_token = Some(FilterPageToken::new(
+            Filter::parse(r#"age < 30 OR (age = 30 AND id >= "42")"#).unwrap(),
+        ));
+        assert_eq!(
+            builder.build(&query).unwrap(),
+            json!({
+                "query": {"bool": {
+                    "must": [{"multi_match": {"query": "john", "fields": ["displayName^2", "bio"]}}],
+                    "filter": [
+                        {"range": {"age": {"gte": 18}}},
+                        {"wildcard": {"displayName": {"value": "J*"}}},
+                        {"bool": {"should": [
+                            {"range": {"age": {"lt": 30}}},
+                            {"bool": {"filter": [
+                                {"term": {"age": 30}},
+                                {"range": {"id": {"gte": "42"}}},
+                            ]}},
+                        ], "minimum_should_match": 1}},
+                    ],
+                }},
+                "size": 20,
+                "sort": [{"age": "desc"}, {"id": "asc"}],
+            })
+        );
+
+        assert_eq!(
+            ElasticQueryBuilder::new(&schema)
+                .build(&get_query("", "", ""))
+                .unwrap(),
+            json!({"query": {"match_all": {}}, "size": 20})
+        );
+
+        query.page_token = Some(FilterPageToken::new(Filter::parse("lol <= 30").unwrap()));
+        assert_eq!(
+            builder.build(&query).unwrap_err(),
+            QueryError::InvalidPageToken
+        );
+        assert_eq!(
+            builder.build(&get_query("", "", "lol")).unwrap_err(),
+            QueryError::OrderingError {
+                error: OrderingError::UnknownMember("lol".into()),
+                span: None,
+            }
+        );
+        assert_eq!(
+            builder.build(&get_query("", "f(age)", "")).unwrap_err(),
+            QueryError::FilterError {
+                error: FilterError::UnknownFunction("f".into()),
+                span: None,
+            }
+        );
+    }
+
+    #[test]
+    fn page_tokens() {
+        let schema = UserItem::get_schema();
+        let builder = ElasticQueryBuilder::new(&schema);
+        let page_token_builder = PlainPageTokenBuilder {};
+        let executor = MemoryQueryExecutor::new(&schema, &page_token_builder);
+        let items: Vec<_> = [
+            ("5", 30),
+            ("1", 20),
+            ("3", 10),
+            ("7", 40),
+            ("2", 20),
+            ("8", 30),
+        ]
+        .into_iter()
+        .map(|(id, age)| UserItem {
+            id: id.into(),
+            display_name: String::new(),
+            age,
+        })
+        .collect();
+        let mut query = get_query("", "", "age desc, id");
+        query.page_size = 2;
+
+        let first_page = executor.execute_search(&query, &items).unwrap();
+        assert_eq!(get_ids(&first_page.items), ["7", "5"]);
+        query.page_token = Some(
+            page_token_builder
+                .parse(
+                    &query.filter,
+                    &query.ordering,
+                    &[],
+                    &first_page.next_page_token.unwrap(),
+                )
+                .unwrap(),
+        );
+        let second_page = executor.execute_search(&query, &items).unwrap();
+        assert_eq!(get_ids(&second_page.items), ["8", "1"]);
+
+        // Page filter includes the first item of the page, and items with smaller IDs after it
+        let body = builder.build(&query).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "query": {"bool": {"filter": [
+                    {"bool": {"should": [
+                        {"range": {"age": {"lt": 30}}},
+                        {"bool": {"filter": [
+                            {"term": {"age": 30}},
+                            {"range": {"id": {"gte": "8"}}},
+                        ]}},
+                    ], "minimum_should_match": 1}},
+                ]}},
+                "size": 2,
+                "sort": [{"age": "desc"}, {"id": "asc"}],
+            })
+        );
+        let page_filter = &query.page_token.as_ref().unwrap().filter;
+        let mut matched: Vec<_> = items
+            .iter()
+            .filter(|item| page_filter.evaluate(*item) == Some(Value::Boolean(true)))
+            .collect();
+        matched.sort_by(|a, b| query.ordering.evaluate(*a, *b).unwrap());
+        matched.truncate(2);
+        assert_eq!(get_ids(&matched), get_ids(&second_page.items));
+    }
+
+    fn get_ids<'a>(items: &[&'a UserItem]) -> Vec<&'a str> {
+        items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn nulls() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema::new_nullable(ValueType::String),
+                "priority" => FieldMemberSchema {
+                    ordered: true,
+                    ..FieldMemberSchema::new_nullable(ValueType::Integer)
+                },
+                "deleted" => FieldMemberSchema::new(ValueType::Boolean),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    ElasticQueryBuilder::new(&schema)
+                        .build_filter(&Filter::parse($filter).unwrap())
+                        .unwrap(),
+                    $expected,
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        // Restrictions of null fields match neither themselves nor their negations
+        check!(
+            "priority != 1",
+            json!({"bool": {
+                "must_not": [{"term": {"priority": 1}}],
+                "filter": [{"exists": {"field": "priority"}}],
+            }})
+        );
+        check!(
+            r#"title != "a*""#,
+            json!({"bool": {
+                "must_not": [{"wildcard": {"title": {"value": "a*"}}}],
+                "filter": [{"exists": {"field": "title"}}],
+            }})
+        );
+        check!(
+            "NOT (priority > 1 AND deleted)",
+            json!({"bool": {"should": [
+                {"bool": {
+                    "must_not": [{"range": {"priority": {"gt": 1}}}],
+                    "filter": [{"exists": {"field": "priority"}}],
+                }},
+                {"bool": {"must_not": [{"term": {"deleted": true}}]}},
+            ], "minimum_should_match": 1}})
+        );
+        // Presence and null checks are never unknown
+        check!(
+            "NOT priority:*",
+            json!({"bool": {"must_not": [{"exists": {"field": "priority"}}]}})
+        );
+    }
+
+    #[test]
+    fn filters() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema::new(ValueType::String),
+                "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                "tags" => FieldMemberSchema::new_repeated(ValueType::String),
+                "state" => FieldMemberSchema::new_enum(EnumSchema::new_numbered([("DRAFT", 1), ("PUBLISHED", 2)])),
+                "deleteTime" => FieldMemberSchema::new_nullable(ValueType::Timestamp),
+                "timeout" => FieldMemberSchema::new_ordered(ValueType::Duration),
+                "labels" => MapMemberSchema::new(ValueType::String, ValueType::String),
+                "authors" => ResourceMemberSchema::new_repeated(btree_map_into! {
+                    "country" => FieldMemberSchema::new(ValueType::String),
+                }),
+            },
+        };
+        let rename_map = SqlRenameMap {
+            members: btree_map_into! {
+                "title" => "name",
+            },
+            ..Default::default()
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                let filter = Filter::parse($filter).unwrap().resolve(&schema);
+                filter.validate(&schema, None).unwrap();
+                assert_eq!(
+                    ElasticQueryBuilder::new(&schema)
+                        .set_rename_map(&rename_map)
+                        .build_filter(&filter)
+                        .unwrap(),
+                    $expected,
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        check!(
+            r#"NOT title = "a" OR age < 10"#,
+            json!({"bool": {"should": [
+                {"bool": {"must_not": [{"term": {"name": "a"}}]}},
+                {"range": {"age": {"lt": 10}}},
+            ], "minimum_should_match": 1}})
+        );
+        check!(
+            r#"title:"a*b""#,
+            json!({"wildcard": {"name": {"value": "*a\\*b*"}}})
+        );
+        check!(r#"tags:"x""#, json!({"term": {"tags": "x"}}));
+        check!(
+            "timeout < 1.5s",
+            json!({"range": {"timeout": {"lt": 1_500_000_000}}})
+        );
+        assert_eq!(
+            ElasticQueryBuilder::new(&schema)
+                .build_filter(&Filter::parse("timeout < 3000000h").unwrap())
+                .unwrap_err(),
+            FilterError::InvalidDuration(Duration::new(10_800_000_000, 0).to_string())
+        );
+        check!(
+            "state = (DRAFT OR PUBLISHED)",
+            json!({"terms": {"state": [1, 2]}})
+        );
+        check!(
+            "age > (1 AND 2)",
+            json!({"bool": {"filter": [
+                {"range": {"age": {"gt": 1}}},
+                {"range": {"age": {"gt": 2}}},
+            ]}})
+        );
+        check!("deleteTime:*", json!({"exists": {"field": "deleteTime"}}));
+        check!(
+            "deleteTime = null",
+            json!({"bool": {"must_not": [{"exists": {"field": "deleteTime"}}]}})
+        );
+        check!(
+            r#"deleteTime < "2024-01-01T00:00:00Z""#,
+            json!({"range": {"deleteTime": {"lt": "2024-01-01T00:00:00Z"}}})
+        );
+        check!("labels:env", json!({"exists": {"field": "labels.env"}}));
+        check!(
+            r#"labels.env = "prod""#,
+            json!({"term": {"labels.env": "prod"}})
+        );
+        check!(
+            r#"authors.country = "SI""#,
+            json!({"nested": {
+                "path": "authors",
+                "query": {"term": {"authors.country": "SI"}},
+            }})
+        );
+
+        assert_eq!(
+            ElasticQueryBuilder::new(&schema)
+                .build_filter(&Filter::parse(r#"tags != "x""#).unwrap())
+                .unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::NotEqual)
+        );
+        assert_eq!(
+            ElasticQueryBuilder::new(&schema)
+                .build_filter(&Filter::parse("age < title").unwrap())
+                .unwrap(),
+            json!({"script": {"script": {
+                "source": "doc['age'].value < doc['title'].value",
+                "params": {},
+            }}})
+        );
+    }
+}
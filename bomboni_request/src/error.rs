@@ -692,6 +692,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn query_error_span() {
+        use crate::{query::utility::parse_query_filter, testing::schema::UserItem};
+
+        let error = parse_query_filter(
+            Some("age > 30 AND lol = 2"),
+            &UserItem::get_schema(),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(
+            RequestError::from(error)
+                .wrap_request("List")
+                .details()
+                .remove(0)
+                .to_msg::<BadRequest>()
+                .unwrap(),
+            BadRequest {
+                field_violations: vec![FieldViolation {
+                    field: "filter".into(),
+                    description: "filter error: unknown filter member `lol` at column 14".into(),
+                }]
+            }
+        );
+    }
+
     #[test]
     fn field_paths() {
         assert_eq!(
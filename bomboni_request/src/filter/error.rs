@@ -4,6 +4,7 @@ use thiserror::Error;
 use crate::{
     filter::{FilterComparator, Rule},
     schema::ValueType,
+    span::Span,
 };
 
 /// Filter parsing and evaluation errors.
@@ -23,9 +24,18 @@ pub enum FilterError {
     /// Invalid number format.
     #[error("invalid number `{0}`")]
     InvalidNumber(String),
+    /// Invalid duration format or range.
+    #[error("invalid duration `{0}`")]
+    InvalidDuration(String),
+    /// String literal compared with a timestamp is not an RFC 3339 timestamp.
+    #[error("invalid timestamp literal `{0}`")]
+    InvalidTimestamp(String),
     /// Unknown field member.
     #[error("unknown filter member `{0}`")]
     UnknownMember(String),
+    /// Member compared to null is not nullable.
+    #[error("filter member `{0}` is not nullable")]
+    NonNullableMember(String),
     /// Unknown function.
     #[error("unknown function `{0}`")]
     UnknownFunction(String),
@@ -48,17 +58,58 @@ pub enum FilterError {
         /// Actual type.
         actual: ValueType,
     },
+    /// Literal cannot be converted to the compared type exactly.
+    #[error("value `{value}` cannot be converted to `{expected}` without losing precision")]
+    PrecisionLoss {
+        /// Literal value.
+        value: String,
+        /// Compared type.
+        expected: ValueType,
+    },
     /// Incomparable value type.
     #[error("incomparable value type `{0}`")]
     IncomparableType(ValueType),
+    /// Value is not one of the enum values.
+    #[error("invalid enum value `{0}`")]
+    InvalidEnumValue(String),
     /// Unsuitable comparator.
     #[error("unsuitable comparator `{0}`")]
     UnsuitableComparator(FilterComparator),
+    /// Value cannot be bound as an argument of a query.
+    #[error("value `{0}` cannot be bound as an argument")]
+    UnsupportedArgument(String),
+    /// Error located in the filter source.
+    #[error("{error} at {span}")]
+    Spanned {
+        /// Located error.
+        error: Box<Self>,
+        /// Span of the offending part of the filter.
+        span: Span,
+    },
 }
 
 /// Filter result type.
 pub type FilterResult<T> = Result<T, FilterError>;
 
+impl FilterError {
+    /// Gets the error without its location.
+    pub fn unspanned(&self) -> &Self {
+        match self {
+            Self::Spanned { error, .. } => error.unspanned(),
+            error => error,
+        }
+    }
+
+    /// Renders the error with the offending part of the filter source underlined.
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            Self::Spanned { error, span } => span.render(source, error),
+            Self::Parse { start, end } => Span::locate(source, *start, *end).render(source, self),
+            error => error.to_string(),
+        }
+    }
+}
+
 impl From<pest::error::Error<Rule>> for FilterError {
     fn from(err: pest::error::Error<Rule>) -> Self {
         match err.location {
@@ -0,0 +1,271 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    schema::{FunctionSchema, FunctionSchemaMap, ValueType},
+    value::Value,
+};
+
+/// Filter function implementation.
+///
+/// Receives evaluated arguments and returns `None` if the function cannot be applied to them.
+pub type FilterFunction = Arc<dyn Fn(&[Value]) -> Option<Value> + Send + Sync>;
+
+/// Maximum number of compiled patterns kept by the `regex` function.
+const REGEX_CACHE_CAPACITY: usize = 64;
+
+/// Maximum size of a compiled pattern of the `regex` function, in bytes.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Registry of filter functions.
+///
+/// Pairs each [`FunctionSchema`] with a Rust implementation, so that filters containing function calls can be evaluated in memory.
+/// The same schemas can be passed to [`crate::filter::Filter::validate`] and [`crate::sql::SqlFilterBuilder`].
+#[derive(Clone, Default)]
+pub struct FunctionRegistry {
+    schemas: FunctionSchemaMap,
+    functions: BTreeMap<String, FilterFunction>,
+}
+
+impl FunctionRegistry {
+    /// Creates a new empty function registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a function registry with the standard library of functions.
+    ///
+    /// - `startsWith(string, prefix)` checks if a string starts with a prefix.
+    /// - `endsWith(string, suffix)` checks if a string ends with a suffix.
+    /// - `contains(string, substring)` checks if a string contains a substring.
+    /// - `lower(string)` converts a string to lowercase.
+    /// - `size(value)` returns the number of characters in a string, or number of elements in a repeated value.
+    /// - `regex(string, pattern)` checks if a string matches a regular expression.
+    ///   Compiled patterns are cached, and patterns that compile too large are rejected.
+    pub fn standard() -> Self {
+        let regex_cache = Arc::new(Mutex::new(RegexCache::default()));
+        let mut registry = Self::new();
+        registry
+            .register(
+                "startsWith",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::String, ValueType::String],
+                    return_value_type: ValueType::Boolean,
+                },
+                |arguments| match arguments {
+                    [Value::String(value), Value::String(prefix)] => {
+                        Some(value.starts_with(prefix.as_str()).into())
+                    }
+                    _ => None,
+                },
+            )
+            .register(
+                "endsWith",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::String, ValueType::String],
+                    return_value_type: ValueType::Boolean,
+                },
+                |arguments| match arguments {
+                    [Value::String(value), Value::String(suffix)] => {
+                        Some(value.ends_with(suffix.as_str()).into())
+                    }
+                    _ => None,
+                },
+            )
+            .register(
+                "contains",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::String, ValueType::String],
+                    return_value_type: ValueType::Boolean,
+                },
+                |arguments| match arguments {
+                    [Value::String(value), Value::String(substring)] => {
+                        Some(value.contains(substring.as_str()).into())
+                    }
+                    _ => None,
+                },
+            )
+            .register(
+                "lower",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::String],
+                    return_value_type: ValueType::String,
+                },
+                |arguments| match arguments {
+                    [Value::String(value)] => Some(value.to_lowercase().into()),
+                    _ => None,
+                },
+            )
+            .register(
+                "size",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::Any],
+                    return_value_type: ValueType::Integer,
+                },
+                |arguments| match arguments {
+                    [Value::String(value)] => {
+                        i64::try_from(value.chars().count()).ok().map(Into::into)
+                    }
+                    [Value::Repeated(values)] => i64::try_from(values.len()).ok().map(Into::into),
+                    _ => None,
+                },
+            )
+            .register(
+                "regex",
+                FunctionSchema {
+                    argument_value_types: vec![ValueType::String, ValueType::String],
+                    return_value_type: ValueType::Boolean,
+                },
+                move |arguments| match arguments {
+                    [Value::String(value), Value::String(pattern)] => regex_cache
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .get(pattern)
+                        .map(|regex| regex.is_match(value).into()),
+                    _ => None,
+                },
+            );
+        registry
+    }
+
+    /// Registers a function.
+    ///
+    /// Replaces any previously registered function with the same name.
+    pub fn register<F>(&mut self, name: &str, schema: FunctionSchema, function: F) -> &mut Self
+    where
+        F: Fn(&[Value]) -> Option<Value> + Send + Sync + 'static,
+    {
+        self.schemas.insert(name.into(), schema);
+        self.functions.insert(name.into(), Arc::new(function));
+        self
+    }
+
+    /// Gets function schema by name.
+    pub fn get_schema(&self, name: &str) -> Option<&FunctionSchema> {
+        self.schemas.get(name)
+    }
+
+    /// Gets schemas of all registered functions.
+    pub const fn schema_functions(&self) -> &FunctionSchemaMap {
+        &self.schemas
+    }
+
+    /// Calls a function with evaluated arguments.
+    ///
+    /// Returns `None` if the function is unknown, argument count doesn't match the schema,
+    /// or the function cannot be applied to the arguments.
+    pub fn call(&self, name: &str, arguments: &[Value]) -> Option<Value> {
+        let schema = self.schemas.get(name)?;
+        if schema.argument_value_types.len() != arguments.len() {
+            return None;
+        }
+        self.functions.get(name)?(arguments)
+    }
+}
+
+/// Compiled patterns of the `regex` function, including patterns that failed to compile.
+///
+/// The cache is cleared once it reaches [`REGEX_CACHE_CAPACITY`] patterns.
+#[derive(Default)]
+struct RegexCache(BTreeMap<String, Option<Regex>>);
+
+impl RegexCache {
+    fn get(&mut self, pattern: &str) -> Option<&Regex> {
+        if !self.0.contains_key(pattern) {
+            if self.0.len() >= REGEX_CACHE_CAPACITY {
+                self.0.clear();
+            }
+            let regex = RegexBuilder::new(pattern)
+                .size_limit(REGEX_SIZE_LIMIT)
+                .build()
+                .ok();
+            self.0.insert(pattern.into(), regex);
+        }
+        self.0.get(pattern)?.as_ref()
+    }
+}
+
+impl Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionRegistry")
+            .field("schemas", &self.schemas)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard() {
+        let registry = FunctionRegistry::standard();
+        assert_eq!(
+            registry.call("startsWith", &["abc".into(), "ab".into()]),
+            Some(true.into())
+        );
+        assert_eq!(
+            registry.call("endsWith", &["abc".into(), "ab".into()]),
+            Some(false.into())
+        );
+        assert_eq!(
+            registry.call("contains", &["abc".into(), "b".into()]),
+            Some(true.into())
+        );
+        assert_eq!(registry.call("lower", &["AbC".into()]), Some("abc".into()));
+        assert_eq!(registry.call("size", &["abc".into()]), Some(3.into()));
+        assert_eq!(
+            registry.call("size", &[Value::Repeated(vec![1.into(), 2.into()])]),
+            Some(2.into())
+        );
+        assert_eq!(
+            registry.call("regex", &["prod-42".into(), "^prod-[0-9]+$".into()]),
+            Some(true.into())
+        );
+        assert_eq!(registry.call("regex", &["a".into(), "(".into()]), None);
+        assert_eq!(
+            registry.call("regex", &["a".into(), "\\w{1000}{1000}".into()]),
+            None
+        );
+        assert_eq!(registry.call("lower", &[42.into()]), None);
+        assert_eq!(registry.call("lower", &[]), None);
+        assert_eq!(registry.call("upper", &["a".into()]), None);
+    }
+
+    #[test]
+    fn regex_cache() {
+        let mut cache = RegexCache::default();
+        assert!(cache.get("^a+$").is_some_and(|regex| regex.is_match("aa")));
+        assert!(cache.get("(").is_none());
+        assert_eq!(cache.0.len(), 2);
+
+        for i in 0..REGEX_CACHE_CAPACITY {
+            cache.get(&format!("^{i}$"));
+        }
+        assert!(cache.0.len() <= REGEX_CACHE_CAPACITY);
+        assert!(cache.get("^a+$").is_some());
+    }
+
+    #[test]
+    fn register() {
+        let mut registry = FunctionRegistry::new();
+        registry.register(
+            "double",
+            FunctionSchema {
+                argument_value_types: vec![ValueType::Integer],
+                return_value_type: ValueType::Integer,
+            },
+            |arguments| match arguments {
+                [Value::Integer(value)] => Some((value * 2).into()),
+                _ => None,
+            },
+        );
+        assert!(registry.get_schema("double").is_some());
+        assert_eq!(registry.call("double", &[21.into()]), Some(42.into()));
+    }
+}
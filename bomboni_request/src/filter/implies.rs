@@ -0,0 +1,466 @@
+use std::{cmp::Ordering, collections::BTreeMap, convert::Infallible};
+
+use crate::{
+    filter::{Filter, FilterComparator, split_wildcard, visit::FilterFolder},
+    schema::{FieldMemberSchema, Schema, ValueType},
+    value::Value,
+};
+
+impl Filter {
+    /// Checks if the filter logically implies another filter.
+    ///
+    /// Every item matched by this filter is then also matched by the other filter,
+    /// such that results of a broader query can be narrowed down to serve a narrower one.
+    /// Both filters are resolved against schema and normalized before being compared.
+    ///
+    /// The check is sound but incomplete: `true` guarantees the implication, while `false` means it could not be proven.
+    /// Besides conjunctions, disjunctions and negations, it understands equality and range restrictions
+    /// of literal values on schema fields, such that `age >= 21 AND age < 30` implies `age > 18`.
+    pub fn implies(&self, other: &Self, schema: &Schema) -> bool {
+        Implication { schema }.implies(&Self::prepare(self, schema), &Self::prepare(other, schema))
+    }
+
+    fn prepare(filter: &Self, schema: &Schema) -> Self {
+        let Ok(filter) = ArgumentExpander.fold_filter(filter.resolve(schema));
+        filter.normalize()
+    }
+}
+
+/// Expands composite restriction arguments, such that `a = (1 OR 2)` becomes `a = 1 OR a = 2`.
+struct ArgumentExpander;
+
+impl ArgumentExpander {
+    fn expand(comparable: &Filter, comparator: FilterComparator, argument: Filter) -> Filter {
+        let Some((parts, disjunction)) = argument.get_composite_argument() else {
+            return Filter::Restriction(
+                Box::new(comparable.clone()),
+                comparator,
+                Box::new(argument),
+            );
+        };
+        let parts = parts
+            .iter()
+            .map(|part| Self::expand(comparable, comparator, part.clone()))
+            .collect();
+        Filter::Composite(Box::new(if disjunction {
+            Filter::Disjunction(parts)
+        } else {
+            Filter::Conjunction(parts)
+        }))
+    }
+}
+
+impl FilterFolder for ArgumentExpander {
+    type Error = Infallible;
+
+    fn fold_restriction(
+        &mut self,
+        comparable: Filter,
+        comparator: FilterComparator,
+        argument: Filter,
+    ) -> Result<Filter, Self::Error> {
+        Ok(Self::expand(&comparable, comparator, argument))
+    }
+}
+
+struct Implication<'a> {
+    schema: &'a Schema,
+}
+
+impl Implication<'_> {
+    fn implies(&self, a: &Filter, b: &Filter) -> bool {
+        let (a, b) = (Self::unwrap_composite(a), Self::unwrap_composite(b));
+        if a == b {
+            return true;
+        }
+        match (a, b) {
+            (_, Filter::Conjunction(parts)) => {
+                return parts.iter().all(|part| self.implies(a, part));
+            }
+            (Filter::Value(Value::Boolean(false)), _) => return true,
+            (Filter::Disjunction(parts), _) => {
+                return parts.iter().all(|part| self.implies(part, b));
+            }
+            _ => {}
+        }
+        if let Filter::Conjunction(parts) = a
+            && parts.iter().any(|part| self.implies(part, b))
+        {
+            return true;
+        }
+        if let Filter::Disjunction(parts) = b
+            && parts.iter().any(|part| self.implies(a, part))
+        {
+            return true;
+        }
+
+        let facts = match a {
+            Filter::Conjunction(parts) => parts.as_slice(),
+            a => std::slice::from_ref(a),
+        };
+        self.is_unsatisfiable(facts) || self.implies_restriction(facts, b)
+    }
+
+    /// Checks if a conjunction of facts contradicts itself, such that it implies anything.
+    fn is_unsatisfiable(&self, facts: &[Filter]) -> bool {
+        let mut ranges = BTreeMap::<&str, FieldRange>::new();
+        for (name, field, comparator, value) in
+            facts.iter().filter_map(|fact| self.get_restriction(fact))
+        {
+            if !field.repeated {
+                ranges.entry(name).or_default().restrict(comparator, value);
+            }
+        }
+        ranges.values().any(FieldRange::is_empty)
+    }
+
+    /// Checks if a conjunction of facts implies a restriction on a field, by comparing ranges of field values.
+    fn implies_restriction(&self, facts: &[Filter], b: &Filter) -> bool {
+        let Some((name, field, comparator, value)) = self.get_restriction(b) else {
+            return false;
+        };
+        let mut restrictions = facts
+            .iter()
+            .filter_map(|fact| self.get_restriction(fact))
+            .filter(|(fact_name, ..)| *fact_name == name);
+
+        // Restrictions on repeated fields may be satisfied by different elements, so they are not combined
+        if field.repeated {
+            return !matches!(b, Filter::Negate(_))
+                && restrictions.any(|(_, _, fact_comparator, fact_value)| {
+                    let mut range = FieldRange::default();
+                    range.restrict(fact_comparator, fact_value);
+                    range.implies(comparator, &value)
+                });
+        }
+
+        let mut range = FieldRange::default();
+        let mut restricted = false;
+        for (_, _, fact_comparator, fact_value) in restrictions {
+            range.restrict(fact_comparator, fact_value);
+            restricted = true;
+        }
+        // A field without restrictions may be null, which matches neither a restriction nor its negation
+        restricted && range.implies(comparator, &value)
+    }
+
+    /// Gets a restriction of a field to a literal value, with negation applied to its comparator.
+    fn get_restriction<'f>(
+        &self,
+        filter: &'f Filter,
+    ) -> Option<(&'f str, &FieldMemberSchema, FilterComparator, Value)> {
+        let (restriction, negated) = match filter {
+            Filter::Negate(tree) => (tree.as_ref(), true),
+            filter => (filter, false),
+        };
+        let Filter::Restriction(comparable, comparator, argument) = restriction else {
+            return None;
+        };
+        let (Filter::Name(name), Filter::Value(value)) = (comparable.as_ref(), argument.as_ref())
+        else {
+            return None;
+        };
+        let field = self.schema.get_field(name)?;
+        if negated && field.repeated {
+            return None;
+        }
+
+        // Literals are compared in the type of the field, such that `2` and `1.5` are comparable on a float field
+        let value = match value {
+            Value::Null | Value::Any | Value::Repeated(_) | Value::Map(_) => return None,
+            value if field.value_type == ValueType::Any => value.clone(),
+            value => value.coerce(field.value_type)?,
+        };
+        // Floats are compared for equality within epsilon, and strings may contain wildcards
+        if matches!(
+            comparator,
+            FilterComparator::Equal | FilterComparator::NotEqual
+        ) {
+            match &value {
+                Value::Float(_) => return None,
+                Value::String(pattern) if split_wildcard(pattern).is_some() => return None,
+                _ => {}
+            }
+        }
+
+        let comparator = if negated {
+            comparator.invert()?
+        } else {
+            *comparator
+        };
+        if comparator == FilterComparator::Has {
+            return None;
+        }
+        Some((name, field, comparator, value))
+    }
+
+    fn unwrap_composite(filter: &Filter) -> &Filter {
+        match filter {
+            Filter::Composite(tree) => Self::unwrap_composite(tree),
+            filter => filter,
+        }
+    }
+}
+
+/// Range of values of a single field, with inclusive flags on bounds.
+#[derive(Debug, Default)]
+struct FieldRange {
+    lower: Option<(Value, bool)>,
+    upper: Option<(Value, bool)>,
+    equal: Vec<Value>,
+    excluded: Vec<Value>,
+}
+
+impl FieldRange {
+    fn restrict(&mut self, comparator: FilterComparator, value: Value) {
+        match comparator {
+            FilterComparator::Equal => self.equal.push(value),
+            FilterComparator::NotEqual => self.excluded.push(value),
+            FilterComparator::Less | FilterComparator::LessOrEqual => {
+                let inclusive = comparator == FilterComparator::LessOrEqual;
+                let tighter =
+                    self.upper.as_ref().is_none_or(|(upper, upper_inclusive)| {
+                        match value.partial_cmp(upper) {
+                            Some(Ordering::Less) => true,
+                            Some(Ordering::Equal) => *upper_inclusive && !inclusive,
+                            _ => false,
+                        }
+                    });
+                if tighter {
+                    self.upper = Some((value, inclusive));
+                }
+            }
+            FilterComparator::Greater | FilterComparator::GreaterOrEqual => {
+                let inclusive = comparator == FilterComparator::GreaterOrEqual;
+                let tighter =
+                    self.lower.as_ref().is_none_or(|(lower, lower_inclusive)| {
+                        match value.partial_cmp(lower) {
+                            Some(Ordering::Greater) => true,
+                            Some(Ordering::Equal) => *lower_inclusive && !inclusive,
+                            _ => false,
+                        }
+                    });
+                if tighter {
+                    self.lower = Some((value, inclusive));
+                }
+            }
+            FilterComparator::Has => {}
+        }
+    }
+
+    /// Checks if every value in range satisfies the comparison.
+    fn implies(&self, comparator: FilterComparator, value: &Value) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match comparator {
+            FilterComparator::Equal => {
+                self.equal.contains(value)
+                    || matches!(
+                        (&self.lower, &self.upper),
+                        (Some((lower, true)), Some((upper, true))) if lower == value && upper == value
+                    )
+            }
+            FilterComparator::NotEqual => {
+                self.excluded.contains(value)
+                    || self
+                        .equal
+                        .iter()
+                        .any(|equal| Self::is_distinct(equal, value))
+                    || self.is_below(value, true)
+                    || self.is_above(value, true)
+            }
+            FilterComparator::Less => self.is_below(value, false),
+            FilterComparator::LessOrEqual => self.is_below(value, true),
+            FilterComparator::Greater => self.is_above(value, false),
+            FilterComparator::GreaterOrEqual => self.is_above(value, true),
+            FilterComparator::Has => false,
+        }
+    }
+
+    /// Checks if no value can be in range.
+    fn is_empty(&self) -> bool {
+        if let Some((first, rest)) = self.equal.split_first() {
+            return rest.iter().any(|equal| Self::is_distinct(first, equal))
+                || self.excluded.contains(first)
+                || self.is_out_of_bounds(first);
+        }
+        match (&self.lower, &self.upper) {
+            (Some((lower, lower_inclusive)), Some((upper, upper_inclusive))) => {
+                match lower.partial_cmp(upper) {
+                    Some(Ordering::Greater) => true,
+                    Some(Ordering::Equal) => {
+                        !lower_inclusive || !upper_inclusive || self.excluded.contains(lower)
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the value is known to be out of bounds.
+    ///
+    /// Values that are incomparable with a bound are not known to be out of it.
+    fn is_out_of_bounds(&self, value: &Value) -> bool {
+        let below_lower =
+            self.lower
+                .as_ref()
+                .is_some_and(|(lower, inclusive)| match value.partial_cmp(lower) {
+                    Some(Ordering::Less) => true,
+                    Some(Ordering::Equal) => !inclusive,
+                    _ => false,
+                });
+        let above_upper =
+            self.upper
+                .as_ref()
+                .is_some_and(|(upper, inclusive)| match value.partial_cmp(upper) {
+                    Some(Ordering::Greater) => true,
+                    Some(Ordering::Equal) => !inclusive,
+                    _ => false,
+                });
+        below_lower || above_upper
+    }
+
+    /// Checks if all values in range are below the value, or equal to it if `or_equal` is set.
+    fn is_below(&self, value: &Value, or_equal: bool) -> bool {
+        let upper = match (self.equal.first(), &self.upper) {
+            (Some(equal), _) => (equal, true),
+            (None, Some((upper, inclusive))) => {
+                (upper, *inclusive && !self.excluded.contains(upper))
+            }
+            (None, None) => return false,
+        };
+        match upper.0.partial_cmp(value) {
+            Some(Ordering::Less) => true,
+            Some(Ordering::Equal) => or_equal || !upper.1,
+            _ => false,
+        }
+    }
+
+    /// Checks if all values in range are above the value, or equal to it if `or_equal` is set.
+    fn is_above(&self, value: &Value, or_equal: bool) -> bool {
+        let lower = match (self.equal.first(), &self.lower) {
+            (Some(equal), _) => (equal, true),
+            (None, Some((lower, inclusive))) => {
+                (lower, *inclusive && !self.excluded.contains(lower))
+            }
+            (None, None) => return false,
+        };
+        match lower.0.partial_cmp(value) {
+            Some(Ordering::Greater) => true,
+            Some(Ordering::Equal) => or_equal || !lower.1,
+            _ => false,
+        }
+    }
+
+    /// Checks if values of the same type are known to differ.
+    fn is_distinct(a: &Value, b: &Value) -> bool {
+        std::mem::discriminant(a) == std::mem::discriminant(b) && a != b
+    }
+}
+
+impl FilterComparator {
+    /// Gets the comparator that negates this one when comparing single values, if any.
+    const fn invert(self) -> Option<Self> {
+        match self {
+            Self::Less => Some(Self::GreaterOrEqual),
+            Self::LessOrEqual => Some(Self::Greater),
+            Self::Greater => Some(Self::LessOrEqual),
+            Self::GreaterOrEqual => Some(Self::Less),
+            Self::Equal => Some(Self::NotEqual),
+            Self::NotEqual => Some(Self::Equal),
+            Self::Has => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use crate::schema::FieldMemberSchema;
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                "score" => FieldMemberSchema::new_ordered(ValueType::Float),
+                "name" => FieldMemberSchema::new(ValueType::String),
+                "createTime" => FieldMemberSchema::new_ordered(ValueType::Timestamp),
+                "tags" => FieldMemberSchema::new_repeated(ValueType::String),
+                "value" => FieldMemberSchema::new_ordered(ValueType::Any),
+            },
+        };
+        macro_rules! check {
+            ($a:expr, $b:expr, $expected:expr) => {{
+                let a = Filter::parse($a).unwrap();
+                let b = Filter::parse($b).unwrap();
+                assert_eq!(a.implies(&b, &schema), $expected, "{} => {}", $a, $b);
+            }};
+        }
+
+        check!("", "", true);
+        check!("age > 18", "", true);
+        check!("", "age > 18", false);
+        check!("1 = 2", "age > 18", true);
+        check!("x AND y", "y AND x", true);
+        check!("x AND y", "x", true);
+        check!("x", "x AND y", false);
+        check!("x", "x OR y", true);
+        check!("x OR y", "x", false);
+        check!("(x OR y) AND z", "y OR x OR w", true);
+
+        check!("age > 21", "age > 18", true);
+        check!("age > 18", "age > 21", false);
+        check!("age >= 18", "age > 18", false);
+        check!("age > 18", "age >= 18", true);
+        check!("age >= 21 AND age < 30", "age > 18 AND age <= 30", true);
+        check!("age = 20", "age > 18 AND age < 30 AND age != 25", true);
+        check!("age >= 20 AND age <= 20", "age = 20", true);
+        check!("age = 20", "age = 21", false);
+        check!("age = 20 AND age = 21", "name = \"x\"", true);
+        check!("age = (20 OR 25)", "age >= 20", true);
+        check!("age = (20 OR 35)", "age < 30", false);
+        check!("age = 20 OR age = 25", "age:(20 OR 25 OR 30)", false);
+        check!("age = 20 OR age = 25", "age = (20 OR 25 OR 30)", true);
+        check!("age > 18", "age:*", false);
+        check!("score > 1.5", "score >= 1.5", true);
+        check!("score = 1.5", "score = 1.5", true);
+        check!("score = 1.5", "score <= 1.5", false);
+        check!("score = 2 AND score > 1.5", "name = \"x\"", false);
+        check!("score > 2 AND score < 1.5", "name = \"x\"", true);
+        check!("age = 20 AND age < 18.0", "name = \"x\"", true);
+        check!("age = 20 AND age > 18.0", "name = \"x\"", false);
+        check!("age = 20 AND age > 18.0", "age > 19", true);
+        check!("age = 20", "age >= 20.0", true);
+        check!("age > 18.5", "age > 18", false);
+        check!("value = 2 AND value > 1.5", "name = \"x\"", false);
+        check!("value = 2 AND value < 1", "name = \"x\"", true);
+        check!(r#"name = "John""#, r#"name != "Jane""#, true);
+        check!(r#"name = "John""#, r#"name != "J*""#, false);
+        check!(r#"name = "J*""#, r#"name < "K""#, false);
+        check!(
+            r#"createTime > "2024-01-01T00:00:00Z""#,
+            r#"createTime >= "2023-01-01T00:00:00Z""#,
+            true
+        );
+
+        check!("NOT age < 18", "age >= 18", true);
+        check!("age >= 18", "NOT age < 18", true);
+        check!("age > 21", "NOT (age < 18 OR age = 20)", true);
+        check!("NOT age = 18 AND age >= 18", "age > 18", true);
+        check!("NOT (age < 18 OR x)", "age > 10 AND NOT x", true);
+        check!("NOT age > 18", "NOT age > 21", true);
+        check!("NOT age > 21", "NOT age > 18", false);
+        check!("other > 21", "other > 18", false);
+        check!("other > 21", "other > 21", true);
+
+        check!(r#"tags > "b""#, r#"tags > "a""#, true);
+        check!(r#"tags > "b" AND tags < "a""#, r#"tags = "x""#, false);
+        check!(r#"NOT tags = "a""#, r#"tags != "a""#, false);
+    }
+}
@@ -4,6 +4,7 @@
 //!
 //! [1]: https://google.aip.dev/160
 
+use std::convert::Infallible;
 use std::fmt;
 use std::fmt::{Display, Formatter, Write};
 
@@ -11,15 +12,36 @@ use itertools::Itertools;
 use parser::{FilterParser, Rule};
 use pest::Parser;
 use pest::iterators::Pair;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::filter::error::FilterError;
-use crate::schema::{FunctionSchemaMap, MemberSchema, Schema, SchemaMapped, ValueType};
-use crate::value::Value;
+use crate::filter::function::FunctionRegistry;
+use crate::schema::{EnumSchema, FunctionSchemaMap, MemberSchema, Schema, SchemaMapped, ValueType};
+use crate::span::Span;
+use crate::value::{Value, duration_nanos};
 use error::FilterResult;
+use visit::{FilterVisitor, visit_filters};
 
 /// Filter error types.
 pub mod error;
 
+/// Filter function registry.
+pub mod function;
+
+mod implies;
+
+mod normalize;
+
+mod partial;
+
+mod resolve;
+
+/// Relevance scoring.
+pub mod score;
+
+/// Filter traversal.
+pub mod visit;
+
 #[allow(clippy::upper_case_acronyms)]
 pub(crate) mod parser {
     use pest_derive::Parser;
@@ -30,6 +52,9 @@ pub(crate) mod parser {
 }
 
 /// Filter expression.
+///
+/// Filters are serialized as objects tagged by node type,
+/// such as `{"type": "name", "name": "user.age"}`.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Filter {
     /// Logical AND of filters.
@@ -51,40 +76,143 @@ pub enum Filter {
 }
 
 /// Filter comparison operators.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Comparators are serialized as their operator, such as `"<="`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FilterComparator {
     /// Less than.
+    #[serde(rename = "<")]
     Less,
     /// Less than or equal.
+    #[serde(rename = "<=")]
     LessOrEqual,
     /// Greater than.
+    #[serde(rename = ">")]
     Greater,
     /// Greater than or equal.
+    #[serde(rename = ">=")]
     GreaterOrEqual,
     /// Equal.
+    #[serde(rename = "=")]
     Equal,
     /// Not equal.
+    #[serde(rename = "!=")]
     NotEqual,
     /// Has operator.
+    #[serde(rename = ":")]
     Has,
 }
 
+/// Serialized form of a filter node.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum FilterRef<'a> {
+    Conjunction {
+        filters: &'a [Filter],
+    },
+    Disjunction {
+        filters: &'a [Filter],
+    },
+    Negate {
+        filter: &'a Filter,
+    },
+    Restriction {
+        comparable: &'a Filter,
+        comparator: FilterComparator,
+        argument: &'a Filter,
+    },
+    Function {
+        name: &'a str,
+        arguments: &'a [Filter],
+    },
+    Composite {
+        filter: &'a Filter,
+    },
+    Name {
+        name: &'a str,
+    },
+    Value {
+        value: &'a Value,
+    },
+}
+
+/// Deserialized form of a filter node.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum FilterRepr {
+    Conjunction {
+        filters: Vec<Filter>,
+    },
+    Disjunction {
+        filters: Vec<Filter>,
+    },
+    Negate {
+        filter: Box<Filter>,
+    },
+    Restriction {
+        comparable: Box<Filter>,
+        comparator: FilterComparator,
+        argument: Box<Filter>,
+    },
+    Function {
+        name: String,
+        arguments: Vec<Filter>,
+    },
+    Composite {
+        filter: Box<Filter>,
+    },
+    Name {
+        name: String,
+    },
+    Value {
+        value: Value,
+    },
+}
+
+/// Spans of a parsed filter.
+///
+/// Children follow the structure of [`Filter`]: parts of conjunctions and disjunctions,
+/// the negated tree, comparable and argument of restrictions, function arguments, or the composite tree.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FilterSpan {
+    /// Span of the node.
+    pub span: Span,
+    /// Spans of child nodes.
+    pub children: Vec<Self>,
+}
+
 impl Filter {
     /// Parses a filter from a source string.
     ///
     /// # Errors
     ///
     /// Will return [`FilterError::Parse`] if the source string cannot be parsed as a valid filter.
+    pub fn parse(source: &str) -> FilterResult<Self> {
+        Self::parse_spanned(source).map(|(filter, _)| filter)
+    }
+
+    /// Parses a filter from a source string, along with spans of its nodes.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FilterError::Parse`] if the source string cannot be parsed as a valid filter.
     ///
     /// # Panics
     ///
     /// Will panic if parsing fails to return a valid filter tree.
-    pub fn parse(source: &str) -> FilterResult<Self> {
+    pub fn parse_spanned(source: &str) -> FilterResult<(Self, FilterSpan)> {
         let filter = FilterParser::parse(Rule::Filter, source)?.next().unwrap();
         Self::parse_tree(filter)
     }
 
-    fn parse_tree(pair: Pair<Rule>) -> FilterResult<Self> {
+    fn parse_tree(pair: Pair<Rule>) -> FilterResult<(Self, FilterSpan)> {
+        let span = {
+            let span = pair.as_span();
+            Span::locate(pair.get_input(), span.start(), span.end())
+        };
+        let node =
+            |filter: Self, children: Vec<FilterSpan>| Ok((filter, FilterSpan { span, children }));
+
         match pair.as_rule() {
             Rule::Filter | Rule::Expression => {
                 match pair
@@ -94,19 +222,25 @@ impl Filter {
                     .exactly_one()
                 {
                     Ok(inner_tree) => inner_tree,
-                    Err(inner_trees) => Ok(Self::Conjunction(inner_trees.try_collect()?)),
+                    Err(inner_trees) => {
+                        let (parts, children) =
+                            inner_trees.process_results(|trees| trees.unzip())?;
+                        node(Self::Conjunction(parts), children)
+                    }
                 }
             }
             Rule::Factor => match pair.into_inner().map(Self::parse_tree).exactly_one() {
                 Ok(inner_tree) => inner_tree,
-                Err(inner_trees) => Ok(Self::Disjunction(inner_trees.try_collect()?)),
+                Err(inner_trees) => {
+                    let (parts, children) = inner_trees.process_results(|trees| trees.unzip())?;
+                    node(Self::Disjunction(parts), children)
+                }
             },
             Rule::Term => {
                 let lexeme = pair.as_str().trim();
                 if lexeme.starts_with("NOT") || lexeme.starts_with('-') {
-                    Ok(Self::Negate(Box::new(Self::parse_tree(
-                        pair.into_inner().next().unwrap(),
-                    )?)))
+                    let (tree, tree_span) = Self::parse_tree(pair.into_inner().next().unwrap())?;
+                    node(Self::Negate(Box::new(tree)), vec![tree_span])
                 } else {
                     Self::parse_tree(pair.into_inner().next().unwrap())
                 }
@@ -126,12 +260,12 @@ impl Filter {
                             ":" => FilterComparator::Has,
                             _ => unreachable!(),
                         };
-                        let arg = inner_pairs.next().unwrap();
-                        Ok(Self::Restriction(
-                            Box::new(Self::parse_tree(comparable)?),
-                            comparator,
-                            Box::new(Self::parse_tree(arg)?),
-                        ))
+                        let (comparable, comparable_span) = Self::parse_tree(comparable)?;
+                        let (arg, arg_span) = Self::parse_tree(inner_pairs.next().unwrap())?;
+                        node(
+                            Self::Restriction(Box::new(comparable), comparator, Box::new(arg)),
+                            vec![comparable_span, arg_span],
+                        )
                     }
                     None => Self::parse_tree(comparable),
                 }
@@ -140,30 +274,38 @@ impl Filter {
             Rule::Function => {
                 let mut name = String::new();
                 let mut arguments = Vec::new();
+                let mut children = Vec::new();
                 let mut argument_list = false;
                 for pair in pair.into_inner() {
-                    if argument_list {
-                        arguments.push(Self::parse_tree(pair)?);
-                    } else if pair.as_rule() == Rule::Name {
-                        name = pair.as_str().into();
-                    } else {
-                        arguments.push(Self::parse_tree(pair)?);
+                    if argument_list || pair.as_rule() != Rule::Name {
+                        let (argument, argument_span) = Self::parse_tree(pair)?;
+                        arguments.push(argument);
+                        children.push(argument_span);
                         argument_list = true;
+                    } else {
+                        name = pair.as_str().into();
                     }
                 }
-                Ok(Self::Function(name, arguments))
-            }
-            Rule::Composite => Ok(Self::Composite(Box::new(Self::parse_tree(
-                pair.into_inner().next().unwrap(),
-            )?))),
-            Rule::Name => Ok(Self::Name(
-                pair.into_inner()
-                    .map(|identifier| identifier.as_str())
-                    .join("."),
-            )),
-            Rule::String | Rule::Boolean | Rule::Number | Rule::Any => {
-                Ok(Self::Value(Value::parse(&pair)?))
+                node(Self::Function(name, arguments), children)
             }
+            Rule::Composite => {
+                let (tree, tree_span) = Self::parse_tree(pair.into_inner().next().unwrap())?;
+                node(Self::Composite(Box::new(tree)), vec![tree_span])
+            }
+            Rule::Name => node(
+                Self::Name(
+                    pair.into_inner()
+                        .map(|identifier| identifier.as_str())
+                        .join("."),
+                ),
+                Vec::new(),
+            ),
+            Rule::String
+            | Rule::Boolean
+            | Rule::Null
+            | Rule::Duration
+            | Rule::Number
+            | Rule::Any => node(Self::Value(Value::parse(&pair)?), Vec::new()),
             _ => {
                 unreachable!("{:?}", pair);
             }
@@ -172,204 +314,259 @@ impl Filter {
 
     /// Returns the length of the filter.
     pub fn len(&self) -> usize {
+        let mut counter = LenCounter(0);
+        let Ok(()) = counter.visit_filter(self);
+        counter.0
+    }
+
+    /// Evaluates the filter against an item.
+    ///
+    /// Evaluation uses three-valued logic, where comparisons with [`Value::Null`] fields are unknown and evaluate to [`Value::Null`].
+    /// Such items should not match the filter, same as rows with `NULL` columns in SQL.
+    /// Filters containing function calls evaluate to `None`.
+    /// Use [`Filter::evaluate_with_functions`] to provide function implementations,
+    /// and [`Filter::evaluate_with_schema`] to compare numeric values of enum fields.
+    pub fn evaluate<T>(&self, item: &T) -> Option<Value>
+    where
+        T: SchemaMapped,
+    {
+        self.evaluate_with_functions(item, &FunctionRegistry::new())
+    }
+
+    /// Evaluates the filter against an item of the schema, calling functions from the registry.
+    ///
+    /// Numeric values of enum fields, such as the ones stored in databases, are compared by their symbolic names.
+    pub fn evaluate_with_schema<T>(
+        &self,
+        item: &T,
+        schema: &Schema,
+        functions: &FunctionRegistry,
+    ) -> Option<Value>
+    where
+        T: SchemaMapped,
+    {
+        self.evaluate_with_functions(&SchemaItem { schema, item }, functions)
+    }
+
+    /// Evaluates the filter against an item, calling functions from the registry.
+    pub fn evaluate_with_functions<T>(
+        &self,
+        item: &T,
+        functions: &FunctionRegistry,
+    ) -> Option<Value>
+    where
+        T: SchemaMapped,
+    {
         match self {
-            Self::Conjunction(parts) | Self::Disjunction(parts) => {
-                parts.iter().map(Self::len).sum::<usize>()
-            }
-            Self::Negate(tree) => 1usize + tree.as_ref().len(),
-            Self::Restriction(comparable, _, arg) => {
-                1usize + comparable.as_ref().len() + arg.as_ref().len()
+            Self::Conjunction(parts) => Self::evaluate_connective(
+                parts
+                    .iter()
+                    .map(|part| part.evaluate_with_functions(item, functions)),
+                false,
+            ),
+            Self::Disjunction(parts) => Self::evaluate_connective(
+                parts
+                    .iter()
+                    .map(|part| part.evaluate_with_functions(item, functions)),
+                true,
+            ),
+            Self::Negate(composite) => match composite.evaluate_with_functions(item, functions)? {
+                Value::Boolean(value) => Some(Value::Boolean(!value)),
+                Value::Null => Some(Value::Null),
+                _ => None,
+            },
+            Self::Restriction(comparable, comparator, arg) => {
+                let a = comparable.evaluate_with_functions(item, functions)?;
+                Self::evaluate_restriction(&a, *comparator, arg, item, functions)
             }
-            Self::Function(tree, arguments) => {
-                1usize + tree.len() + arguments.iter().map(Self::len).sum::<usize>()
+            Self::Composite(composite) => composite.evaluate_with_functions(item, functions),
+            Self::Value(value) => Some(value.clone()),
+            Self::Name(name) => Some(item.get_field(name)),
+            Self::Function(name, arguments) => {
+                let arguments: Vec<_> = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate_with_functions(item, functions))
+                    .collect::<Option<_>>()?;
+                functions.call(name, &arguments)
             }
-            Self::Composite(composite) => 1usize + composite.as_ref().len(),
-            _ => 1usize,
         }
     }
 
-    /// Evaluates the filter against an item.
-    pub fn evaluate<T>(&self, item: &T) -> Option<Value>
+    fn evaluate_restriction<T>(
+        a: &Value,
+        comparator: FilterComparator,
+        argument: &Self,
+        item: &T,
+        functions: &FunctionRegistry,
+    ) -> Option<Value>
     where
         T: SchemaMapped,
     {
-        match self {
-            Self::Conjunction(parts) => {
-                let mut res = true;
-                for part in parts {
-                    if let Some(Value::Boolean(part_res)) = part.evaluate(item) {
-                        if !part_res {
-                            res = false;
-                            break;
-                        }
-                    } else {
-                        return None;
-                    }
-                }
-                Some(Value::Boolean(res))
-            }
-            Self::Disjunction(parts) => {
-                let mut res = false;
-                for part in parts {
-                    if let Some(Value::Boolean(part_res)) = part.evaluate(item) {
-                        if part_res {
-                            res = true;
-                            break;
-                        }
-                    } else {
-                        return None;
-                    }
-                }
-                Some(Value::Boolean(res))
+        // Expand composite arguments, such that `a:(x OR y)` is `a:x OR a:y`
+        if let Some((parts, disjunction)) = argument.get_composite_argument() {
+            return Self::evaluate_connective(
+                parts
+                    .iter()
+                    .map(|part| Self::evaluate_restriction(a, comparator, part, item, functions)),
+                disjunction,
+            );
+        }
+
+        // Presence check
+        if comparator == FilterComparator::Has && matches!(argument, Self::Value(Value::Any)) {
+            return Some(Value::Boolean(a != &Value::Null));
+        }
+
+        // Map key presence check, such as `labels:env`
+        if let (Value::Map(entries), FilterComparator::Has) = (a, comparator) {
+            let key = match argument {
+                Self::Name(key) | Self::Value(Value::String(key)) => key.clone(),
+                Self::Value(key) => key.to_string(),
+                _ => return None,
+            };
+            return Some(Value::Boolean(entries.contains_key(&key)));
+        }
+
+        let b = argument.evaluate_with_functions(item, functions)?;
+        Self::compare_values(a, comparator, &b)
+    }
+
+    fn compare_values(a: &Value, comparator: FilterComparator, b: &Value) -> Option<Value> {
+        let res = match (a, b) {
+            // Restrictions on repeated values match if any element matches
+            (Value::Repeated(elements), b)
+                if comparator != FilterComparator::Has && !matches!(b, Value::Repeated(_)) =>
+            {
+                return Self::evaluate_connective(
+                    elements
+                        .iter()
+                        .map(|element| Self::compare_values(element, comparator, b)),
+                    true,
+                );
             }
-            Self::Negate(composite) => {
-                if let Value::Boolean(value) = composite.evaluate(item)? {
-                    Some(Value::Boolean(!value))
-                } else {
-                    None
-                }
+            (a, Value::Null) => match comparator {
+                FilterComparator::Equal => a == &Value::Null,
+                FilterComparator::NotEqual => a != &Value::Null,
+                _ => return None,
+            },
+            (Value::Null, _) => return Some(Value::Null),
+            (Value::Timestamp(_), Value::String(_)) => {
+                return Self::compare_values(a, comparator, &b.coerce(ValueType::Timestamp)?);
             }
-            Self::Restriction(comparable, comparator, arg) => {
-                let a = comparable.evaluate(item)?;
-                match a {
-                    Value::Integer(a) => {
-                        if let Value::Integer(b) = arg.evaluate(item)? {
-                            Some(
-                                match comparator {
-                                    FilterComparator::Less => a < b,
-                                    FilterComparator::LessOrEqual => a <= b,
-                                    FilterComparator::Greater => a > b,
-                                    FilterComparator::GreaterOrEqual => a >= b,
-                                    FilterComparator::Equal | FilterComparator::Has => a == b,
-                                    FilterComparator::NotEqual => a != b,
-                                }
-                                .into(),
-                            )
-                        } else {
-                            None
-                        }
-                    }
-                    Value::Float(a) => {
-                        if let Value::Float(b) = arg.evaluate(item)? {
-                            Some(
-                                match comparator {
-                                    FilterComparator::Less => a < b,
-                                    FilterComparator::LessOrEqual => a <= b,
-                                    FilterComparator::Greater => a > b,
-                                    FilterComparator::GreaterOrEqual => a >= b,
-                                    FilterComparator::Equal | FilterComparator::Has => {
-                                        (a - b).abs() < f64::EPSILON
-                                    }
-                                    FilterComparator::NotEqual => (a - b).abs() > f64::EPSILON,
-                                }
-                                .into(),
-                            )
-                        } else {
-                            None
-                        }
-                    }
-                    Value::String(a) => {
-                        if let Value::String(b) = arg.evaluate(item)? {
-                            Some(
-                                match comparator {
-                                    FilterComparator::Less => a < b,
-                                    FilterComparator::LessOrEqual => a <= b,
-                                    FilterComparator::Greater => a > b,
-                                    FilterComparator::GreaterOrEqual => a >= b,
-                                    FilterComparator::Equal => a == b,
-                                    FilterComparator::NotEqual => a != b,
-                                    FilterComparator::Has => a.contains(b.as_str()),
-                                }
-                                .into(),
-                            )
-                        } else {
-                            None
-                        }
-                    }
-                    Value::Boolean(a) => {
-                        if let Value::Boolean(b) = arg.evaluate(item)? {
-                            match comparator {
-                                FilterComparator::Equal | FilterComparator::Has => {
-                                    Some((a == b).into())
-                                }
-                                FilterComparator::NotEqual => Some((a != b).into()),
-                                _ => None,
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                    Value::Timestamp(a) => {
-                        if let Value::Timestamp(b) = arg.evaluate(item)? {
-                            Some(
-                                match comparator {
-                                    FilterComparator::Less => a < b,
-                                    FilterComparator::LessOrEqual => a <= b,
-                                    FilterComparator::Greater => a > b,
-                                    FilterComparator::GreaterOrEqual => a >= b,
-                                    FilterComparator::Equal | FilterComparator::Has => a == b,
-                                    FilterComparator::NotEqual => a != b,
-                                }
-                                .into(),
-                            )
-                        } else {
-                            None
-                        }
-                    }
-                    Value::Repeated(a) => match comparator {
-                        FilterComparator::Equal => {
-                            if let Value::Repeated(b) = arg.evaluate(item)? {
-                                Some((a == b).into())
-                            } else {
-                                None
-                            }
-                        }
-                        FilterComparator::NotEqual => {
-                            if let Value::Repeated(b) = arg.evaluate(item)? {
-                                Some((a != b).into())
-                            } else {
-                                None
-                            }
-                        }
-                        FilterComparator::Has => arg.evaluate(item).map_or_else(
-                            || {
-                                if let Self::Composite(composite) = &**arg {
-                                    match composite.as_ref() {
-                                        Self::Conjunction(parts) => Some(Value::Boolean(
-                                            parts.iter().map(|part| part.evaluate(item)).all(
-                                                |value| {
-                                                    value
-                                                        .as_ref()
-                                                        .is_some_and(|value| a.contains(value))
-                                                },
-                                            ),
-                                        )),
-                                        Self::Disjunction(parts) => Some(Value::Boolean(
-                                            parts.iter().map(|part| part.evaluate(item)).any(
-                                                |value| {
-                                                    value
-                                                        .as_ref()
-                                                        .is_some_and(|value| a.contains(value))
-                                                },
-                                            ),
-                                        )),
-                                        _ => None,
-                                    }
-                                } else {
-                                    None
-                                }
-                            },
-                            |b| Some(a.contains(&b).into()),
-                        ),
-                        _ => None,
-                    },
-                    Value::Any => Some(Value::Boolean(true)),
+            // Integers are compared with floats as floats
+            (Value::Integer(_), Value::Float(_)) | (Value::Float(_), Value::Integer(_)) => {
+                return Self::compare_values(
+                    &a.coerce(ValueType::Float)?,
+                    comparator,
+                    &b.coerce(ValueType::Float)?,
+                );
+            }
+            (Value::Any, _) => true,
+            (Value::Integer(a), Value::Integer(b)) => Self::compare_ordered(comparator, a, b),
+            (Value::Float(a), Value::Float(b)) => match comparator {
+                FilterComparator::Equal | FilterComparator::Has => (a - b).abs() < f64::EPSILON,
+                FilterComparator::NotEqual => (a - b).abs() > f64::EPSILON,
+                _ => Self::compare_ordered(comparator, a, b),
+            },
+            (Value::String(a), Value::String(b)) => match comparator {
+                FilterComparator::Has => a.contains(b.as_str()),
+                FilterComparator::Equal => matches_wildcard(a, b),
+                FilterComparator::NotEqual => !matches_wildcard(a, b),
+                _ => Self::compare_ordered(comparator, a, b),
+            },
+            (Value::Boolean(a), Value::Boolean(b)) => match comparator {
+                FilterComparator::Equal | FilterComparator::Has => a == b,
+                FilterComparator::NotEqual => a != b,
+                _ => return None,
+            },
+            (Value::Timestamp(a), Value::Timestamp(b)) => Self::compare_ordered(comparator, a, b),
+            (Value::Duration(a), Value::Duration(b)) => {
+                Self::compare_ordered(comparator, &duration_nanos(a), &duration_nanos(b))
+            }
+            (Value::Enum(a) | Value::String(a), Value::Enum(b))
+            | (Value::Enum(a), Value::String(b)) => match comparator {
+                FilterComparator::Equal | FilterComparator::Has => a == b,
+                FilterComparator::NotEqual => a != b,
+                _ => return None,
+            },
+            (Value::Repeated(a), Value::Repeated(b)) => match comparator {
+                FilterComparator::Equal => a == b,
+                FilterComparator::NotEqual => a != b,
+                _ => return None,
+            },
+            (Value::Repeated(a), b) if comparator == FilterComparator::Has => a.contains(b),
+            _ => return None,
+        };
+        Some(Value::Boolean(res))
+    }
+
+    /// Combines results of conjunction or disjunction parts using three-valued logic.
+    fn evaluate_connective<I>(results: I, disjunction: bool) -> Option<Value>
+    where
+        I: IntoIterator<Item = Option<Value>>,
+    {
+        let mut unknown = false;
+        for result in results {
+            match result? {
+                Value::Boolean(value) if value == disjunction => {
+                    return Some(Value::Boolean(disjunction));
                 }
+                Value::Boolean(_) => {}
+                Value::Null => unknown = true,
+                _ => return None,
             }
-            Self::Composite(composite) => composite.evaluate(item),
-            Self::Value(value) => Some(value.clone()),
-            Self::Name(name) => Some(item.get_field(name)),
-            Self::Function(_, _) => unimplemented!("evaluate {:?}", self),
+        }
+        Some(if unknown {
+            Value::Null
+        } else {
+            Value::Boolean(!disjunction)
+        })
+    }
+
+    fn compare_ordered<V: PartialOrd>(comparator: FilterComparator, a: &V, b: &V) -> bool {
+        match comparator {
+            FilterComparator::Less => a < b,
+            FilterComparator::LessOrEqual => a <= b,
+            FilterComparator::Greater => a > b,
+            FilterComparator::GreaterOrEqual => a >= b,
+            FilterComparator::Equal | FilterComparator::Has => a == b,
+            FilterComparator::NotEqual => a != b,
+        }
+    }
+
+    /// Checks if an argument can be compared with a comparable of the value type.
+    ///
+    /// Types are comparable if they promote to a common type,
+    /// or if the argument consists of literals that can be converted to the comparable type.
+    pub(crate) fn is_comparable_argument(
+        &self,
+        argument_type: ValueType,
+        comparable_type: ValueType,
+    ) -> bool {
+        comparable_type.promote(argument_type).is_some()
+            || (argument_type.is_literal_coercible(comparable_type) && self.is_literal_argument())
+    }
+
+    fn is_literal_argument(&self) -> bool {
+        match self {
+            Self::Value(_) => true,
+            _ => self
+                .get_composite_argument()
+                .is_some_and(|(parts, _)| parts.iter().all(Self::is_literal_argument)),
+        }
+    }
+
+    /// Gets parts of a composite restriction argument, such as `("a" OR "b")`.
+    /// The second element is `true` if the parts form a disjunction.
+    pub(crate) fn get_composite_argument(&self) -> Option<(&[Self], bool)> {
+        if let Self::Composite(composite) = self {
+            match composite.as_ref() {
+                Self::Conjunction(parts) if !parts.is_empty() => Some((parts, false)),
+                Self::Disjunction(parts) if !parts.is_empty() => Some((parts, true)),
+                _ => None,
+            }
+        } else {
+            None
         }
     }
 
@@ -386,10 +583,26 @@ impl Filter {
         schema_functions: Option<&FunctionSchemaMap>,
     ) -> FilterResult<ValueType> {
         match self {
-            Self::Conjunction(_)
-            | Self::Disjunction(_)
-            | Self::Negate(_)
-            | Self::Restriction(_, _, _) => Ok(ValueType::Boolean),
+            Self::Conjunction(parts) | Self::Disjunction(parts) => {
+                // Parts of composite arguments, such as `("a" OR "b")`, share the same type
+                let mut value_type = ValueType::Boolean;
+                for (i, part) in parts.iter().enumerate() {
+                    let part_type = part.get_result_value_type(schema, schema_functions)?;
+                    if i == 0 {
+                        value_type = part_type;
+                    } else {
+                        value_type =
+                            value_type
+                                .promote(part_type)
+                                .ok_or(FilterError::InvalidType {
+                                    actual: part_type,
+                                    expected: value_type,
+                                })?;
+                    }
+                }
+                Ok(value_type)
+            }
+            Self::Negate(_) | Self::Restriction(_, _, _) => Ok(ValueType::Boolean),
             Self::Function(name, _) => Ok(schema_functions
                 .and_then(|schema_functions| schema_functions.get(name))
                 .ok_or_else(|| FilterError::UnknownFunction(name.clone()))?
@@ -420,91 +633,43 @@ impl Filter {
     /// Will return [`FilterError::FunctionInvalidArgumentCount`] if function argument count doesn't match schema.
     /// Will return [`FilterError::UnknownMember`] if the filter contains an unknown field name.
     /// Will return [`FilterError::UnsuitableComparator`] if an unsuitable comparator is used.
+    /// Will return [`FilterError::NonNullableMember`] if a member that is not nullable is compared to null.
+    /// Will return [`FilterError::PrecisionLoss`] if a numeric literal cannot be converted to the compared type exactly.
+    /// Will return [`FilterError::InvalidTimestamp`] if a string literal compared with a timestamp is not an RFC 3339 timestamp.
     pub fn validate(
         &self,
         schema: &Schema,
         schema_functions: Option<&FunctionSchemaMap>,
     ) -> FilterResult<()> {
-        match self {
-            Self::Conjunction(parts) | Self::Disjunction(parts) => {
-                for part in parts {
-                    part.validate(schema, schema_functions)?;
-                    let part_value_type = part.get_result_value_type(schema, schema_functions)?;
-                    if part_value_type != ValueType::Boolean {
-                        return Err(FilterError::InvalidType {
-                            actual: part_value_type,
-                            expected: ValueType::Boolean,
-                        });
-                    }
-                }
-            }
-            Self::Negate(tree) => {
-                let result_value_type = tree.get_result_value_type(schema, schema_functions)?;
-                if result_value_type != ValueType::Boolean {
-                    return Err(FilterError::InvalidType {
-                        actual: result_value_type,
-                        expected: ValueType::Boolean,
-                    });
-                }
-            }
-            Self::Restriction(comparable, comparator, argument) => {
-                comparable.validate(schema, schema_functions)?;
-                let comparable_type = comparable.get_result_value_type(schema, schema_functions)?;
-                argument.validate(schema, schema_functions)?;
-                let argument_type = argument.get_result_value_type(schema, schema_functions)?;
-
-                if comparator == &FilterComparator::Has {
-                    return Err(FilterError::UnsuitableComparator(*comparator));
-                    //     if !matches!(**argument, Filter::Conjunction(_) | Filter::Disjunction(_)) {
-                    //         if comparable_type != argument_type && argument_type != ValueType::Any {
-                    //             return Err(FilterError::InvalidType {
-                    //                 actual: argument_type,
-                    //                 expected: comparable_type,
-                    //             });
-                    //         }
-                    //     }
-                }
+        SchemaValidator {
+            schema,
+            schema_functions,
+            span: None,
+            enum_schema: None,
+        }
+        .visit_filter(self)
+    }
 
-                if comparable_type != argument_type {
-                    return Err(FilterError::InvalidType {
-                        actual: argument_type,
-                        expected: comparable_type,
-                    });
-                }
-            }
-            Self::Function(name, arguments) => {
-                let function = schema_functions
-                    .and_then(|schema_functions| schema_functions.get(name))
-                    .ok_or_else(|| FilterError::UnknownFunction(name.clone()))?;
-                if function.argument_value_types.len() != arguments.len() {
-                    return Err(FilterError::FunctionInvalidArgumentCount {
-                        name: name.clone(),
-                        expected: function.argument_value_types.len(),
-                    });
-                }
-                for (argument, argument_schema) in
-                    arguments.iter().zip(&function.argument_value_types)
-                {
-                    argument.validate(schema, schema_functions)?;
-                    let argument_value_type =
-                        argument.get_result_value_type(schema, schema_functions)?;
-                    if argument_value_type != *argument_schema {
-                        return Err(FilterError::InvalidType {
-                            actual: argument_value_type,
-                            expected: *argument_schema,
-                        });
-                    }
-                }
-            }
-            Self::Composite(composite) => composite.validate(schema, schema_functions)?,
-            Self::Name(name) => {
-                if schema.get_member(name).is_none() {
-                    return Err(FilterError::UnknownMember(name.clone()));
-                }
-            }
-            Self::Value(_) => {}
+    /// Validates filter against schema, attaching the span of the offending node to errors.
+    ///
+    /// Spans are obtained from [`Filter::parse_spanned`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FilterError::Spanned`] wrapping any of the errors returned by [`Filter::validate`].
+    pub fn validate_spanned(
+        &self,
+        span: &FilterSpan,
+        schema: &Schema,
+        schema_functions: Option<&FunctionSchemaMap>,
+    ) -> FilterResult<()> {
+        SchemaValidator {
+            schema,
+            schema_functions,
+            span: Some(span),
+            enum_schema: None,
         }
-        Ok(())
+        .visit_filter(self)
     }
 
     /// Checks if the filter is empty.
@@ -537,108 +702,586 @@ impl Filter {
     }
 }
 
-impl Default for Filter {
-    fn default() -> Self {
-        Self::Conjunction(Vec::new())
-    }
+/// Counts nodes of a filter, excluding conjunctions and disjunctions.
+/// Item with numeric values of enum fields mapped to their symbolic names.
+struct SchemaItem<'a, T> {
+    schema: &'a Schema,
+    item: &'a T,
 }
 
-impl Display for Filter {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Conjunction(parts) => parts.iter().map(ToString::to_string).join(" AND ").fmt(f),
-            Self::Disjunction(parts) => parts.iter().map(ToString::to_string).join(" OR ").fmt(f),
-            Self::Negate(tree) => {
-                f.write_str("NOT ")?;
-                tree.fmt(f)
-            }
-            Self::Restriction(comparable, comparator, arg) => match comparator {
-                FilterComparator::Has => {
-                    comparable.fmt(f)?;
-                    f.write_char(':')?;
-                    arg.fmt(f)
-                }
-                _ => {
-                    write!(f, "{comparable} {comparator} {arg}")
-                }
-            },
-            Self::Function(name, arguments) => {
-                write!(
-                    f,
-                    "{}({})",
-                    name,
-                    arguments.iter().map(ToString::to_string).join(", ")
-                )
-            }
-            Self::Composite(composite) => {
-                f.write_char('(')?;
-                composite.fmt(f)?;
-                f.write_char(')')
-            }
-            Self::Name(name) => name.fmt(f),
-            Self::Value(value) => value.fmt(f),
+impl<T: SchemaMapped> SchemaMapped for SchemaItem<'_, T> {
+    fn get_field(&self, name: &str) -> Value {
+        let value = self.item.get_field(name);
+        match self
+            .schema
+            .get_field(name)
+            .and_then(|field| field.enum_schema.as_ref())
+        {
+            Some(enum_schema) => Self::get_enum_value(value, enum_schema),
+            None => value,
         }
     }
 }
 
-impl Display for FilterComparator {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Less => f.write_str("<"),
-            Self::LessOrEqual => f.write_str("<="),
-            Self::Greater => f.write_str(">"),
-            Self::GreaterOrEqual => f.write_str(">="),
-            Self::Equal => f.write_str("="),
-            Self::NotEqual => f.write_str("!="),
-            Self::Has => f.write_str(":"),
+impl<T> SchemaItem<'_, T> {
+    fn get_enum_value(value: Value, enum_schema: &EnumSchema) -> Value {
+        match value {
+            Value::Integer(number) => enum_schema
+                .get_name(number)
+                .map_or(value, |name| Value::Enum(name.into())),
+            Value::Repeated(values) => Value::Repeated(
+                values
+                    .into_iter()
+                    .map(|value| Self::get_enum_value(value, enum_schema))
+                    .collect(),
+            ),
+            value => value,
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::testing::schema::{RequestItem, TaskItem, UserItem};
+struct LenCounter(usize);
 
-    use super::*;
+impl FilterVisitor for LenCounter {
+    type Error = Infallible;
 
-    #[test]
-    fn validate_schema() {
-        let schema = UserItem::get_schema();
-        macro_rules! check {
-            (@ok $filter:expr) => {
-                match check!($filter) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        panic!("Expected Ok, got Err: {:#?}", err);
-                    }
-                }
-            };
-            (@error $filter:expr) => {
-                match check!($filter) {
-                    Ok(_) => {
-                        panic!("Expected Err, got Ok");
-                    }
-                    Err(_) => {}
-                }
-            };
-            ($filter:expr) => {
-                Filter::parse($filter).unwrap().validate(&schema, None)
-            };
+    fn visit_negate(&mut self, tree: &Filter) -> Result<(), Self::Error> {
+        self.0 += 1;
+        self.visit_filter(tree)
+    }
+
+    fn visit_restriction(
+        &mut self,
+        comparable: &Filter,
+        _comparator: FilterComparator,
+        argument: &Filter,
+    ) -> Result<(), Self::Error> {
+        self.0 += 1;
+        self.visit_filter(comparable)?;
+        self.visit_filter(argument)
+    }
+
+    fn visit_function(&mut self, name: &str, arguments: &[Filter]) -> Result<(), Self::Error> {
+        self.0 += 1 + name.len();
+        visit_filters(self, arguments)
+    }
+
+    fn visit_composite(&mut self, tree: &Filter) -> Result<(), Self::Error> {
+        self.0 += 1;
+        self.visit_filter(tree)
+    }
+
+    fn visit_name(&mut self, _name: &str) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+
+    fn visit_value(&mut self, _value: &Value) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+}
+
+/// Validates a filter against schema.
+struct SchemaValidator<'a> {
+    schema: &'a Schema,
+    schema_functions: Option<&'a FunctionSchemaMap>,
+    span: Option<&'a FilterSpan>,
+    /// Enum of the comparable, while visiting a restriction argument.
+    enum_schema: Option<&'a EnumSchema>,
+}
+
+impl SchemaValidator<'_> {
+    /// Attaches the span of the current node, or one of its children, to the error.
+    fn locate(&self, error: FilterError, child: Option<usize>) -> FilterError {
+        let span = child.map_or(self.span, |child| {
+            self.span.and_then(|span| span.children.get(child))
+        });
+        match span {
+            Some(span) if !matches!(error, FilterError::Spanned { .. }) => FilterError::Spanned {
+                error: Box::new(error),
+                span: span.span,
+            },
+            _ => error,
         }
+    }
 
-        check!(@ok "42");
-        check!(@ok "false");
-        check!(@ok r#"id="42""#);
+    fn visit_child(&mut self, filter: &Filter, child: usize) -> FilterResult<()> {
+        let span = self.span;
+        self.span = span.and_then(|span| span.children.get(child));
+        let result = self.visit_filter(filter);
+        self.span = span;
+        result
+    }
 
-        check!(@error "a");
-        check!(@error "a.b");
-        check!(@error "f()");
-        check!(@error "id=42");
+    fn visit_argument(&mut self, argument: &Filter, child: usize) -> FilterResult<()> {
+        self.for_each_argument_part(argument, child, &mut Self::visit_child)
     }
 
-    #[test]
-    fn it_works() {
-        Filter::parse("  ").unwrap();
+    /// Calls `f` on the argument, or on each part of a composite argument, such as `("a" OR "b")`.
+    fn for_each_argument_part(
+        &mut self,
+        argument: &Filter,
+        child: usize,
+        f: &mut dyn FnMut(&mut Self, &Filter, usize) -> FilterResult<()>,
+    ) -> FilterResult<()> {
+        if let Some((parts, _)) = argument.get_composite_argument() {
+            let span = self.span;
+            // Parts are children of the conjunction or disjunction inside of the composite
+            self.span = span
+                .and_then(|span| span.children.get(child))
+                .and_then(|span| span.children.first());
+            let result = parts
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, part)| self.for_each_argument_part(part, i, f));
+            self.span = span;
+            result
+        } else {
+            f(self, argument, child)
+        }
+    }
+
+    /// Checks that a literal can be converted to the value type.
+    fn check_coercion(
+        &self,
+        part: &Filter,
+        value_type: ValueType,
+        child: usize,
+    ) -> FilterResult<()> {
+        if let Filter::Value(value) = part
+            && value.value_type().is_some_and(|part_type| {
+                part_type != value_type && part_type.is_literal_coercible(value_type)
+            })
+        {
+            value
+                .coerce_literal(value_type)
+                .map_err(|error| self.locate(error, Some(child)))?;
+        }
+        Ok(())
+    }
+
+    /// Checks a key of a map presence check, such as `labels:env`.
+    fn check_map_key(&self, key: &Filter, key_type: ValueType, child: usize) -> FilterResult<()> {
+        let actual = match key {
+            // Bare identifiers are string keys
+            Filter::Name(_) => ValueType::String,
+            Filter::Value(value) => value.value_type().unwrap_or(ValueType::Any),
+            _ => return Err(self.locate(FilterError::InvalidResultValueType, Some(child))),
+        };
+        if actual != key_type && actual != ValueType::Any {
+            return Err(self.locate(
+                FilterError::InvalidType {
+                    actual,
+                    expected: key_type,
+                },
+                Some(child),
+            ));
+        }
+        Ok(())
+    }
+
+    fn get_child_type(&self, filter: &Filter, child: usize) -> FilterResult<ValueType> {
+        filter
+            .get_result_value_type(self.schema, self.schema_functions)
+            .map_err(|error| self.locate(error, Some(child)))
+    }
+
+    fn check_boolean(&self, filter: &Filter, child: usize) -> FilterResult<()> {
+        let value_type = self.get_child_type(filter, child)?;
+        if value_type != ValueType::Boolean {
+            return Err(self.locate(
+                FilterError::InvalidType {
+                    actual: value_type,
+                    expected: ValueType::Boolean,
+                },
+                Some(child),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FilterVisitor for SchemaValidator<'_> {
+    type Error = FilterError;
+
+    fn visit_conjunction(&mut self, parts: &[Filter]) -> FilterResult<()> {
+        for (i, part) in parts.iter().enumerate() {
+            self.visit_child(part, i)?;
+            self.check_boolean(part, i)?;
+        }
+        Ok(())
+    }
+
+    fn visit_disjunction(&mut self, parts: &[Filter]) -> FilterResult<()> {
+        self.visit_conjunction(parts)
+    }
+
+    fn visit_negate(&mut self, tree: &Filter) -> FilterResult<()> {
+        self.visit_child(tree, 0)?;
+        self.check_boolean(tree, 0)
+    }
+
+    fn visit_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<()> {
+        self.visit_child(comparable, 0)?;
+        if let Filter::Name(name) = comparable
+            && let Some(MemberSchema::Map(map)) = self.schema.get_member(name)
+        {
+            if comparator != FilterComparator::Has {
+                return Err(self.locate(FilterError::UnsuitableComparator(comparator), None));
+            }
+            let key_type = map.key_type;
+            return self.for_each_argument_part(argument, 1, &mut |validator, key, child| {
+                validator.check_map_key(key, key_type, child)
+            });
+        }
+        let comparable_type = self.get_child_type(comparable, 0)?;
+        let field = if let Filter::Name(name) = comparable {
+            self.schema.get_field(name)
+        } else {
+            None
+        };
+        self.enum_schema = field.and_then(|field| field.enum_schema.as_ref());
+        let argument_result = self.visit_argument(argument, 1);
+        self.enum_schema = None;
+        argument_result?;
+        let argument_type = self.get_child_type(argument, 1)?;
+
+        if matches!(argument, Filter::Value(Value::Null)) {
+            if !matches!(
+                comparator,
+                FilterComparator::Equal | FilterComparator::NotEqual
+            ) {
+                return Err(self.locate(FilterError::UnsuitableComparator(comparator), None));
+            }
+            if let Filter::Name(name) = comparable
+                && field.is_some_and(|field| !field.nullable)
+            {
+                return Err(self.locate(FilterError::NonNullableMember(name.clone()), Some(0)));
+            }
+            return Ok(());
+        }
+
+        if comparable_type == ValueType::Enum
+            && !matches!(
+                comparator,
+                FilterComparator::Equal | FilterComparator::NotEqual | FilterComparator::Has
+            )
+        {
+            return Err(self.locate(FilterError::UnsuitableComparator(comparator), None));
+        }
+
+        if comparator == FilterComparator::Has {
+            if field.is_some_and(|field| !field.allow_has_operator) {
+                return Err(self.locate(FilterError::UnsuitableComparator(comparator), None));
+            }
+            // Presence check
+            if argument_type == ValueType::Any {
+                return Ok(());
+            }
+        }
+
+        if !argument.is_comparable_argument(argument_type, comparable_type) {
+            return Err(self.locate(
+                FilterError::InvalidType {
+                    actual: argument_type,
+                    expected: comparable_type,
+                },
+                Some(1),
+            ));
+        }
+        if comparable_type != argument_type {
+            // Literal arguments are converted to the type of the comparable
+            return self.for_each_argument_part(argument, 1, &mut |validator, part, child| {
+                validator.check_coercion(part, comparable_type, child)
+            });
+        }
+        Ok(())
+    }
+
+    fn visit_function(&mut self, name: &str, arguments: &[Filter]) -> FilterResult<()> {
+        let function = self
+            .schema_functions
+            .and_then(|schema_functions| schema_functions.get(name))
+            .ok_or_else(|| self.locate(FilterError::UnknownFunction(name.into()), None))?;
+        if function.argument_value_types.len() != arguments.len() {
+            return Err(self.locate(
+                FilterError::FunctionInvalidArgumentCount {
+                    name: name.into(),
+                    expected: function.argument_value_types.len(),
+                },
+                None,
+            ));
+        }
+        for (i, (argument, argument_schema)) in arguments
+            .iter()
+            .zip(&function.argument_value_types)
+            .enumerate()
+        {
+            self.visit_child(argument, i)?;
+            let argument_value_type = self.get_child_type(argument, i)?;
+            if *argument_schema != ValueType::Any && argument_value_type != *argument_schema {
+                return Err(self.locate(
+                    FilterError::InvalidType {
+                        actual: argument_value_type,
+                        expected: *argument_schema,
+                    },
+                    Some(i),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_composite(&mut self, tree: &Filter) -> FilterResult<()> {
+        self.visit_child(tree, 0)
+    }
+
+    fn visit_name(&mut self, name: &str) -> FilterResult<()> {
+        if self.schema.get_member(name).is_none() {
+            return Err(self.locate(FilterError::UnknownMember(name.into()), None));
+        }
+        Ok(())
+    }
+
+    fn visit_value(&mut self, value: &Value) -> FilterResult<()> {
+        if let (Value::Enum(name), Some(enum_schema)) = (value, self.enum_schema)
+            && !enum_schema.contains(name)
+        {
+            return Err(self.locate(FilterError::InvalidEnumValue(name.clone()), None));
+        }
+        Ok(())
+    }
+}
+
+/// Splits a string pattern with a leading or trailing `*` wildcard.
+///
+/// Returns whether the pattern has a leading wildcard, the literal part, and whether it has a trailing wildcard.
+/// Returns `None` if the pattern contains no wildcards.
+pub(crate) fn split_wildcard(pattern: &str) -> Option<(bool, &str, bool)> {
+    let (leading, pattern) = pattern
+        .strip_prefix('*')
+        .map_or((false, pattern), |pattern| (true, pattern));
+    let (trailing, pattern) = pattern
+        .strip_suffix('*')
+        .map_or((false, pattern), |pattern| (true, pattern));
+    if leading || trailing {
+        Some((leading, pattern, trailing))
+    } else {
+        None
+    }
+}
+
+fn matches_wildcard(value: &str, pattern: &str) -> bool {
+    match split_wildcard(pattern) {
+        Some((true, literal, true)) => value.contains(literal),
+        Some((true, literal, false)) => value.ends_with(literal),
+        Some((false, literal, _)) => value.starts_with(literal),
+        None => value == pattern,
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::Conjunction(Vec::new())
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conjunction(parts) => parts.iter().map(ToString::to_string).join(" AND ").fmt(f),
+            Self::Disjunction(parts) => parts.iter().map(ToString::to_string).join(" OR ").fmt(f),
+            Self::Negate(tree) => {
+                f.write_str("NOT ")?;
+                tree.fmt(f)
+            }
+            Self::Restriction(comparable, comparator, arg) => match comparator {
+                FilterComparator::Has => {
+                    comparable.fmt(f)?;
+                    f.write_char(':')?;
+                    arg.fmt(f)
+                }
+                _ => {
+                    write!(f, "{comparable} {comparator} {arg}")
+                }
+            },
+            Self::Function(name, arguments) => {
+                write!(
+                    f,
+                    "{}({})",
+                    name,
+                    arguments.iter().map(ToString::to_string).join(", ")
+                )
+            }
+            Self::Composite(composite) => {
+                f.write_char('(')?;
+                composite.fmt(f)?;
+                f.write_char(')')
+            }
+            Self::Name(name) => name.fmt(f),
+            Self::Value(value) => value.fmt(f),
+        }
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Conjunction(filters) => FilterRef::Conjunction { filters },
+            Self::Disjunction(filters) => FilterRef::Disjunction { filters },
+            Self::Negate(filter) => FilterRef::Negate { filter },
+            Self::Restriction(comparable, comparator, argument) => FilterRef::Restriction {
+                comparable,
+                comparator: *comparator,
+                argument,
+            },
+            Self::Function(name, arguments) => FilterRef::Function { name, arguments },
+            Self::Composite(filter) => FilterRef::Composite { filter },
+            Self::Name(name) => FilterRef::Name { name },
+            Self::Value(value) => FilterRef::Value { value },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match FilterRepr::deserialize(deserializer)? {
+            FilterRepr::Conjunction { filters } => Self::Conjunction(filters),
+            FilterRepr::Disjunction { filters } => Self::Disjunction(filters),
+            FilterRepr::Negate { filter } => Self::Negate(filter),
+            FilterRepr::Restriction {
+                comparable,
+                comparator,
+                argument,
+            } => Self::Restriction(comparable, comparator, argument),
+            FilterRepr::Function { name, arguments } => Self::Function(name, arguments),
+            FilterRepr::Composite { filter } => Self::Composite(filter),
+            FilterRepr::Name { name } => Self::Name(name),
+            FilterRepr::Value { value } => Self::Value(value),
+        })
+    }
+}
+
+impl Display for FilterComparator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Less => f.write_str("<"),
+            Self::LessOrEqual => f.write_str("<="),
+            Self::Greater => f.write_str(">"),
+            Self::GreaterOrEqual => f.write_str(">="),
+            Self::Equal => f.write_str("="),
+            Self::NotEqual => f.write_str("!="),
+            Self::Has => f.write_str(":"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::schema::{RequestItem, TaskItem, UserItem};
+
+    use super::*;
+
+    #[test]
+    fn validate_schema() {
+        let schema = UserItem::get_schema();
+        macro_rules! check {
+            (@ok $filter:expr) => {
+                match check!($filter) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        panic!("Expected Ok, got Err: {:#?}", err);
+                    }
+                }
+            };
+            (@error $filter:expr) => {
+                match check!($filter) {
+                    Ok(_) => {
+                        panic!("Expected Err, got Ok");
+                    }
+                    Err(_) => {}
+                }
+            };
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().validate(&schema, None)
+            };
+        }
+
+        check!(@ok "42");
+        check!(@ok "false");
+        check!(@ok r#"id="42""#);
+
+        check!(@ok r#"displayName:"J""#);
+        check!(@ok "age:30");
+        check!(@ok "displayName:*");
+        check!(@ok "age = (30 OR 31)");
+        check!(@ok r#"displayName:("a" "b")"#);
+
+        check!(@error "a");
+        check!(@error "a.b");
+        check!(@error "f()");
+        check!(@error "id=42");
+        check!(@error r#"id:"4""#);
+        check!(@error r#"age:"30""#);
+        check!(@error r#"age = (30 OR "31")"#);
+        check!(@error r#"displayName = (30 OR 31)"#);
+    }
+
+    #[test]
+    fn validate_spanned() {
+        let schema = UserItem::get_schema();
+        macro_rules! check {
+            ($source:expr, $expected:expr) => {{
+                let (filter, span) = Filter::parse_spanned($source).unwrap();
+                let error = filter.validate_spanned(&span, &schema, None).unwrap_err();
+                assert_eq!(error.render($source), $expected);
+            }};
+        }
+
+        check!(
+            r#"age > 30 AND lol = 2"#,
+            "age > 30 AND lol = 2\n             ^^^ unknown filter member `lol`"
+        );
+        check!(
+            r#"age > 30 OR (id = "1" AND age = "2")"#,
+            "age > 30 OR (id = \"1\" AND age = \"2\")\n                                ^^^ expected filter type `Integer`, but got `String`"
+        );
+        check!(
+            r#"age = (30 OR "31")"#,
+            "age = (30 OR \"31\")\n      ^^^^^^^^^^^^ expected filter type `Integer`, but got `String`"
+        );
+        check!("NOT f(age)", "NOT f(age)\n    ^^^^^^ unknown function `f`");
+
+        let (filter, span) = Filter::parse_spanned("age > 30 AND lol = 2").unwrap();
+        assert_eq!(
+            filter
+                .validate_spanned(&span, &schema, None)
+                .unwrap_err()
+                .to_string(),
+            "unknown filter member `lol` at column 14"
+        );
+        assert_eq!(
+            Filter::parse("a <").unwrap_err().render("a <"),
+            "a <\n   ^ failed to parse filter from `3` to `3`"
+        );
+    }
+
+    #[test]
+    fn it_works() {
+        Filter::parse("  ").unwrap();
         Filter::parse("").unwrap();
         Filter::parse("x").unwrap();
         Filter::parse("42").unwrap();
@@ -700,6 +1343,98 @@ mod tests {
         assert_eq!(tree.to_string(), src);
     }
 
+    #[test]
+    fn serialize() {
+        let filter = Filter::parse(r#"NOT (a.f(42) AND b != "x") OR c:*"#).unwrap();
+        let json = serde_json::to_value(&filter).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "disjunction",
+                "filters": [
+                    {
+                        "type": "negate",
+                        "filter": {
+                            "type": "composite",
+                            "filter": {
+                                "type": "conjunction",
+                                "filters": [
+                                    {
+                                        "type": "function",
+                                        "name": "a.f",
+                                        "arguments": [
+                                            {"type": "value", "value": {"type": "integer", "value": 42}},
+                                        ],
+                                    },
+                                    {
+                                        "type": "restriction",
+                                        "comparable": {"type": "name", "name": "b"},
+                                        "comparator": "!=",
+                                        "argument": {"type": "value", "value": {"type": "string", "value": "x"}},
+                                    },
+                                ],
+                            },
+                        },
+                    },
+                    {
+                        "type": "restriction",
+                        "comparable": {"type": "name", "name": "c"},
+                        "comparator": ":",
+                        "argument": {"type": "value", "value": {"type": "any"}},
+                    },
+                ],
+            })
+        );
+        assert_eq!(serde_json::from_value::<Filter>(json).unwrap(), filter);
+
+        assert!(serde_json::from_value::<Filter>(serde_json::json!({"type": "lol"})).is_err());
+        assert!(
+            serde_json::from_value::<Filter>(serde_json::json!({
+                "type": "restriction",
+                "comparable": {"type": "name", "name": "c"},
+                "comparator": "~",
+                "argument": {"type": "name", "name": "d"},
+            }))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn macros() {
+        use bomboni_request_derive::filter;
+
+        macro_rules! assert_parsed {
+            ($source:literal) => {
+                assert_eq!(
+                    filter!(
+                        bomboni_proto_crate = bomboni_proto,
+                        bomboni_request_crate = crate,
+                        $source
+                    ),
+                    Filter::parse($source).unwrap(),
+                    "{}",
+                    $source
+                );
+            };
+        }
+        assert_parsed!("");
+        assert_parsed!("a b AND c OR d");
+        assert_parsed!(r#"NOT (a.f(42, -1.5e3) AND b != "x\"y") OR c:*"#);
+        assert_parsed!("f() AND g(a, (1 OR 2)) AND x = null AND y = nullable");
+        assert_parsed!("d >= 1.5h AND d < -250ms AND e = 9223372036854775808");
+        assert_parsed!(r#"t > "2024-01-01T00:00:00Z" AND ok = true AND n:"*.a""#);
+
+        let min_age = 18;
+        let name = "John";
+        assert_eq!(
+            filter!(
+                bomboni_request_crate = crate,
+                r#"age >= #{min_age} AND displayName = #{name} AND score < #{f64::from(min_age) * 1.5}"#
+            ),
+            Filter::parse(r#"age >= 18 AND displayName = "John" AND score < 27.0"#).unwrap()
+        );
+    }
+
     #[test]
     fn modify() {
         let mut f = Filter::parse("x=42").unwrap();
@@ -742,4 +1477,645 @@ mod tests {
             .unwrap();
         assert_eq!(res, Value::Boolean(true));
     }
+
+    #[test]
+    fn evaluate_composite_arguments() {
+        let item = TaskItem {
+            id: "1".into(),
+            user_id: "42".into(),
+            content: "hello world".into(),
+            deleted: false,
+            tags: vec!["a".into(), "b".into()],
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    Filter::parse($filter).unwrap().evaluate(&item),
+                    Some(Value::Boolean($expected)),
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        check!(r#"tags:("a" OR "x")"#, true);
+        check!(r#"tags:("a" AND "x")"#, false);
+        check!(r#"tags:("a" "b")"#, true);
+        check!(r#"content:("hello" OR "x")"#, true);
+        check!(r#"id = ("1" OR "2")"#, true);
+        check!(r#"id != ("1" AND "2")"#, false);
+        check!(r#"id != ("3" AND "2")"#, true);
+        check!(r#"id = ("3" OR ("4" OR "1"))"#, true);
+    }
+
+    #[test]
+    fn evaluate_wildcards() {
+        let item = UserItem {
+            id: "978-3".into(),
+            display_name: "J. R. R. Tolkien".into(),
+            age: 30,
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    Filter::parse($filter).unwrap().evaluate(&item),
+                    Some(Value::Boolean($expected)),
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        check!(r#"displayName = "*Tolkien""#, true);
+        check!(r#"displayName = "J.*""#, true);
+        check!(r#"displayName = "*R. R.*""#, true);
+        check!(r#"displayName = "*""#, true);
+        check!(r#"displayName = "Tolkien*""#, false);
+        check!(r#"displayName != "*Tolkien""#, false);
+        check!(r#"id = ("123*" OR "978*")"#, true);
+    }
+
+    #[test]
+    fn evaluate_functions() {
+        let item = UserItem {
+            id: "42".into(),
+            display_name: "John Doe".into(),
+            age: 30,
+        };
+        let functions = FunctionRegistry::standard();
+        let schema = UserItem::get_schema();
+
+        let f = Filter::parse(
+            r#"startsWith(displayName, "John") AND lower(displayName) = "john doe" AND size(id) = 2"#,
+        )
+        .unwrap();
+        f.validate(&schema, Some(functions.schema_functions()))
+            .unwrap();
+        assert_eq!(
+            f.evaluate_with_functions(&item, &functions),
+            Some(Value::Boolean(true))
+        );
+        assert_eq!(f.evaluate(&item), None);
+
+        let f = Filter::parse(r#"regex(displayName, "^J.*e$") AND NOT endsWith(id, "2")"#).unwrap();
+        assert_eq!(
+            f.evaluate_with_functions(&item, &functions),
+            Some(Value::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn enums() {
+        use crate::schema::FieldMemberSchema;
+        use bomboni_macros::btree_map_into;
+
+        struct BookItem {
+            state: &'static str,
+        }
+
+        impl SchemaMapped for BookItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "state" => Value::Enum(self.state.into()),
+                    _ => unimplemented!("SchemaMapped: Book::{}", name),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "state" => FieldMemberSchema::new_enum(EnumSchema::new(["DRAFT", "PUBLISHED"])),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr) => {
+                Filter::parse($filter)
+                    .unwrap()
+                    .resolve(&schema)
+                    .validate(&schema, None)
+            };
+        }
+
+        check!("state = PUBLISHED").unwrap();
+        check!(r#"state != "DRAFT""#).unwrap();
+        check!("state:(DRAFT OR PUBLISHED)").unwrap();
+        assert_eq!(
+            check!("state = NONSENSE").unwrap_err(),
+            FilterError::InvalidEnumValue("NONSENSE".into())
+        );
+        assert_eq!(
+            check!(r#"state = ("DRAFT" OR "NONSENSE")"#).unwrap_err(),
+            FilterError::InvalidEnumValue("NONSENSE".into())
+        );
+        assert_eq!(
+            check!("state > DRAFT").unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Greater)
+        );
+        assert!(matches!(
+            check!("state = 1").unwrap_err(),
+            FilterError::InvalidType { .. }
+        ));
+
+        let item = BookItem { state: "PUBLISHED" };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter)
+                    .unwrap()
+                    .resolve(&schema)
+                    .evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!("state = PUBLISHED"), Some(true.into()));
+        assert_eq!(evaluate!("state != PUBLISHED"), Some(false.into()));
+        assert_eq!(
+            evaluate!(r#"state:("DRAFT" OR "PUBLISHED")"#),
+            Some(true.into())
+        );
+        assert_eq!(evaluate!("state < DRAFT"), None);
+
+        // Items may return numeric values of numbered enums
+        struct NumberedBookItem {
+            state: i64,
+        }
+
+        impl SchemaMapped for NumberedBookItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "state" => Value::Integer(self.state),
+                    _ => unimplemented!("SchemaMapped: NumberedBook::{}", name),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "state" => FieldMemberSchema::new_enum(EnumSchema::new_numbered([("DRAFT", 1), ("PUBLISHED", 2)])),
+            },
+        };
+        let item = NumberedBookItem { state: 2 };
+        let functions = FunctionRegistry::new();
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter)
+                    .unwrap()
+                    .resolve(&schema)
+                    .evaluate_with_schema(&item, &schema, &functions)
+            };
+        }
+        assert_eq!(evaluate!("state = PUBLISHED"), Some(true.into()));
+        assert_eq!(evaluate!("state = DRAFT"), Some(false.into()));
+        assert_eq!(evaluate!("state != PUBLISHED"), Some(false.into()));
+        assert_eq!(
+            evaluate!(r#"state:("DRAFT" OR "PUBLISHED")"#),
+            Some(true.into())
+        );
+    }
+
+    #[test]
+    fn durations() {
+        use crate::schema::FieldMemberSchema;
+        use bomboni_macros::btree_map_into;
+        use bomboni_proto::google::protobuf::Duration;
+
+        struct CacheItem {
+            ttl: Duration,
+        }
+
+        impl SchemaMapped for CacheItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "ttl" => self.ttl.into(),
+                    _ => unimplemented!("SchemaMapped: Cache::{}", name),
+                }
+            }
+        }
+
+        let filter = Filter::parse("ttl > 1.5h AND ttl <= -250ms").unwrap();
+        assert_eq!(filter.to_string(), "ttl > 5400s AND ttl <= -0.25s");
+        assert_eq!(Filter::parse(&filter.to_string()).unwrap(), filter);
+        assert_eq!(
+            Filter::parse("ttl > 1000000000h").unwrap_err(),
+            FilterError::InvalidDuration("1000000000h".into())
+        );
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "ttl" => FieldMemberSchema::new_ordered(ValueType::Duration),
+            },
+        };
+        Filter::parse("ttl > 30s AND ttl:1m")
+            .unwrap()
+            .validate(&schema, None)
+            .unwrap();
+        assert!(matches!(
+            Filter::parse("ttl > 30")
+                .unwrap()
+                .validate(&schema, None)
+                .unwrap_err(),
+            FilterError::InvalidType { .. }
+        ));
+
+        let item = CacheItem {
+            ttl: Duration::new(90, 0),
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!("ttl > 1m"), Some(true.into()));
+        assert_eq!(evaluate!("ttl = 1.5m"), Some(true.into()));
+        assert_eq!(evaluate!("ttl <= 89999ms"), Some(false.into()));
+        assert_eq!(evaluate!("ttl > 30"), None);
+    }
+
+    #[test]
+    fn nulls() {
+        use crate::schema::FieldMemberSchema;
+        use bomboni_macros::btree_map_into;
+
+        struct TaskItem {
+            deleted: bool,
+            priority: Option<i64>,
+        }
+
+        impl SchemaMapped for TaskItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "deleted" => self.deleted.into(),
+                    "priority" => self.priority.map_or(Value::Null, Value::Integer),
+                    _ => unimplemented!("SchemaMapped: Task::{}", name),
+                }
+            }
+        }
+
+        let filter = Filter::parse("priority = null OR NOT priority:*").unwrap();
+        assert_eq!(filter.to_string(), "priority = null OR NOT priority:*");
+        assert_eq!(
+            Filter::parse("nullable = 1").unwrap().to_string(),
+            "nullable = 1"
+        );
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "deleted" => FieldMemberSchema::new(ValueType::Boolean),
+                "priority" => FieldMemberSchema::new_nullable(ValueType::Integer),
+            },
+        };
+        macro_rules! validate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().validate(&schema, None)
+            };
+        }
+        validate!("priority = null AND priority != null AND priority:*").unwrap();
+        assert_eq!(
+            validate!("deleted = null").unwrap_err(),
+            FilterError::NonNullableMember("deleted".into())
+        );
+        assert_eq!(
+            validate!("priority < null").unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Less)
+        );
+
+        let item = TaskItem {
+            deleted: false,
+            priority: None,
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!("priority:*"), Some(false.into()));
+        assert_eq!(evaluate!("NOT priority:*"), Some(true.into()));
+        assert_eq!(evaluate!("priority = null"), Some(true.into()));
+        assert_eq!(evaluate!("priority != null"), Some(false.into()));
+        // Comparisons with absent values are unknown
+        assert_eq!(evaluate!("priority > 1"), Some(Value::Null));
+        assert_eq!(evaluate!("NOT priority > 1"), Some(Value::Null));
+        assert_eq!(evaluate!("priority = (1 OR 2)"), Some(Value::Null));
+        assert_eq!(evaluate!("priority > 1 AND deleted"), Some(false.into()));
+        assert_eq!(evaluate!("priority > 1 AND NOT deleted"), Some(Value::Null));
+        assert_eq!(evaluate!("priority > 1 OR NOT deleted"), Some(true.into()));
+        assert_eq!(evaluate!("priority > 1 OR deleted"), Some(Value::Null));
+
+        let item = TaskItem {
+            deleted: true,
+            priority: Some(2),
+        };
+        assert_eq!(
+            Filter::parse("priority:* AND priority > 1 AND priority != null")
+                .unwrap()
+                .evaluate(&item),
+            Some(true.into())
+        );
+    }
+
+    #[test]
+    fn maps() {
+        use crate::schema::{FieldMemberSchema, MapMemberSchema};
+        use bomboni_macros::btree_map_into;
+        use std::collections::BTreeMap;
+
+        struct ServerItem {
+            labels: BTreeMap<String, String>,
+        }
+
+        impl SchemaMapped for ServerItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "labels" => Value::Map(
+                        self.labels
+                            .iter()
+                            .map(|(key, value)| (key.clone(), value.as_str().into()))
+                            .collect(),
+                    ),
+                    _ => name
+                        .strip_prefix("labels.")
+                        .and_then(|key| self.labels.get(key))
+                        .map_or(Value::Null, |value| value.as_str().into()),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "labels" => MapMemberSchema::new(ValueType::String, ValueType::String),
+                "counts" => MapMemberSchema::new(ValueType::Integer, ValueType::Integer),
+                "name" => FieldMemberSchema::new(ValueType::String),
+            },
+        };
+        macro_rules! validate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().validate(&schema, None)
+            };
+        }
+        validate!(r#"labels.env = "prod" AND labels:env AND labels:("a" OR b)"#).unwrap();
+        validate!("labels.env = null AND labels:* AND counts:1 AND counts.x > 2").unwrap();
+        assert_eq!(
+            validate!("labels.env = 1").unwrap_err(),
+            FilterError::InvalidType {
+                expected: ValueType::String,
+                actual: ValueType::Integer,
+            }
+        );
+        assert_eq!(
+            validate!("counts:env").unwrap_err(),
+            FilterError::InvalidType {
+                expected: ValueType::Integer,
+                actual: ValueType::String,
+            }
+        );
+        assert_eq!(
+            validate!(r#"labels = "x""#).unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Equal)
+        );
+
+        let item = ServerItem {
+            labels: btree_map_into! {
+                "env" => "prod",
+            },
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!(r#"labels.env = "prod""#), Some(true.into()));
+        assert_eq!(evaluate!(r#"labels.tier = "web""#), Some(Value::Null));
+        assert_eq!(evaluate!("labels:env"), Some(true.into()));
+        assert_eq!(evaluate!(r#"labels:"tier""#), Some(false.into()));
+        assert_eq!(evaluate!("labels:(tier OR env)"), Some(true.into()));
+        assert_eq!(evaluate!("NOT labels:tier"), Some(true.into()));
+    }
+
+    #[test]
+    fn repeated_resources() {
+        use crate::schema::{FieldMemberSchema, ResourceMemberSchema};
+        use bomboni_macros::btree_map_into;
+
+        struct AuthorItem {
+            country: &'static str,
+            age: i64,
+        }
+
+        struct BookItem {
+            authors: Vec<AuthorItem>,
+        }
+
+        impl SchemaMapped for BookItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "authors.country" => Value::Repeated(
+                        self.authors
+                            .iter()
+                            .map(|author| author.country.into())
+                            .collect(),
+                    ),
+                    "authors.age" => Value::Repeated(
+                        self.authors
+                            .iter()
+                            .map(|author| author.age.into())
+                            .collect(),
+                    ),
+                    _ => unimplemented!("SchemaMapped: Book::{}", name),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "authors" => ResourceMemberSchema::new_repeated(btree_map_into! {
+                    "country" => FieldMemberSchema::new(ValueType::String),
+                    "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                }),
+            },
+        };
+        Filter::parse(r#"authors.country = "SI" AND authors.age > 30"#)
+            .unwrap()
+            .validate(&schema, None)
+            .unwrap();
+
+        let item = BookItem {
+            authors: vec![
+                AuthorItem {
+                    country: "SI",
+                    age: 25,
+                },
+                AuthorItem {
+                    country: "HR",
+                    age: 40,
+                },
+            ],
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!(r#"authors.country = "SI""#), Some(true.into()));
+        assert_eq!(evaluate!(r#"authors.country != "SI""#), Some(true.into()));
+        assert_eq!(
+            evaluate!(r#"NOT authors.country = "SI""#),
+            Some(false.into())
+        );
+        assert_eq!(evaluate!(r#"authors.country = "AT""#), Some(false.into()));
+        assert_eq!(evaluate!("authors.age > 30"), Some(true.into()));
+        assert_eq!(evaluate!("authors.age > 50"), Some(false.into()));
+        assert_eq!(evaluate!("authors.age = (40 OR 50)"), Some(true.into()));
+        assert_eq!(
+            Filter::parse("authors.age > 1")
+                .unwrap()
+                .evaluate(&BookItem {
+                    authors: Vec::new()
+                }),
+            Some(false.into())
+        );
+    }
+
+    #[test]
+    fn numeric_coercion() {
+        use crate::schema::FieldMemberSchema;
+        use bomboni_macros::btree_map_into;
+
+        struct ProductItem {
+            price: f64,
+            stock: i64,
+        }
+
+        impl SchemaMapped for ProductItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "price" => self.price.into(),
+                    "stock" => self.stock.into(),
+                    _ => unimplemented!("SchemaMapped: Product::{}", name),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "price" => FieldMemberSchema::new_ordered(ValueType::Float),
+                "stock" => FieldMemberSchema::new_ordered(ValueType::Integer),
+            },
+        };
+        macro_rules! validate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().validate(&schema, None)
+            };
+        }
+        validate!("price > 10 AND price = (1 OR 2.5) AND stock = 3.0 AND price < stock").unwrap();
+        assert_eq!(
+            validate!("stock > 2.5").unwrap_err(),
+            FilterError::PrecisionLoss {
+                value: "2.5".into(),
+                expected: ValueType::Integer,
+            }
+        );
+        assert_eq!(
+            validate!("price = 9007199254740993").unwrap_err(),
+            FilterError::PrecisionLoss {
+                value: "9007199254740993".into(),
+                expected: ValueType::Float,
+            }
+        );
+        assert_eq!(
+            Filter::parse("(1 OR 2.5)")
+                .unwrap()
+                .get_result_value_type(&schema, None)
+                .unwrap(),
+            ValueType::Float
+        );
+
+        let item = ProductItem {
+            price: 12.0,
+            stock: 12,
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(evaluate!("price > 10"), Some(true.into()));
+        assert_eq!(evaluate!("price = 12"), Some(true.into()));
+        assert_eq!(evaluate!("stock < 12.5"), Some(true.into()));
+        assert_eq!(evaluate!("price = stock"), Some(true.into()));
+    }
+
+    #[test]
+    fn timestamps() {
+        use crate::schema::FieldMemberSchema;
+        use bomboni_common::date_time::UtcDateTime;
+        use bomboni_macros::btree_map_into;
+
+        struct EventItem {
+            title: String,
+            create_time: UtcDateTime,
+        }
+
+        impl SchemaMapped for EventItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "title" => self.title.clone().into(),
+                    "createTime" => self.create_time.into(),
+                    _ => unimplemented!("SchemaMapped: Event::{}", name),
+                }
+            }
+        }
+
+        assert_eq!(
+            Filter::parse(r#"title = "2024-01-01T00:00:00Z""#).unwrap(),
+            Filter::Restriction(
+                Box::new(Filter::Name("title".into())),
+                FilterComparator::Equal,
+                Box::new(Filter::Value(Value::String("2024-01-01T00:00:00Z".into()))),
+            )
+        );
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema::new(ValueType::String),
+                "createTime" => FieldMemberSchema::new_ordered(ValueType::Timestamp),
+            },
+        };
+        macro_rules! validate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().validate(&schema, None)
+            };
+        }
+        validate!(r#"title = "2024-01-01T00:00:00Z""#).unwrap();
+        validate!(r#"createTime > "2024-01-01T00:00:00Z""#).unwrap();
+        validate!(r#"createTime = ("2024-01-01T00:00:00Z" OR "2024-02-01T00:00:00+01:00")"#)
+            .unwrap();
+        assert_eq!(
+            validate!(r#"createTime > "2024-13-01""#).unwrap_err(),
+            FilterError::InvalidTimestamp("2024-13-01".into())
+        );
+        assert_eq!(
+            validate!("createTime > title").unwrap_err(),
+            FilterError::InvalidType {
+                expected: ValueType::Timestamp,
+                actual: ValueType::String,
+            }
+        );
+
+        let item = EventItem {
+            title: "launch".into(),
+            create_time: UtcDateTime::parse_rfc3339("2024-06-01T12:00:00Z").unwrap(),
+        };
+        macro_rules! evaluate {
+            ($filter:expr) => {
+                Filter::parse($filter).unwrap().evaluate(&item)
+            };
+        }
+        assert_eq!(
+            evaluate!(r#"createTime > "2024-01-01T00:00:00Z""#),
+            Some(true.into())
+        );
+        assert_eq!(
+            evaluate!(r#"createTime = "2024-06-01T14:00:00+02:00""#),
+            Some(true.into())
+        );
+        assert_eq!(evaluate!(r#"createTime > "yesterday""#), None);
+    }
 }
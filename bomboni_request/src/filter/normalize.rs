@@ -0,0 +1,298 @@
+use std::convert::Infallible;
+
+use itertools::Itertools;
+
+use crate::{
+    filter::{
+        Filter, FilterComparator,
+        visit::{FilterFolder, fold_filters},
+    },
+    schema::SchemaMapped,
+    value::Value,
+};
+
+/// Item without any fields, used for folding restrictions between literal values.
+struct LiteralItem;
+
+impl SchemaMapped for LiteralItem {
+    fn get_field(&self, name: &str) -> Value {
+        unreachable!("literal restrictions have no field `{name}`")
+    }
+}
+
+impl Filter {
+    /// Normalizes the filter into a canonical form.
+    ///
+    /// Nested conjunctions and disjunctions are flattened, redundant composites are removed,
+    /// negations are pushed inward using De Morgan's laws, constant subexpressions are folded,
+    /// and operands of commutative operators are sorted and deduplicated.
+    /// Semantically identical filters produce the same normalized filter, and thus the same string representation.
+    ///
+    /// A filter that is always true normalizes to an empty filter.
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        let Ok(filter) = Normalizer { negate: false }.fold_filter(self.clone());
+        match filter {
+            Self::Value(Value::Boolean(true)) => Self::default(),
+            filter => filter,
+        }
+    }
+
+    fn normalize_connective<I>(parts: I, disjunction: bool) -> Self
+    where
+        I: Iterator<Item = Self>,
+    {
+        let mut normalized = Vec::new();
+        for part in parts {
+            match part {
+                // Absorbing element makes the whole expression constant, identity element is dropped
+                Self::Value(Value::Boolean(value)) => {
+                    if value == disjunction {
+                        return Self::Value(Value::Boolean(disjunction));
+                    }
+                }
+                Self::Conjunction(parts) if !disjunction => normalized.extend(parts),
+                Self::Disjunction(parts) if disjunction => normalized.extend(parts),
+                // Conjunctions inside of disjunctions need parentheses
+                Self::Conjunction(parts) => {
+                    normalized.push(Self::Composite(Box::new(Self::Conjunction(parts))));
+                }
+                part => normalized.push(part),
+            }
+        }
+
+        Self::sort_operands(&mut normalized);
+        match normalized.len() {
+            0 => Self::Value(Value::Boolean(!disjunction)),
+            1 => normalized.pop().unwrap(),
+            _ if disjunction => Self::Disjunction(normalized),
+            _ => Self::Conjunction(normalized),
+        }
+    }
+
+    fn normalize_argument(self) -> Self {
+        let Self::Composite(composite) = self else {
+            return self;
+        };
+        let (parts, disjunction) = match *composite {
+            Self::Conjunction(parts) => (parts, false),
+            Self::Disjunction(parts) => (parts, true),
+            composite => return composite.normalize_argument(),
+        };
+
+        let mut normalized = Vec::new();
+        for part in parts.into_iter().map(Self::normalize_argument) {
+            match part {
+                Self::Composite(composite) => match *composite {
+                    Self::Conjunction(parts) if !disjunction => normalized.extend(parts),
+                    Self::Disjunction(parts) if disjunction => normalized.extend(parts),
+                    composite => normalized.push(Self::Composite(Box::new(composite))),
+                },
+                part => normalized.push(part),
+            }
+        }
+
+        Self::sort_operands(&mut normalized);
+        if normalized.len() == 1 {
+            normalized.pop().unwrap()
+        } else if disjunction {
+            Self::Composite(Box::new(Self::Disjunction(normalized)))
+        } else {
+            Self::Composite(Box::new(Self::Conjunction(normalized)))
+        }
+    }
+
+    /// Sorts operands by their string form, removing duplicates.
+    fn sort_operands(operands: &mut Vec<Self>) {
+        let mut sorted: Vec<(String, Self)> = Vec::with_capacity(operands.len());
+        for (key, operand) in operands
+            .drain(..)
+            .map(|operand| (operand.to_string(), operand))
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        {
+            // Different operands may have the same string form, such as `1` and `1.0`
+            if !sorted
+                .iter()
+                .rev()
+                .take_while(|(other_key, _)| *other_key == key)
+                .any(|(_, other)| *other == operand)
+            {
+                sorted.push((key, operand));
+            }
+        }
+        *operands = sorted.into_iter().map(|(_, operand)| operand).collect();
+    }
+}
+
+/// Folds filter into its normal form, negating it while `negate` is set.
+struct Normalizer {
+    negate: bool,
+}
+
+impl Normalizer {
+    fn negated(&self, filter: Filter) -> Filter {
+        if self.negate {
+            Filter::Negate(Box::new(filter))
+        } else {
+            filter
+        }
+    }
+}
+
+impl FilterFolder for Normalizer {
+    type Error = Infallible;
+
+    fn fold_conjunction(&mut self, parts: Vec<Filter>) -> Result<Filter, Self::Error> {
+        let parts = fold_filters(self, parts)?;
+        Ok(Filter::normalize_connective(parts.into_iter(), self.negate))
+    }
+
+    fn fold_disjunction(&mut self, parts: Vec<Filter>) -> Result<Filter, Self::Error> {
+        let parts = fold_filters(self, parts)?;
+        Ok(Filter::normalize_connective(
+            parts.into_iter(),
+            !self.negate,
+        ))
+    }
+
+    fn fold_negate(&mut self, tree: Filter) -> Result<Filter, Self::Error> {
+        self.negate = !self.negate;
+        let tree = self.fold_filter(tree);
+        self.negate = !self.negate;
+        tree
+    }
+
+    fn fold_restriction(
+        &mut self,
+        comparable: Filter,
+        comparator: FilterComparator,
+        argument: Filter,
+    ) -> Result<Filter, Self::Error> {
+        let comparable = comparable.normalize_argument();
+        let argument = argument.normalize_argument();
+
+        if matches!(comparable, Filter::Value(_)) && matches!(argument, Filter::Value(_)) {
+            let restriction =
+                Filter::Restriction(Box::new(comparable), comparator, Box::new(argument));
+            return Ok(match restriction.evaluate(&LiteralItem) {
+                Some(Value::Boolean(value)) => Filter::Value(Value::Boolean(value != self.negate)),
+                _ => self.negated(restriction),
+            });
+        }
+
+        // Negated restrictions are not inverted, since `NOT a = 1` and `a != 1` differ if `a` is repeated
+        Ok(self.negated(Filter::Restriction(
+            Box::new(comparable),
+            comparator,
+            Box::new(argument),
+        )))
+    }
+
+    fn fold_function(
+        &mut self,
+        name: String,
+        arguments: Vec<Filter>,
+    ) -> Result<Filter, Self::Error> {
+        Ok(self.negated(Filter::Function(
+            name,
+            arguments
+                .into_iter()
+                .map(Filter::normalize_argument)
+                .collect(),
+        )))
+    }
+
+    fn fold_composite(&mut self, tree: Filter) -> Result<Filter, Self::Error> {
+        self.fold_filter(tree)
+    }
+
+    fn fold_name(&mut self, name: String) -> Result<Filter, Self::Error> {
+        Ok(self.negated(Filter::Name(name)))
+    }
+
+    fn fold_value(&mut self, value: Value) -> Result<Filter, Self::Error> {
+        Ok(match value {
+            Value::Boolean(value) => Filter::Value(Value::Boolean(value != self.negate)),
+            value => self.negated(Filter::Value(value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        macro_rules! check {
+            ($source:expr, $expected:expr) => {{
+                let normalized = Filter::parse($source).unwrap().normalize();
+                assert_eq!(normalized.to_string(), $expected, "{}", $source);
+                // Normalized string is parsed back into the same tree
+                assert_eq!(
+                    Filter::parse(&normalized.to_string()).unwrap().normalize(),
+                    normalized
+                );
+                assert_eq!(normalized.normalize(), normalized);
+            }};
+        }
+
+        check!("", "");
+        check!("b AND a", "a AND b");
+        check!("(b AND (a)) AND c", "a AND b AND c");
+        check!("a b a", "a AND b");
+        check!("(a OR (b OR c)) OR d", "a OR b OR c OR d");
+        check!("NOT (a AND NOT b)", "NOT a OR b");
+        check!("NOT (NOT a)", "a");
+        check!("NOT (a OR b)", "NOT a AND NOT b");
+        check!("NOT x < 10", "NOT x < 10");
+        check!("NOT (x = 10 OR y)", "NOT x = 10 AND NOT y");
+        check!(r#"NOT x:"a""#, r#"NOT x:"a""#);
+        check!("NOT x = (1 OR 2)", "NOT x = (1 OR 2)");
+        check!(r#"x:("b" OR ("a" OR "b"))"#, r#"x:("a" OR "b")"#);
+        check!("x = (1)", "x = 1");
+        check!("c OR a AND b", "a OR c AND b");
+        check!("d OR (b AND a)", "(a AND b) OR d");
+        check!("x = 1 AND (y OR z AND true)", "x = 1 AND y OR z");
+        check!("1 = 1 AND a", "a");
+        check!("1 = 1", "");
+        check!("1 = 2 AND a", "false");
+        check!("1 = 2 OR a", "a");
+        check!("NOT (1 < 2) OR a", "a");
+        check!("a OR true", "");
+        check!("NOT true", "false");
+        check!("f(b) AND NOT f(a)", "NOT f(a) AND f(b)");
+    }
+
+    #[test]
+    fn equivalent() {
+        let a = Filter::parse(r#"(y:("b" "a") AND NOT (x < 3 OR z)) AND w"#).unwrap();
+        let b = Filter::parse(r#"NOT z AND w AND NOT x < 3 AND y:("a" "b")"#).unwrap();
+        assert_ne!(a.to_string(), b.to_string());
+        assert_eq!(a.normalize(), b.normalize());
+        assert_eq!(a.normalize().to_string(), b.normalize().to_string());
+    }
+
+    #[test]
+    fn distinct_operands() {
+        let restriction = |value: Value| {
+            Filter::Restriction(
+                Box::new(Filter::Name("x".into())),
+                FilterComparator::Equal,
+                Box::new(Filter::Value(value)),
+            )
+        };
+        let filter = Filter::Conjunction(vec![
+            restriction(Value::Integer(1)),
+            restriction(Value::Float(1.0)),
+            restriction(Value::Integer(1)),
+        ]);
+        assert_eq!(
+            filter.normalize(),
+            Filter::Conjunction(vec![
+                restriction(Value::Integer(1)),
+                restriction(Value::Float(1.0)),
+            ])
+        );
+    }
+}
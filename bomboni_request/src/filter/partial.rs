@@ -0,0 +1,174 @@
+use std::{collections::BTreeMap, convert::Infallible};
+
+use crate::{
+    filter::{Filter, FilterComparator, visit::FilterFolder},
+    schema::SchemaMapped,
+    value::Value,
+};
+
+impl Filter {
+    /// Partially evaluates the filter against values of bound members.
+    ///
+    /// Restrictions on bound members, such as a parent resource or a tenant ID, are folded into `true` or `false`,
+    /// and remaining references to bound members are replaced with their values.
+    /// Restrictions that are unknown, such as comparisons of members bound to null, never match,
+    /// and are folded into `false`, or `true` under negation, such that the negation does not match either.
+    /// The residual filter is normalized, such that it is empty if it matches any item,
+    /// and `false` if it can never match.
+    ///
+    /// Members are bound by their full names, such as `user.id`.
+    #[must_use]
+    pub fn partial_evaluate(&self, bindings: &BTreeMap<String, Value>) -> Self {
+        let Ok(filter) = PartialEvaluator {
+            bindings,
+            negated: false,
+            operand: false,
+        }
+        .fold_filter(self.clone());
+        filter.normalize()
+    }
+}
+
+struct PartialEvaluator<'a> {
+    bindings: &'a BTreeMap<String, Value>,
+    /// Whether the filter being folded is under an odd number of negations.
+    negated: bool,
+    /// Whether the filter being folded is an operand of a restriction.
+    operand: bool,
+}
+
+impl PartialEvaluator<'_> {
+    /// Checks if the value of an operand is known, such that it references only bound members.
+    fn is_bound(&self, filter: &Filter) -> bool {
+        match filter {
+            Filter::Conjunction(parts) | Filter::Disjunction(parts) => {
+                parts.iter().all(|part| self.is_bound(part))
+            }
+            Filter::Composite(tree) => self.is_bound(tree),
+            Filter::Name(name) => self.bindings.contains_key(name),
+            Filter::Value(_) => true,
+            Filter::Negate(_) | Filter::Restriction(..) | Filter::Function(..) => false,
+        }
+    }
+}
+
+impl SchemaMapped for PartialEvaluator<'_> {
+    fn get_field(&self, name: &str) -> Value {
+        self.bindings.get(name).cloned().unwrap_or(Value::Null)
+    }
+}
+
+impl FilterFolder for PartialEvaluator<'_> {
+    type Error = Infallible;
+
+    fn fold_negate(&mut self, tree: Filter) -> Result<Filter, Self::Error> {
+        self.negated = !self.negated;
+        let tree = self.fold_filter(tree);
+        self.negated = !self.negated;
+        Ok(Filter::Negate(Box::new(tree?)))
+    }
+
+    fn fold_restriction(
+        &mut self,
+        comparable: Filter,
+        comparator: FilterComparator,
+        argument: Filter,
+    ) -> Result<Filter, Self::Error> {
+        self.operand = true;
+        let restriction = Filter::Restriction(
+            Box::new(self.fold_filter(comparable)?),
+            comparator,
+            Box::new(self.fold_filter(argument)?),
+        );
+        self.operand = false;
+        if let Filter::Restriction(comparable, _, argument) = &restriction
+            && self.is_bound(comparable)
+            && self.is_bound(argument)
+        {
+            match restriction.evaluate(self) {
+                Some(Value::Boolean(value)) => return Ok(Filter::Value(Value::Boolean(value))),
+                Some(Value::Null) => return Ok(Filter::Value(Value::Boolean(self.negated))),
+                _ => {}
+            }
+        }
+        Ok(restriction)
+    }
+
+    fn fold_name(&mut self, name: String) -> Result<Filter, Self::Error> {
+        Ok(match self.bindings.get(&name) {
+            // Boolean members bound to null are unknown
+            Some(Value::Null) if !self.operand => Filter::Value(Value::Boolean(self.negated)),
+            Some(value) => Filter::Value(value.clone()),
+            None => Filter::Name(name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use crate::{
+        sql::{SqlDialect, SqlFilterBuilder},
+        testing::schema::RequestItem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let bindings: BTreeMap<String, Value> = btree_map_into! {
+            "tenant" => Value::from("acme"),
+            "user.id" => Value::from(42),
+            "archived" => Value::from(false),
+            "tags" => Value::Repeated(vec!["a".into(), "b".into()]),
+            "deleteTime" => Value::Null,
+        };
+        macro_rules! check {
+            ($source:expr, $expected:expr) => {{
+                let residual = Filter::parse($source).unwrap().partial_evaluate(&bindings);
+                assert_eq!(residual.to_string(), $expected, "{}", $source);
+            }};
+        }
+
+        check!("", "");
+        check!("x = 1", "x = 1");
+        check!(r#"tenant = "acme" AND x = 1"#, "x = 1");
+        check!(r#"tenant = "other" AND x = 1"#, "false");
+        check!(r#"tenant = "other" OR x = 1"#, "x = 1");
+        check!(r#"tenant = ("other" OR "acme")"#, "");
+        check!(r#"tenant = "a*""#, "");
+        check!("user.id > 18 AND NOT archived", "");
+        check!("user.id < 18 OR archived", "false");
+        check!(r#"tags:"b" AND NOT tags:"c""#, "");
+        check!("deleteTime = null AND user.id:*", "");
+        check!("x = user.id AND f(tenant, y)", r#"f("acme", y) AND x = 42"#);
+        check!("user.id = x", "42 = x");
+        check!("NOT (tenant = \"acme\" AND x)", "NOT x");
+        // Restrictions of null members never match, nor do their negations
+        check!("deleteTime > 5 AND x", "false");
+        check!("NOT deleteTime > 5", "false");
+        check!("deleteTime > 5 OR x", "x");
+        check!("NOT (deleteTime > 5 AND x)", "NOT x");
+        check!("deleteTime OR NOT deleteTime", "false");
+    }
+
+    #[test]
+    fn sql() {
+        let schema = RequestItem::get_schema();
+        let bindings: BTreeMap<String, Value> = btree_map_into! {
+            "user.age" => Value::Null,
+        };
+        let residual = Filter::parse("user.age > 5 OR NOT task.deleted")
+            .unwrap()
+            .partial_evaluate(&bindings);
+        assert_eq!(residual.to_string(), "NOT task.deleted");
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&residual)
+                .unwrap()
+                .0,
+            r#"NOT ("task"."deleted")"#
+        );
+    }
+}
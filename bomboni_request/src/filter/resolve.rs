@@ -0,0 +1,136 @@
+use std::convert::Infallible;
+
+use crate::{
+    filter::{Filter, FilterComparator, visit::FilterFolder},
+    schema::{EnumSchema, Schema},
+    value::Value,
+};
+
+impl Filter {
+    /// Resolves literals of the filter against schema.
+    ///
+    /// Bare identifiers and strings compared to enum fields, such as `state = PUBLISHED`, become enum values.
+    /// An identifier is kept as a member name only if it is a schema member, and not a value of the enum.
+    ///
+    /// Filters should be resolved before they are validated, evaluated, or built into SQL.
+    #[must_use]
+    pub fn resolve(&self, schema: &Schema) -> Self {
+        let Ok(filter) = SchemaResolver { schema }.fold_filter(self.clone());
+        filter
+    }
+}
+
+struct SchemaResolver<'a> {
+    schema: &'a Schema,
+}
+
+impl SchemaResolver<'_> {
+    fn resolve_enum_argument(&self, argument: Filter, enum_schema: &EnumSchema) -> Filter {
+        match argument {
+            Filter::Name(name)
+                if enum_schema.contains(&name) || self.schema.get_member(&name).is_none() =>
+            {
+                Filter::Value(Value::Enum(name))
+            }
+            Filter::Value(Value::String(name)) => Filter::Value(Value::Enum(name)),
+            Filter::Composite(composite) => Filter::Composite(Box::new(match *composite {
+                Filter::Conjunction(parts) => Filter::Conjunction(
+                    parts
+                        .into_iter()
+                        .map(|part| self.resolve_enum_argument(part, enum_schema))
+                        .collect(),
+                ),
+                Filter::Disjunction(parts) => Filter::Disjunction(
+                    parts
+                        .into_iter()
+                        .map(|part| self.resolve_enum_argument(part, enum_schema))
+                        .collect(),
+                ),
+                composite => self.resolve_enum_argument(composite, enum_schema),
+            })),
+            argument => argument,
+        }
+    }
+}
+
+impl FilterFolder for SchemaResolver<'_> {
+    type Error = Infallible;
+
+    fn fold_restriction(
+        &mut self,
+        comparable: Filter,
+        comparator: FilterComparator,
+        argument: Filter,
+    ) -> Result<Filter, Self::Error> {
+        let enum_schema = match &comparable {
+            Filter::Name(name) => self
+                .schema
+                .get_field(name)
+                .and_then(|field| field.enum_schema.as_ref()),
+            _ => None,
+        };
+        let argument = match enum_schema {
+            Some(enum_schema) => self.resolve_enum_argument(argument, enum_schema),
+            None => self.fold_filter(argument)?,
+        };
+        Ok(Filter::Restriction(
+            Box::new(self.fold_filter(comparable)?),
+            comparator,
+            Box::new(argument),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use crate::schema::{FieldMemberSchema, ValueType};
+
+    use super::*;
+
+    #[test]
+    fn resolve_enums() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "state" => FieldMemberSchema::new_enum(EnumSchema::new(["DRAFT", "PUBLISHED"])),
+                "title" => FieldMemberSchema::new(ValueType::String),
+                "DRAFT" => FieldMemberSchema::new(ValueType::String),
+            },
+        };
+        macro_rules! check {
+            ($source:expr, $expected:expr) => {{
+                let filter = Filter::parse($source).unwrap().resolve(&schema);
+                assert_eq!(filter, Filter::parse($expected).unwrap().resolve(&schema));
+                assert_eq!(filter.to_string(), $expected);
+            }};
+        }
+
+        check!("state = PUBLISHED", "state = PUBLISHED");
+        check!(r#"state = "PUBLISHED""#, "state = PUBLISHED");
+        check!("state != (DRAFT OR LOL)", "state != (DRAFT OR LOL)");
+        check!(
+            r#"title = DRAFT AND state:"DRAFT""#,
+            "title = DRAFT AND state:DRAFT"
+        );
+
+        let filter = Filter::parse("state = title OR state = DRAFT")
+            .unwrap()
+            .resolve(&schema);
+        assert_eq!(
+            filter,
+            Filter::Disjunction(vec![
+                Filter::Restriction(
+                    Box::new(Filter::Name("state".into())),
+                    FilterComparator::Equal,
+                    Box::new(Filter::Name("title".into())),
+                ),
+                Filter::Restriction(
+                    Box::new(Filter::Name("state".into())),
+                    FilterComparator::Equal,
+                    Box::new(Filter::Value(Value::Enum("DRAFT".into()))),
+                ),
+            ])
+        );
+    }
+}
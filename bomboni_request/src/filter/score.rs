@@ -0,0 +1,160 @@
+use crate::{
+    filter::{Filter, function::FunctionRegistry},
+    schema::{Schema, SchemaMapped},
+    value::Value,
+};
+
+/// Result of evaluating a filter with relevance scoring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FilterScore {
+    /// Whether the filter matches, with the exact semantics of [`Filter::evaluate`].
+    pub matched: bool,
+    /// Weighted number of matched factors.
+    pub score: u32,
+}
+
+impl Filter {
+    /// Evaluates the filter against an item, scoring its relevance.
+    ///
+    /// Each matched factor of a sequence or conjunction, and each matched alternative of a disjunction,
+    /// adds the weight of its field to the score, see [`crate::schema::FieldMemberSchema::weight`].
+    /// Factors that do not restrict a schema field, such as global restrictions and functions, have weight 1.
+    /// Items that do not match the filter may still have a score, allowing fuzzy matching of sequences.
+    pub fn evaluate_score<T>(&self, item: &T, schema: &Schema) -> FilterScore
+    where
+        T: SchemaMapped,
+    {
+        self.evaluate_score_with_functions(item, schema, &FunctionRegistry::new())
+    }
+
+    /// Evaluates the filter against an item with relevance scoring, calling functions from the registry.
+    pub fn evaluate_score_with_functions<T>(
+        &self,
+        item: &T,
+        schema: &Schema,
+        functions: &FunctionRegistry,
+    ) -> FilterScore
+    where
+        T: SchemaMapped,
+    {
+        FilterScore {
+            matched: self.evaluate_with_functions(item, functions) == Some(Value::Boolean(true)),
+            score: self.score(item, schema, functions),
+        }
+    }
+
+    fn score<T>(&self, item: &T, schema: &Schema, functions: &FunctionRegistry) -> u32
+    where
+        T: SchemaMapped,
+    {
+        match self {
+            Self::Conjunction(parts) | Self::Disjunction(parts) => parts
+                .iter()
+                .map(|part| part.score(item, schema, functions))
+                .fold(0, u32::saturating_add),
+            Self::Composite(tree) => tree.score(item, schema, functions),
+            term => {
+                if term.evaluate_with_functions(item, functions) == Some(Value::Boolean(true)) {
+                    term.get_score_weight(schema)
+                } else {
+                    0
+                }
+            }
+        }
+    }
+
+    fn get_score_weight(&self, schema: &Schema) -> u32 {
+        match self {
+            Self::Negate(tree) => tree.get_score_weight(schema),
+            Self::Restriction(comparable, _, _) => comparable.get_score_weight(schema),
+            Self::Name(name) => schema.get_field(name).map_or(1, |field| field.weight),
+            _ => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use crate::schema::{FieldMemberSchema, ValueType};
+
+    use super::*;
+
+    struct ArticleItem {
+        title: String,
+        tags: Vec<String>,
+        views: i64,
+    }
+
+    impl SchemaMapped for ArticleItem {
+        fn get_field(&self, name: &str) -> Value {
+            match name {
+                "title" => self.title.clone().into(),
+                "tags" => Value::Repeated(self.tags.iter().cloned().map(Value::from).collect()),
+                "views" => self.views.into(),
+                _ => unimplemented!("SchemaMapped for ArticleItem::{name}"),
+            }
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema {
+                    weight: 3,
+                    ..FieldMemberSchema::new(ValueType::String)
+                },
+                "tags" => FieldMemberSchema::new_repeated(ValueType::String),
+                "views" => FieldMemberSchema::new_ordered(ValueType::Integer),
+            },
+        };
+        let item = ArticleItem {
+            title: "New York Giants".into(),
+            tags: vec!["sports".into(), "football".into()],
+            views: 100,
+        };
+        let score = |source: &str| {
+            Filter::parse(source)
+                .unwrap()
+                .evaluate_score(&item, &schema)
+        };
+
+        assert_eq!(
+            score(""),
+            FilterScore {
+                matched: true,
+                score: 0,
+            }
+        );
+        assert_eq!(
+            score(r#"title:"York" tags:"sports" views > 10"#),
+            FilterScore {
+                matched: true,
+                score: 5,
+            }
+        );
+        assert_eq!(
+            score(r#"title:"York" tags:"baseball" views > 10"#),
+            FilterScore {
+                matched: false,
+                score: 4,
+            }
+        );
+        assert_eq!(
+            score(r#"title:("Giants" OR "Yankees") AND (tags:"sports" OR tags:"football")"#),
+            FilterScore {
+                matched: true,
+                score: 5,
+            }
+        );
+        assert_eq!(
+            score(r#"NOT title:"Yankees" AND (views < 10 OR tags:"news")"#),
+            FilterScore {
+                matched: false,
+                score: 3,
+            }
+        );
+    }
+}
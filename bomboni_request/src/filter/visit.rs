@@ -0,0 +1,292 @@
+#![allow(clippy::missing_errors_doc)]
+
+use crate::{
+    filter::{Filter, FilterComparator},
+    value::Value,
+};
+
+/// Immutable traversal of a filter tree.
+///
+/// Each method handles one variant of [`Filter`] and recurses into its children by default.
+/// Overriding a method replaces the traversal of that variant, so call [`FilterVisitor::visit_filter`] on children to keep descending.
+/// The first error returned by any method stops the traversal and is propagated to the caller.
+pub trait FilterVisitor {
+    /// Error that stops the traversal.
+    type Error;
+
+    /// Visits any filter, dispatching to the method for its variant.
+    fn visit_filter(&mut self, filter: &Filter) -> Result<(), Self::Error> {
+        visit_filter(self, filter)
+    }
+
+    /// Visits parts of a conjunction.
+    fn visit_conjunction(&mut self, parts: &[Filter]) -> Result<(), Self::Error> {
+        visit_filters(self, parts)
+    }
+
+    /// Visits parts of a disjunction.
+    fn visit_disjunction(&mut self, parts: &[Filter]) -> Result<(), Self::Error> {
+        visit_filters(self, parts)
+    }
+
+    /// Visits a negated filter.
+    fn visit_negate(&mut self, tree: &Filter) -> Result<(), Self::Error> {
+        self.visit_filter(tree)
+    }
+
+    /// Visits a restriction.
+    fn visit_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> Result<(), Self::Error> {
+        let _ = comparator;
+        self.visit_filter(comparable)?;
+        self.visit_filter(argument)
+    }
+
+    /// Visits a function call.
+    fn visit_function(&mut self, name: &str, arguments: &[Filter]) -> Result<(), Self::Error> {
+        let _ = name;
+        visit_filters(self, arguments)
+    }
+
+    /// Visits a parenthesized filter.
+    fn visit_composite(&mut self, tree: &Filter) -> Result<(), Self::Error> {
+        self.visit_filter(tree)
+    }
+
+    /// Visits a member name.
+    fn visit_name(&mut self, name: &str) -> Result<(), Self::Error> {
+        let _ = name;
+        Ok(())
+    }
+
+    /// Visits a literal value.
+    fn visit_value(&mut self, value: &Value) -> Result<(), Self::Error> {
+        let _ = value;
+        Ok(())
+    }
+}
+
+/// Calls the visitor method matching the variant of the filter.
+pub fn visit_filter<V>(visitor: &mut V, filter: &Filter) -> Result<(), V::Error>
+where
+    V: FilterVisitor + ?Sized,
+{
+    match filter {
+        Filter::Conjunction(parts) => visitor.visit_conjunction(parts),
+        Filter::Disjunction(parts) => visitor.visit_disjunction(parts),
+        Filter::Negate(tree) => visitor.visit_negate(tree),
+        Filter::Restriction(comparable, comparator, argument) => {
+            visitor.visit_restriction(comparable, *comparator, argument)
+        }
+        Filter::Function(name, arguments) => visitor.visit_function(name, arguments),
+        Filter::Composite(tree) => visitor.visit_composite(tree),
+        Filter::Name(name) => visitor.visit_name(name),
+        Filter::Value(value) => visitor.visit_value(value),
+    }
+}
+
+/// Visits each filter in order.
+pub fn visit_filters<V>(visitor: &mut V, filters: &[Filter]) -> Result<(), V::Error>
+where
+    V: FilterVisitor + ?Sized,
+{
+    for filter in filters {
+        visitor.visit_filter(filter)?;
+    }
+    Ok(())
+}
+
+/// Rewriting traversal of a filter tree.
+///
+/// Each method takes ownership of one variant of [`Filter`] and returns its replacement.
+/// By default, children are folded and the variant is rebuilt unchanged.
+/// The first error returned by any method stops the traversal and is propagated to the caller.
+pub trait FilterFolder {
+    /// Error that stops the traversal.
+    type Error;
+
+    /// Folds any filter, dispatching to the method for its variant.
+    fn fold_filter(&mut self, filter: Filter) -> Result<Filter, Self::Error> {
+        fold_filter(self, filter)
+    }
+
+    /// Folds parts of a conjunction.
+    fn fold_conjunction(&mut self, parts: Vec<Filter>) -> Result<Filter, Self::Error> {
+        Ok(Filter::Conjunction(fold_filters(self, parts)?))
+    }
+
+    /// Folds parts of a disjunction.
+    fn fold_disjunction(&mut self, parts: Vec<Filter>) -> Result<Filter, Self::Error> {
+        Ok(Filter::Disjunction(fold_filters(self, parts)?))
+    }
+
+    /// Folds a negated filter.
+    fn fold_negate(&mut self, tree: Filter) -> Result<Filter, Self::Error> {
+        Ok(Filter::Negate(Box::new(self.fold_filter(tree)?)))
+    }
+
+    /// Folds a restriction.
+    fn fold_restriction(
+        &mut self,
+        comparable: Filter,
+        comparator: FilterComparator,
+        argument: Filter,
+    ) -> Result<Filter, Self::Error> {
+        Ok(Filter::Restriction(
+            Box::new(self.fold_filter(comparable)?),
+            comparator,
+            Box::new(self.fold_filter(argument)?),
+        ))
+    }
+
+    /// Folds a function call.
+    fn fold_function(
+        &mut self,
+        name: String,
+        arguments: Vec<Filter>,
+    ) -> Result<Filter, Self::Error> {
+        Ok(Filter::Function(name, fold_filters(self, arguments)?))
+    }
+
+    /// Folds a parenthesized filter.
+    fn fold_composite(&mut self, tree: Filter) -> Result<Filter, Self::Error> {
+        Ok(Filter::Composite(Box::new(self.fold_filter(tree)?)))
+    }
+
+    /// Folds a member name.
+    fn fold_name(&mut self, name: String) -> Result<Filter, Self::Error> {
+        Ok(Filter::Name(name))
+    }
+
+    /// Folds a literal value.
+    fn fold_value(&mut self, value: Value) -> Result<Filter, Self::Error> {
+        Ok(Filter::Value(value))
+    }
+}
+
+/// Calls the folder method matching the variant of the filter.
+pub fn fold_filter<F>(folder: &mut F, filter: Filter) -> Result<Filter, F::Error>
+where
+    F: FilterFolder + ?Sized,
+{
+    match filter {
+        Filter::Conjunction(parts) => folder.fold_conjunction(parts),
+        Filter::Disjunction(parts) => folder.fold_disjunction(parts),
+        Filter::Negate(tree) => folder.fold_negate(*tree),
+        Filter::Restriction(comparable, comparator, argument) => {
+            folder.fold_restriction(*comparable, comparator, *argument)
+        }
+        Filter::Function(name, arguments) => folder.fold_function(name, arguments),
+        Filter::Composite(tree) => folder.fold_composite(*tree),
+        Filter::Name(name) => folder.fold_name(name),
+        Filter::Value(value) => folder.fold_value(value),
+    }
+}
+
+/// Folds each filter in order.
+pub fn fold_filters<F>(folder: &mut F, filters: Vec<Filter>) -> Result<Vec<Filter>, F::Error>
+where
+    F: FilterFolder + ?Sized,
+{
+    filters
+        .into_iter()
+        .map(|filter| folder.fold_filter(filter))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, convert::Infallible};
+
+    use super::*;
+
+    #[test]
+    fn visit() {
+        struct MemberCollector(BTreeSet<String>);
+
+        impl FilterVisitor for MemberCollector {
+            type Error = Infallible;
+
+            fn visit_name(&mut self, name: &str) -> Result<(), Self::Error> {
+                self.0.insert(name.into());
+                Ok(())
+            }
+        }
+
+        let filter = Filter::parse(r#"a = 1 AND NOT (b:"x" OR f(c, 2)) AND a < d"#).unwrap();
+        let mut collector = MemberCollector(BTreeSet::new());
+        collector.visit_filter(&filter).unwrap();
+        assert_eq!(
+            collector.0.into_iter().collect::<Vec<_>>(),
+            vec!["a", "b", "c", "d"]
+        );
+
+        struct FunctionForbidder;
+
+        impl FilterVisitor for FunctionForbidder {
+            type Error = String;
+
+            fn visit_function(&mut self, name: &str, _: &[Filter]) -> Result<(), Self::Error> {
+                Err(name.into())
+            }
+        }
+
+        assert_eq!(
+            FunctionForbidder.visit_filter(&filter),
+            Err("f".to_string())
+        );
+        assert!(
+            FunctionForbidder
+                .visit_filter(&Filter::parse("a = 1").unwrap())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn fold() {
+        struct Renamer;
+
+        impl FilterFolder for Renamer {
+            type Error = Infallible;
+
+            fn fold_name(&mut self, name: String) -> Result<Filter, Self::Error> {
+                Ok(Filter::Name(format!("user.{name}")))
+            }
+        }
+
+        let filter = Filter::parse(r#"a = 1 AND NOT (b:"x" OR f(c))"#).unwrap();
+        assert_eq!(
+            Renamer.fold_filter(filter).unwrap().to_string(),
+            r#"user.a = 1 AND NOT (user.b:"x" OR f(user.c))"#
+        );
+
+        struct Aliases;
+
+        impl FilterFolder for Aliases {
+            type Error = String;
+
+            fn fold_name(&mut self, name: String) -> Result<Filter, Self::Error> {
+                match name.as_str() {
+                    "age" => Ok(Filter::Name("user.age".into())),
+                    _ => Err(name),
+                }
+            }
+        }
+
+        assert_eq!(
+            Aliases
+                .fold_filter(Filter::parse("age > 30").unwrap())
+                .unwrap()
+                .to_string(),
+            "user.age > 30"
+        );
+        assert_eq!(
+            Aliases.fold_filter(Filter::parse("age > 30 OR name = \"a\"").unwrap()),
+            Err("name".to_string())
+        );
+    }
+}
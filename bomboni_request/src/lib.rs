@@ -6,21 +6,32 @@ extern crate regex;
 /// Error handling types.
 pub mod error;
 
+/// Elasticsearch query generation.
+pub mod elastic;
+
 /// Filter expression parsing and evaluation.
 pub mod filter;
 
+/// MongoDB query document generation.
+pub mod mongo;
+
 /// Query ordering parsing and validation.
 pub mod ordering;
 
 /// Parsing utilities and traits.
 pub mod parse;
 
+mod proto;
+
 /// Query builders for list and search operations.
 pub mod query;
 
 /// Schema definitions for validation.
 pub mod schema;
 
+/// Source spans for error reporting.
+pub mod span;
+
 /// SQL generation utilities.
 pub mod sql;
 
@@ -0,0 +1,769 @@
+use serde_json::{Map, Value as JsonValue, json};
+
+use crate::{
+    filter::{
+        Filter, FilterComparator,
+        error::{FilterError, FilterResult},
+        split_wildcard,
+    },
+    schema::{FieldMemberSchema, MemberSchema, Schema},
+    sql::SqlRenameMap,
+    value::{Value, duration_nanos_i64},
+};
+
+/// Builder for MongoDB query documents.
+///
+/// Logical operators become `$and`, `$or` and `$nor`, wildcards and substrings become `$regex`,
+/// and restrictions on repeated fields and resources become `$elemMatch`, matching if any element matches.
+/// Timestamps are extended JSON dates, and durations are nanoseconds.
+pub struct MongoFilterBuilder<'a> {
+    schema: &'a Schema,
+    rename_map: Option<&'a SqlRenameMap>,
+    case_insensitive_regex: bool,
+    /// Name of the repeated resource, while building an `$elemMatch` document.
+    repeated_resource: Option<String>,
+}
+
+impl<'a> MongoFilterBuilder<'a> {
+    /// Creates a new MongoDB filter builder.
+    pub const fn new(schema: &'a Schema) -> Self {
+        Self {
+            schema,
+            rename_map: None,
+            case_insensitive_regex: false,
+            repeated_resource: None,
+        }
+    }
+
+    /// Sets the rename map.
+    pub const fn set_rename_map(&mut self, rename_map: &'a SqlRenameMap) -> &mut Self {
+        self.rename_map = Some(rename_map);
+        self
+    }
+
+    /// Enables case insensitive regular expressions.
+    pub const fn case_insensitive_regex(&mut self) -> &mut Self {
+        self.case_insensitive_regex = true;
+        self
+    }
+
+    /// Builds a MongoDB query document.
+    ///
+    /// The filter is validated against the schema first.
+    /// Functions have no MongoDB equivalent, and are reported as unknown.
+    ///
+    /// # Errors
+    ///
+    /// Will return any error returned by [`Filter::validate`],
+    /// or [`FilterError::InvalidResultValueType`] if a map key contains `.` or starts with `$`.
+    pub fn build(&mut self, filter: &Filter) -> FilterResult<JsonValue> {
+        filter.validate(self.schema, None)?;
+        self.build_filter(filter)
+    }
+
+    fn build_filter(&mut self, filter: &Filter) -> FilterResult<JsonValue> {
+        match filter {
+            Filter::Conjunction(parts) => self.build_connective(parts, false),
+            Filter::Disjunction(parts) => self.build_connective(parts, true),
+            Filter::Negate(tree) => self.build_negation(tree),
+            Filter::Restriction(comparable, comparator, argument) => {
+                self.build_restriction(comparable, *comparator, argument)
+            }
+            Filter::Composite(tree) => self.build_filter(tree),
+            // Boolean members
+            Filter::Name(name) => Ok(get_document(self.get_field_name(name)?, true)),
+            Filter::Value(value) => Ok(get_document("$expr", get_json_value(value.clone())?)),
+            Filter::Function(name, _) => Err(FilterError::UnknownFunction(name.clone())),
+        }
+    }
+
+    fn build_connective(&mut self, parts: &[Filter], disjunction: bool) -> FilterResult<JsonValue> {
+        let documents = parts
+            .iter()
+            .map(|part| self.build_filter(part))
+            .collect::<FilterResult<_>>()?;
+        Ok(join_documents(documents, disjunction))
+    }
+
+    /// Builds a document matching documents for which the filter is false.
+    ///
+    /// Restrictions of null members are unknown, as in [`Filter::evaluate`], and neither they nor their negations match.
+    /// Since `$nor` also matches documents where the members are null or missing,
+    /// negations of restrictions of nullable members also require the members to not be null.
+    fn build_negation(&mut self, filter: &Filter) -> FilterResult<JsonValue> {
+        if !self.has_nullable_restrictions(filter) {
+            // `$not` only applies to operator expressions of a single field
+            return Ok(get_document("$nor", vec![self.build_filter(filter)?]));
+        }
+        match filter {
+            Filter::Conjunction(parts) | Filter::Disjunction(parts) => {
+                let documents = parts
+                    .iter()
+                    .map(|part| self.build_negation(part))
+                    .collect::<FilterResult<_>>()?;
+                Ok(join_documents(
+                    documents,
+                    matches!(filter, Filter::Conjunction(_)),
+                ))
+            }
+            Filter::Negate(tree) => self.build_filter(tree),
+            Filter::Composite(tree) => self.build_negation(tree),
+            filter => {
+                let mut documents = vec![get_document("$nor", vec![self.build_filter(filter)?])];
+                for name in self.get_nullable_names(filter) {
+                    documents.push(get_document(
+                        self.get_field_name(name)?,
+                        json!({ "$ne": null }),
+                    ));
+                }
+                Ok(join_documents(documents, false))
+            }
+        }
+    }
+
+    fn has_nullable_restrictions(&self, filter: &Filter) -> bool {
+        match filter {
+            Filter::Conjunction(parts) | Filter::Disjunction(parts) => parts
+                .iter()
+                .any(|part| self.has_nullable_restrictions(part)),
+            Filter::Negate(tree) | Filter::Composite(tree) => self.has_nullable_restrictions(tree),
+            filter => !self.get_nullable_names(filter).is_empty(),
+        }
+    }
+
+    /// Gets names of nullable members that a restriction is unknown for, if they are null.
+    fn get_nullable_names<'f>(&self, filter: &'f Filter) -> Vec<&'f str> {
+        let operands = match filter {
+            // Presence and null checks are never unknown
+            Filter::Restriction(_, _, argument)
+                if matches!(argument.as_ref(), Filter::Value(Value::Any | Value::Null)) =>
+            {
+                return Vec::new();
+            }
+            Filter::Restriction(comparable, _, argument) => {
+                vec![comparable.as_ref(), argument.as_ref()]
+            }
+            Filter::Name(_) => vec![filter],
+            _ => return Vec::new(),
+        };
+        operands
+            .into_iter()
+            .filter_map(|operand| match operand {
+                Filter::Name(name)
+                    if self.schema.split_repeated_resource(name).is_none()
+                        && self
+                            .schema
+                            .get_field(name)
+                            .is_some_and(|field| field.nullable) =>
+                {
+                    Some(name.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<JsonValue> {
+        let Filter::Name(name) = comparable else {
+            return self.build_expression(comparable, comparator, argument);
+        };
+
+        if self.repeated_resource.is_none()
+            && let Some((resource_name, _)) = self.schema.split_repeated_resource(name)
+        {
+            return self.build_elem_match(resource_name, comparable, comparator, argument);
+        }
+
+        if let Some(MemberSchema::Map(_)) = self.schema.get_member(name) {
+            return self.build_map_key_presence(name, argument);
+        }
+
+        let field = self.schema.get_field(name);
+        let repeated = field.is_some_and(|field| field.repeated);
+        // Inequalities of null fields are unknown, whereas `$ne` and `$nin` match them
+        let nullable = field.is_some_and(|field| field.nullable);
+
+        if let Some((parts, disjunction)) = argument.get_composite_argument() {
+            let list_operator = match (comparator, disjunction) {
+                (FilterComparator::Equal, true) => Some("$in"),
+                // Elements of repeated fields are not all compared to the same values
+                (FilterComparator::NotEqual, false) if !repeated => Some("$nin"),
+                _ => None,
+            };
+            if let Some(list_operator) = list_operator
+                && parts.iter().all(|part| match part {
+                    Filter::Value(Value::String(value)) => split_wildcard(value).is_none(),
+                    Filter::Value(value) => !matches!(value, Value::Any | Value::Null),
+                    _ => false,
+                })
+            {
+                let mut values = parts
+                    .iter()
+                    .map(|part| match part {
+                        Filter::Value(value) => Self::build_value(value, field),
+                        _ => unreachable!(),
+                    })
+                    .collect::<FilterResult<Vec<_>>>()?;
+                if list_operator == "$nin" && nullable {
+                    values.push(JsonValue::Null);
+                }
+                return Ok(get_document(
+                    self.get_field_name(name)?,
+                    get_element_operation(get_document(list_operator, values), repeated),
+                ));
+            }
+
+            // Expand into separate restrictions, such that `a:(x OR y)` is `a:x OR a:y`
+            let documents = parts
+                .iter()
+                .map(|part| self.build_restriction(comparable, comparator, part))
+                .collect::<FilterResult<_>>()?;
+            return Ok(join_documents(documents, disjunction));
+        }
+
+        let Filter::Value(value) = argument else {
+            return self.build_expression(comparable, comparator, argument);
+        };
+
+        // Presence and null checks apply to the field as a whole
+        let field_name = self.get_field_name(name)?;
+        match (comparator, value) {
+            (FilterComparator::Has, Value::Any) | (FilterComparator::NotEqual, Value::Null) => {
+                return Ok(get_document(field_name, json!({ "$ne": null })));
+            }
+            (FilterComparator::Equal, Value::Null) => {
+                return Ok(get_document(field_name, json!({ "$eq": null })));
+            }
+            _ => {}
+        }
+
+        let operation = if let Value::String(value) = value
+            && matches!(
+                comparator,
+                FilterComparator::Equal | FilterComparator::NotEqual
+            )
+            && let Some((leading, literal, trailing)) = split_wildcard(value)
+        {
+            let regex = self.build_regex(format!(
+                "{}{}{}",
+                if leading { "" } else { "^" },
+                regex::escape(literal),
+                if trailing { "" } else { "$" },
+            ));
+            if comparator == FilterComparator::NotEqual && nullable {
+                json!({ "$not": regex, "$ne": null })
+            } else if comparator == FilterComparator::NotEqual {
+                get_document("$not", regex)
+            } else {
+                regex
+            }
+        } else if let Value::String(value) = value
+            && comparator == FilterComparator::Has
+            && !repeated
+        {
+            self.build_regex(regex::escape(value))
+        } else if comparator == FilterComparator::NotEqual && nullable {
+            json!({ "$nin": [Self::build_value(value, field)?, null] })
+        } else {
+            get_document(get_operator(comparator), Self::build_value(value, field)?)
+        };
+
+        Ok(get_document(
+            field_name,
+            get_element_operation(operation, repeated),
+        ))
+    }
+
+    /// Builds a restriction on fields of a repeated resource, matching if any element matches.
+    fn build_elem_match(
+        &mut self,
+        resource_name: &str,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<JsonValue> {
+        let field_name = self.get_field_name(resource_name)?;
+        self.repeated_resource = Some(resource_name.into());
+        let result = self.build_restriction(comparable, comparator, argument);
+        self.repeated_resource = None;
+        Ok(get_document(
+            field_name,
+            get_document("$elemMatch", result?),
+        ))
+    }
+
+    fn build_map_key_presence(&mut self, name: &str, key: &Filter) -> FilterResult<JsonValue> {
+        if let Some((parts, disjunction)) = key.get_composite_argument() {
+            let documents = parts
+                .iter()
+                .map(|part| self.build_map_key_presence(name, part))
+                .collect::<FilterResult<_>>()?;
+            return Ok(join_documents(documents, disjunction));
+        }
+
+        let field_name = self.get_field_name(name)?;
+        let key = match key {
+            // Presence of the map itself
+            Filter::Value(Value::Any) => {
+                return Ok(get_document(field_name, json!({ "$ne": null })));
+            }
+            Filter::Name(key) | Filter::Value(Value::String(key)) => key.clone(),
+            Filter::Value(key) => key.to_string(),
+            _ => return Err(FilterError::InvalidResultValueType),
+        };
+        Ok(get_document(
+            format!("{field_name}.{}", check_map_key(&key)?),
+            json!({ "$exists": true }),
+        ))
+    }
+
+    /// Builds a comparison between expressions, such as members compared with each other.
+    fn build_expression(
+        &self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<JsonValue> {
+        if comparator == FilterComparator::Has {
+            return Err(FilterError::UnsuitableComparator(comparator));
+        }
+        Ok(get_document(
+            "$expr",
+            get_document(
+                get_operator(comparator),
+                vec![
+                    self.build_operand(comparable)?,
+                    self.build_operand(argument)?,
+                ],
+            ),
+        ))
+    }
+
+    fn build_operand(&self, operand: &Filter) -> FilterResult<JsonValue> {
+        match operand {
+            Filter::Name(name) => Ok(format!("${}", self.get_field_name(name)?).into()),
+            // Literal strings starting with `$` would be field paths
+            Filter::Value(value) => Ok(get_document("$literal", get_json_value(value.clone())?)),
+            Filter::Composite(tree) => self.build_operand(tree),
+            _ => Err(FilterError::InvalidResultValueType),
+        }
+    }
+
+    fn build_regex(&self, pattern: String) -> JsonValue {
+        if self.case_insensitive_regex {
+            json!({ "$regex": pattern, "$options": "i" })
+        } else {
+            json!({ "$regex": pattern })
+        }
+    }
+
+    /// Converts a literal to the type of the field, and enums to their numeric values, if any.
+    fn build_value(value: &Value, field: Option<&FieldMemberSchema>) -> FilterResult<JsonValue> {
+        let value = match field {
+            Some(field)
+                if value.value_type().is_some_and(|literal_type| {
+                    literal_type != field.value_type
+                        && literal_type.is_literal_coercible(field.value_type)
+                }) =>
+            {
+                value.coerce_literal(field.value_type)?
+            }
+            _ => value.clone(),
+        };
+        Ok(match value {
+            Value::Enum(name) => field
+                .and_then(|field| field.enum_schema.as_ref())
+                .and_then(|enum_schema| enum_schema.get_number(&name))
+                .map_or_else(|| name.into(), Into::into),
+            value => get_json_value(value)?,
+        })
+    }
+
+    fn get_field_name(&self, name: &str) -> FilterResult<String> {
+        // Fields of repeated resources are relative to the element
+        if let Some(resource_name) = &self.repeated_resource
+            && let Some(field_name) = name
+                .strip_prefix(resource_name.as_str())
+                .and_then(|field_name| field_name.strip_prefix('.'))
+        {
+            return Ok(field_name.into());
+        }
+        if let Some((map_name, key, _)) = self.schema.split_map_entry(name) {
+            return Ok(format!(
+                "{}.{}",
+                self.get_field_name(map_name)?,
+                check_map_key(key)?
+            ));
+        }
+        Ok(self
+            .rename_map
+            .map_or_else(|| name.into(), |rename_map| rename_map.rename_member(name)))
+    }
+}
+
+/// Checks that a map key can be used as a path segment.
+///
+/// Keys containing `.` would address nested fields, and keys starting with `$` would be operators.
+fn check_map_key(key: &str) -> FilterResult<&str> {
+    if key.contains('.') || key.starts_with('$') {
+        return Err(FilterError::InvalidResultValueType);
+    }
+    Ok(key)
+}
+
+const fn get_operator(comparator: FilterComparator) -> &'static str {
+    match comparator {
+        FilterComparator::Less => "$lt",
+        FilterComparator::LessOrEqual => "$lte",
+        FilterComparator::Greater => "$gt",
+        FilterComparator::GreaterOrEqual => "$gte",
+        FilterComparator::Equal | FilterComparator::Has => "$eq",
+        FilterComparator::NotEqual => "$ne",
+    }
+}
+
+fn get_document<K, V>(key: K, value: V) -> JsonValue
+where
+    K: Into<String>,
+    V: Into<JsonValue>,
+{
+    let mut document = Map::new();
+    document.insert(key.into(), value.into());
+    document.into()
+}
+
+/// Wraps an operation on repeated fields, such that it matches if any element matches.
+fn get_element_operation(operation: JsonValue, repeated: bool) -> JsonValue {
+    if repeated {
+        get_document("$elemMatch", operation)
+    } else {
+        operation
+    }
+}
+
+fn join_documents(mut documents: Vec<JsonValue>, disjunction: bool) -> JsonValue {
+    match documents.len() {
+        // Empty document matches everything
+        0 if disjunction => json!({ "$expr": false }),
+        0 => json!({}),
+        1 => documents.pop().unwrap(),
+        _ if disjunction => get_document("$or", documents),
+        _ => get_document("$and", documents),
+    }
+}
+
+fn get_json_value(value: Value) -> FilterResult<JsonValue> {
+    Ok(match value {
+        Value::Integer(value) => value.into(),
+        Value::Float(value) => value.into(),
+        Value::Boolean(value) => value.into(),
+        Value::String(value) | Value::Enum(value) => value.into(),
+        Value::Timestamp(value) => json!({ "$date": value.format_rfc3339().unwrap() }),
+        // Durations are stored as nanoseconds
+        Value::Duration(value) => duration_nanos_i64(&value)?.into(),
+        Value::Repeated(values) => values
+            .into_iter()
+            .map(get_json_value)
+            .collect::<FilterResult<_>>()?,
+        Value::Map(entries) => entries
+            .into_iter()
+            .map(|(key, value)| Ok((key, get_json_value(value)?)))
+            .collect::<FilterResult<Map<_, _>>>()?
+            .into(),
+        Value::Null | Value::Any => JsonValue::Null,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_common::date_time::UtcDateTime;
+    use bomboni_macros::btree_map_into;
+    use bomboni_proto::google::protobuf::Duration;
+
+    use crate::{
+        schema::{EnumSchema, MapMemberSchema, ResourceMemberSchema, ValueType},
+        testing::schema::RequestItem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let schema = RequestItem::get_schema();
+        let rename_map = SqlRenameMap {
+            members: btree_map_into! {
+                "user" => "u",
+                "task.userId" => "user_id",
+            },
+            ..Default::default()
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    MongoFilterBuilder::new(&schema)
+                        .set_rename_map(&rename_map)
+                        .build(&Filter::parse($filter).unwrap())
+                        .unwrap(),
+                    $expected,
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        check!("", json!({}));
+        check!(
+            r#"NOT task.deleted AND task.userId = "2" OR user.age >= 30"#,
+            // `OR` takes precedence over `AND`
+            json!({"$and": [
+                {"$nor": [{"task.deleted": true}]},
+                {"$or": [
+                    {"task.user_id": {"$eq": "2"}},
+                    {"u.age": {"$gte": 30}},
+                ]},
+            ]})
+        );
+        check!(
+            "user.age < 30 OR user.age > 60",
+            json!({"$or": [{"u.age": {"$lt": 30}}, {"u.age": {"$gt": 60}}]})
+        );
+        check!("user.age = (20 OR 30)", json!({"u.age": {"$in": [20, 30]}}));
+        check!(
+            "user.age != (20 AND 30)",
+            json!({"u.age": {"$nin": [20, 30]}})
+        );
+        check!(
+            "user.age > (20 AND 30)",
+            json!({"$and": [{"u.age": {"$gt": 20}}, {"u.age": {"$gt": 30}}]})
+        );
+        check!(
+            "user.id = task.userId",
+            json!({"$expr": {"$eq": ["$u.id", "$task.user_id"]}})
+        );
+        check!(
+            "user.displayName:*",
+            json!({"u.displayName": {"$ne": null}})
+        );
+
+        assert_eq!(
+            MongoFilterBuilder::new(&schema)
+                .build(&Filter::parse("user.id = 42").unwrap())
+                .unwrap_err(),
+            FilterError::InvalidType {
+                expected: ValueType::String,
+                actual: ValueType::Integer,
+            }
+        );
+        assert_eq!(
+            MongoFilterBuilder::new(&schema)
+                .build(&Filter::parse("f(user.id)").unwrap())
+                .unwrap_err(),
+            FilterError::UnknownFunction("f".into())
+        );
+    }
+
+    #[test]
+    fn regex() {
+        let schema = RequestItem::get_schema();
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    MongoFilterBuilder::new(&schema)
+                        .build(&Filter::parse($filter).unwrap())
+                        .unwrap(),
+                    $expected
+                );
+            };
+        }
+
+        check!(
+            r#"user.displayName = "Jo*""#,
+            json!({"user.displayName": {"$regex": "^Jo"}})
+        );
+        check!(
+            r#"user.displayName != "*a.b""#,
+            json!({"user.displayName": {"$not": {"$regex": "a\\.b$"}}})
+        );
+        check!(
+            r#"task.content:"x+y""#,
+            json!({"task.content": {"$regex": "x\\+y"}})
+        );
+        check!(
+            r#"task.tags:"urgent""#,
+            json!({"task.tags": {"$elemMatch": {"$eq": "urgent"}}})
+        );
+        check!(
+            r#"task.tags != "urgent""#,
+            json!({"task.tags": {"$elemMatch": {"$ne": "urgent"}}})
+        );
+
+        assert_eq!(
+            MongoFilterBuilder::new(&schema)
+                .case_insensitive_regex()
+                .build(&Filter::parse(r#"task.content:"x""#).unwrap())
+                .unwrap(),
+            json!({"task.content": {"$regex": "x", "$options": "i"}})
+        );
+    }
+
+    #[test]
+    fn values() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "state" => FieldMemberSchema::new_enum(EnumSchema::new_numbered([("DRAFT", 1), ("PUBLISHED", 2)])),
+                "createTime" => FieldMemberSchema::new_ordered(ValueType::Timestamp),
+                "deleteTime" => FieldMemberSchema::new_nullable(ValueType::Timestamp),
+                "timeout" => FieldMemberSchema::new_ordered(ValueType::Duration),
+                "price" => FieldMemberSchema::new_ordered(ValueType::Float),
+                "labels" => MapMemberSchema::new(ValueType::String, ValueType::String),
+                "authors" => ResourceMemberSchema::new_repeated(btree_map_into! {
+                    "country" => FieldMemberSchema::new(ValueType::String),
+                    "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                }),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    MongoFilterBuilder::new(&schema)
+                        .build(&Filter::parse($filter).unwrap().resolve(&schema))
+                        .unwrap(),
+                    $expected,
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        check!(
+            "state = (DRAFT OR PUBLISHED)",
+            json!({"state": {"$in": [1, 2]}})
+        );
+        check!(
+            r#"createTime > "2024-01-01T00:00:00Z""#,
+            json!({"createTime": {"$gt": {
+                "$date": UtcDateTime::parse_rfc3339("2024-01-01T00:00:00Z").unwrap().format_rfc3339().unwrap(),
+            }}})
+        );
+        check!("deleteTime = null", json!({"deleteTime": {"$eq": null}}));
+        check!("timeout < 1.5s", json!({"timeout": {"$lt": 1_500_000_000}}));
+        assert_eq!(
+            MongoFilterBuilder::new(&schema)
+                .build(&Filter::parse("timeout < 3000000h").unwrap())
+                .unwrap_err(),
+            FilterError::InvalidDuration(Duration::new(10_800_000_000, 0).to_string())
+        );
+        check!("price > 10", json!({"price": {"$gt": 10.0}}));
+        check!(
+            r#"labels.env = "prod" AND labels:(tier OR "team")"#,
+            json!({"$and": [
+                {"labels.env": {"$eq": "prod"}},
+                {"$or": [
+                    {"labels.tier": {"$exists": true}},
+                    {"labels.team": {"$exists": true}},
+                ]},
+            ]})
+        );
+        for filter in [
+            Filter::parse(r#"labels:"a.b""#).unwrap(),
+            Filter::parse(r#"labels:"$where""#).unwrap(),
+            Filter::Restriction(
+                Box::new(Filter::Name("labels.$where".into())),
+                FilterComparator::Equal,
+                Box::new(Filter::Value("prod".into())),
+            ),
+        ] {
+            assert_eq!(
+                MongoFilterBuilder::new(&schema).build(&filter).unwrap_err(),
+                FilterError::InvalidResultValueType,
+                "{filter}"
+            );
+        }
+        check!(
+            r#"authors.country = "SI" AND authors.age >= 18"#,
+            json!({"$and": [
+                {"authors": {"$elemMatch": {"country": {"$eq": "SI"}}}},
+                {"authors": {"$elemMatch": {"age": {"$gte": 18}}}},
+            ]})
+        );
+        check!(
+            r#"authors.country = ("SI" OR "HR")"#,
+            json!({"authors": {"$elemMatch": {"country": {"$in": ["SI", "HR"]}}}})
+        );
+    }
+
+    #[test]
+    fn nulls() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema::new_nullable(ValueType::String),
+                "priority" => FieldMemberSchema {
+                    ordered: true,
+                    ..FieldMemberSchema::new_nullable(ValueType::Integer)
+                },
+                "archived" => FieldMemberSchema::new_nullable(ValueType::Boolean),
+                "deleted" => FieldMemberSchema::new(ValueType::Boolean),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr, $expected:expr) => {
+                assert_eq!(
+                    MongoFilterBuilder::new(&schema)
+                        .build(&Filter::parse($filter).unwrap())
+                        .unwrap(),
+                    $expected,
+                    "{}",
+                    $filter
+                );
+            };
+        }
+
+        // Restrictions of null fields match neither themselves nor their negations
+        check!("priority != 1", json!({"priority": {"$nin": [1, null]}}));
+        check!(
+            "priority != (1 AND 2)",
+            json!({"priority": {"$nin": [1, 2, null]}})
+        );
+        check!(
+            r#"title != "a*""#,
+            json!({"title": {"$not": {"$regex": "^a"}, "$ne": null}})
+        );
+        check!(
+            "NOT priority > 1",
+            json!({"$and": [
+                {"$nor": [{"priority": {"$gt": 1}}]},
+                {"priority": {"$ne": null}},
+            ]})
+        );
+        check!(
+            "NOT (priority > 1 AND deleted) AND NOT archived",
+            json!({"$and": [
+                {"$or": [
+                    {"$and": [
+                        {"$nor": [{"priority": {"$gt": 1}}]},
+                        {"priority": {"$ne": null}},
+                    ]},
+                    {"$nor": [{"deleted": true}]},
+                ]},
+                {"$and": [
+                    {"$nor": [{"archived": true}]},
+                    {"archived": {"$ne": null}},
+                ]},
+            ]})
+        );
+        check!("NOT (NOT priority > 1)", json!({"priority": {"$gt": 1}}));
+        // Presence and null checks are never unknown
+        check!(
+            "NOT priority:* OR NOT priority = null",
+            json!({"$or": [
+                {"$nor": [{"priority": {"$ne": null}}]},
+                {"$nor": [{"priority": {"$eq": null}}]},
+            ]})
+        );
+    }
+}
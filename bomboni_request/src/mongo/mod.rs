@@ -0,0 +1,5 @@
+pub use filter::MongoFilterBuilder;
+pub use ordering::MongoOrderingBuilder;
+
+mod filter;
+mod ordering;
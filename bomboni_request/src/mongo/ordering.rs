@@ -0,0 +1,94 @@
+use serde_json::{Map, Value as JsonValue};
+
+use crate::{
+    ordering::{
+        Ordering, OrderingDirection,
+        error::{OrderingError, OrderingResult},
+    },
+    schema::Schema,
+    sql::SqlRenameMap,
+};
+
+/// Builder for MongoDB sort documents.
+pub struct MongoOrderingBuilder<'a> {
+    schema: &'a Schema,
+    rename_map: Option<&'a SqlRenameMap>,
+}
+
+impl<'a> MongoOrderingBuilder<'a> {
+    /// Creates a new MongoDB ordering builder.
+    pub const fn new(schema: &'a Schema) -> Self {
+        Self {
+            schema,
+            rename_map: None,
+        }
+    }
+
+    /// Sets the rename map.
+    pub const fn set_rename_map(&mut self, rename_map: &'a SqlRenameMap) -> &mut Self {
+        self.rename_map = Some(rename_map);
+        self
+    }
+
+    /// Builds a sort document, such as `{"age": -1, "displayName": 1}`.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderingError::UnknownMember`] if the ordering contains an unknown field name.
+    pub fn build(&self, ordering: &Ordering) -> OrderingResult<JsonValue> {
+        let mut document = Map::new();
+        for term in ordering.iter() {
+            if self.schema.get_member(&term.name).is_none() {
+                return Err(OrderingError::UnknownMember(term.name.clone()));
+            }
+
+            let name = self.rename_map.map_or_else(
+                || term.name.clone(),
+                |rename_map| rename_map.rename_member(&term.name),
+            );
+            let direction = match term.direction {
+                OrderingDirection::Ascending => 1,
+                OrderingDirection::Descending => -1,
+            };
+            document.insert(name, direction.into());
+        }
+        Ok(document.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+    use serde_json::json;
+
+    use crate::testing::schema::RequestItem;
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let schema = RequestItem::get_schema();
+        let rename_map = SqlRenameMap {
+            members: btree_map_into! {
+                "user" => "u",
+            },
+            ..Default::default()
+        };
+        let sort = MongoOrderingBuilder::new(&schema)
+            .set_rename_map(&rename_map)
+            .build(&Ordering::parse("user.id, user.age desc").unwrap())
+            .unwrap();
+        assert_eq!(sort, json!({"u.id": 1, "u.age": -1}));
+        // Terms keep their order
+        assert_eq!(
+            sort.as_object().unwrap().keys().collect::<Vec<_>>(),
+            vec!["u.id", "u.age"]
+        );
+        assert_eq!(
+            MongoOrderingBuilder::new(&schema)
+                .build(&Ordering::parse("lol").unwrap())
+                .unwrap_err(),
+            OrderingError::UnknownMember("lol".into())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::span::Span;
+
 /// Ordering parsing errors.
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum OrderingError {
@@ -18,7 +20,33 @@ pub enum OrderingError {
     /// Field is not orderable.
     #[error("unordered field `{0}`")]
     UnorderedField(String),
+    /// Error located in the ordering source.
+    #[error("{error} at {span}")]
+    Spanned {
+        /// Located error.
+        error: Box<Self>,
+        /// Span of the offending ordering term.
+        span: Span,
+    },
 }
 
 /// Ordering result type.
 pub type OrderingResult<T> = Result<T, OrderingError>;
+
+impl OrderingError {
+    /// Gets the error without its location.
+    pub fn unspanned(&self) -> &Self {
+        match self {
+            Self::Spanned { error, .. } => error.unspanned(),
+            error => error,
+        }
+    }
+
+    /// Renders the error with the offending part of the ordering source underlined.
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            Self::Spanned { error, span } => span.render(source, error),
+            error => error.to_string(),
+        }
+    }
+}
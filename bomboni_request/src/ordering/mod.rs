@@ -5,20 +5,26 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     ordering::error::{OrderingError, OrderingResult},
     schema::{Schema, SchemaMapped},
+    span::Span,
+    value::Value,
 };
 
 /// Ordering error types.
 pub mod error;
 
 /// Query ordering specification.
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+///
+/// Orderings are serialized as lists of terms, such as `[{"name": "age", "direction": "desc"}]`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Ordering(Vec<OrderingTerm>);
 
 /// Ordering term.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OrderingTerm {
     /// Field name.
     pub name: String,
@@ -27,11 +33,13 @@ pub struct OrderingTerm {
 }
 
 /// Sort direction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderingDirection {
     /// Ascending order.
+    #[serde(rename = "asc")]
     Ascending,
     /// Descending order.
+    #[serde(rename = "desc")]
     Descending,
 }
 
@@ -49,26 +57,52 @@ impl Ordering {
     /// Will return [`OrderingError::DuplicateField`] if the same field appears multiple times.
     /// Will return [`OrderingError::InvalidDirection`] if ordering direction is invalid.
     pub fn parse(source: &str) -> OrderingResult<Self> {
+        Self::parse_spanned(source)
+            .map(|(ordering, _)| ordering)
+            .map_err(|error| error.unspanned().clone())
+    }
+
+    /// Parses an ordering string, along with spans of its terms.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderingError::Spanned`] wrapping any of the errors returned by [`Ordering::parse`].
+    pub fn parse_spanned(source: &str) -> OrderingResult<(Self, Vec<Span>)> {
         let mut terms = Vec::new();
+        let mut spans = Vec::new();
         let mut term_names = BTreeSet::<&str>::new();
 
-        for parts in source
-            .split(',')
-            .map(|part| part.split_whitespace().collect::<Vec<_>>())
-            .filter(|parts| !parts.is_empty())
-        {
+        let mut offset = 0;
+        for part in source.split(',') {
+            let part_start = offset;
+            offset += part.len() + 1;
+
+            let parts: Vec<_> = part.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+            let span = Span::locate(
+                source,
+                part_start + part.len() - part.trim_start().len(),
+                part_start + part.trim_end().len(),
+            );
+            let spanned = |error: OrderingError| OrderingError::Spanned {
+                error: Box::new(error),
+                span,
+            };
+
             if parts.len() > 2 {
-                return Err(OrderingError::InvalidTermFormat(parts.join(" ")));
+                return Err(spanned(OrderingError::InvalidTermFormat(parts.join(" "))));
             }
             match parts.as_slice() {
                 [name, dir] => {
                     if !term_names.insert(*name) {
-                        return Err(OrderingError::DuplicateField((*name).into()));
+                        return Err(spanned(OrderingError::DuplicateField((*name).into())));
                     }
                     let direction = match *dir {
                         "asc" => OrderingDirection::Ascending,
                         "desc" => OrderingDirection::Descending,
-                        _ => return Err(OrderingError::InvalidDirection((*dir).into())),
+                        _ => return Err(spanned(OrderingError::InvalidDirection((*dir).into()))),
                     };
                     terms.push(OrderingTerm {
                         name: (*name).into(),
@@ -77,21 +111,26 @@ impl Ordering {
                 }
                 [name] => {
                     if !term_names.insert(name) {
-                        return Err(OrderingError::DuplicateField((*name).into()));
+                        return Err(spanned(OrderingError::DuplicateField((*name).into())));
                     }
                     terms.push(OrderingTerm {
                         name: (*name).into(),
                         direction: OrderingDirection::Ascending,
                     });
                 }
-                _ => return Err(OrderingError::InvalidTermFormat(parts.join(" "))),
+                _ => return Err(spanned(OrderingError::InvalidTermFormat(parts.join(" ")))),
             }
+            spans.push(span);
         }
 
-        Ok(Self(terms))
+        Ok((Self(terms), spans))
     }
 
     /// Evaluates ordering between two items.
+    ///
+    /// Null values are ordered last regardless of direction, as with `NULLS LAST` in SQL.
+    /// Floats are ordered with NaN above infinity, and values of different types are ordered by their type,
+    /// such that items are compared in a total order.
     pub fn evaluate<T>(&self, lhs: &T, rhs: &T) -> Option<cmp::Ordering>
     where
         T: SchemaMapped,
@@ -99,7 +138,18 @@ impl Ordering {
         for term in self.iter() {
             let a = lhs.get_field(&term.name);
             let b = rhs.get_field(&term.name);
-            match a.partial_cmp(&b)? {
+            let result = match (&a, &b) {
+                (Value::Null, Value::Null) => cmp::Ordering::Equal,
+                (Value::Null, _) => return Some(cmp::Ordering::Greater),
+                (_, Value::Null) => return Some(cmp::Ordering::Less),
+                (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+                (a, b) => a.partial_cmp(b).unwrap_or_else(|| {
+                    let get_type_rank =
+                        |value: &Value| value.value_type().map(|value_type| value_type as u8);
+                    get_type_rank(a).cmp(&get_type_rank(b))
+                }),
+            };
+            match result {
                 cmp::Ordering::Less => {
                     return Some(match term.direction {
                         OrderingDirection::Ascending => cmp::Ordering::Less,
@@ -126,12 +176,37 @@ impl Ordering {
     /// Will return [`OrderingError::UnorderedField`] if the ordering contains a field that cannot be ordered.
     pub fn validate(&self, schema: &Schema) -> OrderingResult<()> {
         for term in self.iter() {
-            let field_schema = schema
-                .get_field(&term.name)
-                .ok_or_else(|| OrderingError::UnknownMember(term.name.clone()))?;
-            if !field_schema.ordered {
-                return Err(OrderingError::UnorderedField(term.name.clone()));
-            }
+            term.validate(schema)?;
+        }
+        Ok(())
+    }
+
+    /// Validates the ordering against a schema, attaching the span of the offending term to errors.
+    ///
+    /// Spans are obtained from [`Ordering::parse_spanned`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`OrderingError::Spanned`] wrapping any of the errors returned by [`Ordering::validate`].
+    pub fn validate_spanned(&self, spans: &[Span], schema: &Schema) -> OrderingResult<()> {
+        for (term, span) in self.iter().zip(spans) {
+            term.validate(schema)
+                .map_err(|error| OrderingError::Spanned {
+                    error: Box::new(error),
+                    span: *span,
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl OrderingTerm {
+    fn validate(&self, schema: &Schema) -> OrderingResult<()> {
+        let field_schema = schema
+            .get_field(&self.name)
+            .ok_or_else(|| OrderingError::UnknownMember(self.name.clone()))?;
+        if !field_schema.ordered {
+            return Err(OrderingError::UnorderedField(self.name.clone()));
         }
         Ok(())
     }
@@ -211,6 +286,59 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn macros() {
+        use bomboni_request_derive::order_by;
+
+        assert_eq!(
+            order_by!(
+                bomboni_request_crate = crate,
+                " , user.displayName, task.userId desc"
+            ),
+            Ordering::parse(" , user.displayName, task.userId desc").unwrap()
+        );
+        assert_eq!(
+            order_by!(bomboni_request_crate = crate, ""),
+            Ordering::default()
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let ordering = Ordering::parse("user.id, user.age desc").unwrap();
+        let json = serde_json::to_value(&ordering).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"name": "user.id", "direction": "asc"},
+                {"name": "user.age", "direction": "desc"},
+            ])
+        );
+        assert_eq!(serde_json::from_value::<Ordering>(json).unwrap(), ordering);
+    }
+
+    #[test]
+    fn spanned() {
+        let schema = UserItem::get_schema();
+        let source = "age desc, displayName, lol asc";
+        let (ordering, spans) = Ordering::parse_spanned(source).unwrap();
+        assert_eq!(spans[1], Span::locate(source, 10, 21));
+        assert_eq!(
+            ordering
+                .validate_spanned(&spans, &schema)
+                .unwrap_err()
+                .render(source),
+            "age desc, displayName, lol asc\n                       ^^^^^^^ unknown ordering member `lol`"
+        );
+        assert_eq!(
+            Ordering::parse_spanned("age, age desc").unwrap_err(),
+            OrderingError::Spanned {
+                error: Box::new(OrderingError::DuplicateField("age".into())),
+                span: Span::locate("age, age desc", 5, 13),
+            }
+        );
+    }
+
     #[test]
     fn evaluate() {
         let a = UserItem {
@@ -248,5 +376,26 @@ mod tests {
                 .unwrap(),
             cmp::Ordering::Less
         );
+
+        struct PriorityItem(Option<i64>);
+
+        impl SchemaMapped for PriorityItem {
+            fn get_field(&self, _name: &str) -> Value {
+                self.0.map_or(Value::Null, Value::from)
+            }
+        }
+
+        // Nulls are last in both directions
+        for ordering in ["priority", "priority desc"] {
+            let ordering = Ordering::parse(ordering).unwrap();
+            assert_eq!(
+                ordering.evaluate(&PriorityItem(None), &PriorityItem(Some(1))),
+                Some(cmp::Ordering::Greater)
+            );
+            assert_eq!(
+                ordering.evaluate(&PriorityItem(None), &PriorityItem(None)),
+                Some(cmp::Ordering::Equal)
+            );
+        }
     }
 }
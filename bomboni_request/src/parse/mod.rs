@@ -1876,6 +1876,7 @@ mod tests {
                         }),
                         max_filter_length: Some(50),
                         max_ordering_length: Some(50),
+                        ..Default::default()
                     },
                     PlainPageTokenBuilder {},
                 )
@@ -1899,6 +1900,7 @@ mod tests {
                         }),
                         max_filter_length: Some(50),
                         max_ordering_length: Some(50),
+                        ..Default::default()
                     },
                     PlainPageTokenBuilder {},
                 )
@@ -1923,6 +1925,7 @@ mod tests {
 
             fn build_next<T: crate::schema::SchemaMapped>(
                 &self,
+                _schema: &crate::schema::Schema,
                 _filter: &Filter,
                 _ordering: &Ordering,
                 _salt: &[u8],
@@ -2039,6 +2042,7 @@ mod tests {
                         }),
                         max_filter_length: Some(50),
                         max_ordering_length: Some(50),
+                        ..Default::default()
                     },
                     CustomPageTokenBuilder {},
                 )
@@ -0,0 +1,445 @@
+//! Conversions between parsed queries and their protobuf representation.
+//!
+//! Allows services to exchange pre-validated filters and orderings, instead of re-parsing strings.
+
+use bomboni_common::date_time::UtcDateTime;
+use bomboni_proto::{
+    bomboni::request::v1 as proto,
+    google::protobuf::{Empty, NullValue},
+};
+
+use crate::{
+    error::{CommonError, RequestError, RequestResult},
+    filter::{Filter, FilterComparator},
+    ordering::{Ordering, OrderingDirection, OrderingTerm},
+    query::{list::ListQuery, page_token::FilterPageToken},
+    value::Value,
+};
+
+impl From<Filter> for proto::Filter {
+    fn from(filter: Filter) -> Self {
+        use proto::filter::{Function, Kind, List, Restriction};
+
+        let kind = match filter {
+            Filter::Conjunction(parts) => Kind::Conjunction(List {
+                filters: parts.into_iter().map(Into::into).collect(),
+            }),
+            Filter::Disjunction(parts) => Kind::Disjunction(List {
+                filters: parts.into_iter().map(Into::into).collect(),
+            }),
+            Filter::Negate(filter) => Kind::Negate(Box::new((*filter).into())),
+            Filter::Restriction(comparable, comparator, argument) => {
+                Kind::Restriction(Box::new(Restriction {
+                    comparable: Some(Box::new((*comparable).into())),
+                    comparator: proto::FilterComparator::from(comparator).into(),
+                    argument: Some(Box::new((*argument).into())),
+                }))
+            }
+            Filter::Function(name, arguments) => Kind::Function(Function {
+                name,
+                arguments: arguments.into_iter().map(Into::into).collect(),
+            }),
+            Filter::Composite(filter) => Kind::Composite(Box::new((*filter).into())),
+            Filter::Name(name) => Kind::Name(name),
+            Filter::Value(value) => Kind::Value(value.into()),
+        };
+        Self { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::Filter> for Filter {
+    type Error = RequestError;
+
+    fn try_from(filter: proto::Filter) -> RequestResult<Self> {
+        use proto::filter::Kind;
+
+        let kind = filter
+            .kind
+            .ok_or_else(|| RequestError::field("kind", CommonError::RequiredFieldMissing))?;
+        Ok(match kind {
+            Kind::Conjunction(list) => Self::Conjunction(
+                parse_filters(list.filters).map_err(|err| err.wrap_field("conjunction"))?,
+            ),
+            Kind::Disjunction(list) => Self::Disjunction(
+                parse_filters(list.filters).map_err(|err| err.wrap_field("disjunction"))?,
+            ),
+            Kind::Negate(filter) => Self::Negate(Box::new(
+                Self::try_from(*filter).map_err(|err| err.wrap_field("negate"))?,
+            )),
+            Kind::Restriction(restriction) => {
+                let parse_operand = |operand: Option<Box<proto::Filter>>, field: &str| {
+                    operand
+                        .ok_or_else(|| RequestError::generic(CommonError::RequiredFieldMissing))
+                        .and_then(|operand| Self::try_from(*operand))
+                        .map(Box::new)
+                        .map_err(|err| err.wrap_field(field).wrap_field("restriction"))
+                };
+                let comparable = parse_operand(restriction.comparable, "comparable")?;
+                let argument = parse_operand(restriction.argument, "argument")?;
+                let comparator = proto::FilterComparator::try_from(restriction.comparator)
+                    .map_err(|_| RequestError::generic(CommonError::InvalidEnumValue))
+                    .and_then(FilterComparator::try_from)
+                    .map_err(|err| err.wrap_field("comparator").wrap_field("restriction"))?;
+                Self::Restriction(comparable, comparator, argument)
+            }
+            Kind::Function(function) => Self::Function(
+                function.name,
+                parse_filters(function.arguments).map_err(|err| err.wrap_field("function"))?,
+            ),
+            Kind::Composite(filter) => Self::Composite(Box::new(
+                Self::try_from(*filter).map_err(|err| err.wrap_field("composite"))?,
+            )),
+            Kind::Name(name) => Self::Name(name),
+            Kind::Value(value) => {
+                Self::Value(Value::try_from(value).map_err(|err| err.wrap_field("value"))?)
+            }
+        })
+    }
+}
+
+fn parse_filters(filters: Vec<proto::Filter>) -> RequestResult<Vec<Filter>> {
+    filters
+        .into_iter()
+        .enumerate()
+        .map(|(i, filter)| {
+            Filter::try_from(filter).map_err(|err| err.wrap_field_index("filters", i))
+        })
+        .collect()
+}
+
+impl From<FilterComparator> for proto::FilterComparator {
+    fn from(comparator: FilterComparator) -> Self {
+        match comparator {
+            FilterComparator::Less => Self::Less,
+            FilterComparator::LessOrEqual => Self::LessOrEqual,
+            FilterComparator::Greater => Self::Greater,
+            FilterComparator::GreaterOrEqual => Self::GreaterOrEqual,
+            FilterComparator::Equal => Self::Equal,
+            FilterComparator::NotEqual => Self::NotEqual,
+            FilterComparator::Has => Self::Has,
+        }
+    }
+}
+
+impl TryFrom<proto::FilterComparator> for FilterComparator {
+    type Error = RequestError;
+
+    fn try_from(comparator: proto::FilterComparator) -> RequestResult<Self> {
+        Ok(match comparator {
+            proto::FilterComparator::Unspecified => {
+                return Err(RequestError::generic(CommonError::InvalidEnumValue));
+            }
+            proto::FilterComparator::Less => Self::Less,
+            proto::FilterComparator::LessOrEqual => Self::LessOrEqual,
+            proto::FilterComparator::Greater => Self::Greater,
+            proto::FilterComparator::GreaterOrEqual => Self::GreaterOrEqual,
+            proto::FilterComparator::Equal => Self::Equal,
+            proto::FilterComparator::NotEqual => Self::NotEqual,
+            proto::FilterComparator::Has => Self::Has,
+        })
+    }
+}
+
+impl From<Value> for proto::Value {
+    fn from(value: Value) -> Self {
+        use proto::value::{Kind, List, Map};
+
+        let kind = match value {
+            Value::Integer(value) => Kind::IntegerValue(value),
+            Value::Float(value) => Kind::FloatValue(value),
+            Value::Boolean(value) => Kind::BooleanValue(value),
+            Value::String(value) => Kind::StringValue(value),
+            Value::Timestamp(value) => Kind::TimestampValue(value.into()),
+            Value::Duration(value) => Kind::DurationValue(value),
+            Value::Enum(value) => Kind::EnumValue(value),
+            Value::Repeated(values) => Kind::RepeatedValue(List {
+                values: values.into_iter().map(Into::into).collect(),
+            }),
+            Value::Map(entries) => Kind::MapValue(Map {
+                entries: entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            }),
+            Value::Any => Kind::AnyValue(Empty {}),
+            Value::Null => Kind::NullValue(NullValue::NullValue.into()),
+        };
+        Self { kind: Some(kind) }
+    }
+}
+
+impl TryFrom<proto::Value> for Value {
+    type Error = RequestError;
+
+    fn try_from(value: proto::Value) -> RequestResult<Self> {
+        use proto::value::Kind;
+
+        let kind = value
+            .kind
+            .ok_or_else(|| RequestError::field("kind", CommonError::RequiredFieldMissing))?;
+        Ok(match kind {
+            Kind::IntegerValue(value) => Self::Integer(value),
+            Kind::FloatValue(value) => Self::Float(value),
+            Kind::BooleanValue(value) => Self::Boolean(value),
+            Kind::StringValue(value) => Self::String(value),
+            Kind::TimestampValue(value) => {
+                Self::Timestamp(UtcDateTime::try_from(value).map_err(|_| {
+                    RequestError::field("timestamp_value", CommonError::InvalidDateTime)
+                })?)
+            }
+            Kind::DurationValue(value) => Self::Duration(value),
+            Kind::EnumValue(value) => Self::Enum(value),
+            Kind::RepeatedValue(list) => Self::Repeated(
+                list.values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        Self::try_from(value).map_err(|err| err.wrap_field_index("values", i))
+                    })
+                    .collect::<RequestResult<_>>()
+                    .map_err(|err| err.wrap_field("repeated_value"))?,
+            ),
+            Kind::MapValue(map) => Self::Map(
+                map.entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        Self::try_from(value)
+                            .map(|value| (key.clone(), value))
+                            .map_err(|err| err.wrap_field_key("entries", key))
+                    })
+                    .collect::<RequestResult<_>>()
+                    .map_err(|err| err.wrap_field("map_value"))?,
+            ),
+            Kind::AnyValue(_) => Self::Any,
+            Kind::NullValue(_) => Self::Null,
+        })
+    }
+}
+
+impl From<Ordering> for proto::Ordering {
+    fn from(ordering: Ordering) -> Self {
+        Self {
+            terms: ordering
+                .iter()
+                .map(|term| proto::ordering::Term {
+                    name: term.name.clone(),
+                    direction: match term.direction {
+                        OrderingDirection::Ascending => proto::ordering::Direction::Ascending,
+                        OrderingDirection::Descending => proto::ordering::Direction::Descending,
+                    }
+                    .into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::Ordering> for Ordering {
+    type Error = RequestError;
+
+    fn try_from(ordering: proto::Ordering) -> RequestResult<Self> {
+        ordering
+            .terms
+            .into_iter()
+            .enumerate()
+            .map(|(i, term)| {
+                let direction = match proto::ordering::Direction::try_from(term.direction) {
+                    Ok(proto::ordering::Direction::Ascending) => OrderingDirection::Ascending,
+                    Ok(proto::ordering::Direction::Descending) => OrderingDirection::Descending,
+                    _ => {
+                        return Err(RequestError::field(
+                            "direction",
+                            CommonError::InvalidEnumValue,
+                        )
+                        .wrap_field_index("terms", i));
+                    }
+                };
+                Ok(OrderingTerm {
+                    name: term.name,
+                    direction,
+                })
+            })
+            .collect::<RequestResult<_>>()
+            .map(Self::new)
+    }
+}
+
+impl From<ListQuery> for proto::ListQuery {
+    fn from(query: ListQuery) -> Self {
+        Self {
+            page_size: query.page_size,
+            page_token: query.page_token.map(|page_token| page_token.filter.into()),
+            filter: Some(query.filter.into()),
+            ordering: Some(query.ordering.into()),
+        }
+    }
+}
+
+impl TryFrom<proto::ListQuery> for ListQuery {
+    type Error = RequestError;
+
+    fn try_from(query: proto::ListQuery) -> RequestResult<Self> {
+        Ok(Self {
+            page_size: query.page_size,
+            page_token: query
+                .page_token
+                .map(|filter| {
+                    Filter::try_from(filter)
+                        .map(FilterPageToken::new)
+                        .map_err(|err| err.wrap_field("page_token"))
+                })
+                .transpose()?,
+            filter: query
+                .filter
+                .map(|filter| Filter::try_from(filter).map_err(|err| err.wrap_field("filter")))
+                .transpose()?
+                .unwrap_or_default(),
+            ordering: query
+                .ordering
+                .map(|ordering| {
+                    Ordering::try_from(ordering).map_err(|err| err.wrap_field("ordering"))
+                })
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn assert_path_error(err: RequestError, path: &str, expected: &CommonError) {
+        let RequestError::Path(error) = err else {
+            panic!("expected path error, got {err:?}");
+        };
+        assert_eq!(
+            error.error.as_any().downcast_ref::<CommonError>().unwrap(),
+            expected
+        );
+        assert_eq!(error.path_to_string(), path);
+    }
+
+    #[test]
+    fn it_works() {
+        let filter =
+            Filter::parse(r#"NOT (a.f(42, 1.5) AND b != "x") OR c:* AND e = 10s AND f = null"#)
+                .unwrap();
+        let message = proto::Filter::from(filter.clone());
+        assert!(matches!(
+            message.kind,
+            Some(proto::filter::Kind::Conjunction(_))
+        ));
+        assert_eq!(Filter::try_from(message).unwrap(), filter);
+
+        let timestamp = UtcDateTime::parse_rfc3339("2024-01-01T00:00:00Z").unwrap();
+        assert!(matches!(
+            proto::Value::from(Value::Timestamp(timestamp)).kind,
+            Some(proto::value::Kind::TimestampValue(_))
+        ));
+        let filter = Filter::Restriction(
+            Box::new(Filter::Name("d".into())),
+            FilterComparator::LessOrEqual,
+            Box::new(Filter::Value(Value::Timestamp(timestamp))),
+        );
+        assert_eq!(
+            Filter::try_from(proto::Filter::from(filter.clone())).unwrap(),
+            filter
+        );
+
+        let filter = Filter::Restriction(
+            Box::new(Filter::Name("labels".into())),
+            FilterComparator::Equal,
+            Box::new(Filter::Value(Value::Map(BTreeMap::from([(
+                "tags".into(),
+                Value::Repeated(vec![Value::Enum("A".into()), true.into()]),
+            )])))),
+        );
+        assert_eq!(
+            Filter::try_from(proto::Filter::from(filter.clone())).unwrap(),
+            filter
+        );
+
+        let ordering = Ordering::parse("user.id, user.age desc").unwrap();
+        assert_eq!(
+            Ordering::try_from(proto::Ordering::from(ordering.clone())).unwrap(),
+            ordering
+        );
+
+        let query = ListQuery {
+            page_size: 20,
+            page_token: Some(FilterPageToken::new(Filter::parse("id > 42").unwrap())),
+            filter: Filter::parse("age >= 18").unwrap(),
+            ordering,
+        };
+        assert_eq!(
+            ListQuery::try_from(proto::ListQuery::from(query.clone())).unwrap(),
+            query
+        );
+        assert_eq!(
+            ListQuery::try_from(proto::ListQuery::default()).unwrap(),
+            ListQuery {
+                page_size: 0,
+                page_token: None,
+                filter: Filter::default(),
+                ordering: Ordering::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use proto::filter::{Kind, List, Restriction};
+
+        assert_path_error(
+            Filter::try_from(proto::Filter {
+                kind: Some(Kind::Conjunction(List {
+                    filters: vec![Filter::Name("a".into()).into(), proto::Filter::default()],
+                })),
+            })
+            .unwrap_err(),
+            "conjunction.filters[1].kind",
+            &CommonError::RequiredFieldMissing,
+        );
+        assert_path_error(
+            Filter::try_from(proto::Filter {
+                kind: Some(Kind::Restriction(Box::new(Restriction {
+                    comparable: Some(Box::new(Filter::Name("a".into()).into())),
+                    comparator: proto::FilterComparator::Equal.into(),
+                    argument: None,
+                }))),
+            })
+            .unwrap_err(),
+            "restriction.argument",
+            &CommonError::RequiredFieldMissing,
+        );
+        assert_path_error(
+            Filter::try_from(proto::Filter {
+                kind: Some(Kind::Restriction(Box::new(Restriction {
+                    comparable: Some(Box::new(Filter::Name("a".into()).into())),
+                    comparator: proto::FilterComparator::Unspecified.into(),
+                    argument: Some(Box::new(Filter::Name("b".into()).into())),
+                }))),
+            })
+            .unwrap_err(),
+            "restriction.comparator",
+            &CommonError::InvalidEnumValue,
+        );
+        assert_path_error(
+            ListQuery::try_from(proto::ListQuery {
+                ordering: Some(proto::Ordering {
+                    terms: vec![proto::ordering::Term {
+                        name: "id".into(),
+                        direction: 42,
+                    }],
+                }),
+                ..Default::default()
+            })
+            .unwrap_err(),
+            "ordering.terms[0].direction",
+            &CommonError::InvalidEnumValue,
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    filter::{
+        Filter,
+        visit::{FilterVisitor, visit_filter, visit_filters},
+    },
+    query::error::{QueryError, QueryResult},
+};
+
+/// Structural complexity limits of query filters.
+///
+/// Unlike the maximum filter length, these limits reject filters that are short but expensive to execute,
+/// such as deeply nested expressions or disjunctions with many alternatives.
+#[derive(Debug, Clone, Default)]
+pub struct FilterComplexityConfig {
+    /// Maximum nesting depth of expressions.
+    ///
+    /// Each expression adds a level, such that `a = 1` has depth 1 and `NOT (a = 1 OR b = 2)` has depth 4.
+    pub max_depth: Option<usize>,
+    /// Maximum number of alternatives in a single disjunction.
+    pub max_disjunction_width: Option<usize>,
+    /// Maximum number of function calls.
+    pub max_function_calls: Option<usize>,
+    /// Maximum total cost of function calls.
+    pub max_function_cost: Option<usize>,
+    /// Cost weights of functions, by name.
+    ///
+    /// Calls of functions without a weight cost 1.
+    pub function_costs: BTreeMap<String, usize>,
+}
+
+impl FilterComplexityConfig {
+    /// Checks the filter against complexity limits.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`QueryError::FilterTooDeep`] if filter exceeds maximum nesting depth.
+    /// Will return [`QueryError::FilterDisjunctionTooWide`] if a disjunction exceeds maximum width.
+    /// Will return [`QueryError::FilterTooManyFunctionCalls`] if filter exceeds maximum number of function calls.
+    /// Will return [`QueryError::FilterTooExpensive`] if function calls exceed maximum total cost.
+    pub fn check(&self, filter: &Filter) -> QueryResult<()> {
+        ComplexityChecker {
+            config: self,
+            depth: 0,
+            function_calls: 0,
+            function_cost: 0,
+        }
+        .visit_filter(filter)
+    }
+}
+
+struct ComplexityChecker<'a> {
+    config: &'a FilterComplexityConfig,
+    depth: usize,
+    function_calls: usize,
+    function_cost: usize,
+}
+
+impl FilterVisitor for ComplexityChecker<'_> {
+    type Error = QueryError;
+
+    fn visit_filter(&mut self, filter: &Filter) -> Result<(), Self::Error> {
+        if matches!(filter, Filter::Name(_) | Filter::Value(_)) {
+            return Ok(());
+        }
+        self.depth += 1;
+        if matches!(self.config.max_depth, Some(max) if self.depth > max) {
+            return Err(QueryError::FilterTooDeep);
+        }
+        visit_filter(self, filter)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn visit_disjunction(&mut self, parts: &[Filter]) -> Result<(), Self::Error> {
+        if matches!(self.config.max_disjunction_width, Some(max) if parts.len() > max) {
+            return Err(QueryError::FilterDisjunctionTooWide);
+        }
+        visit_filters(self, parts)
+    }
+
+    fn visit_function(&mut self, name: &str, arguments: &[Filter]) -> Result<(), Self::Error> {
+        self.function_calls += 1;
+        if matches!(self.config.max_function_calls, Some(max) if self.function_calls > max) {
+            return Err(QueryError::FilterTooManyFunctionCalls);
+        }
+        self.function_cost += self.config.function_costs.get(name).copied().unwrap_or(1);
+        if matches!(self.config.max_function_cost, Some(max) if self.function_cost > max) {
+            return Err(QueryError::FilterTooExpensive);
+        }
+        visit_filters(self, arguments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let check = |config: &FilterComplexityConfig, source: &str| {
+            config.check(&Filter::parse(source).unwrap())
+        };
+
+        let config = FilterComplexityConfig {
+            max_depth: Some(4),
+            ..Default::default()
+        };
+        assert!(check(&config, "a = 1").is_ok());
+        assert!(check(&config, "NOT (a = 1 OR b = 2)").is_ok());
+        assert_eq!(
+            check(&config, "NOT (a = 1 OR NOT b = 2)").unwrap_err(),
+            QueryError::FilterTooDeep
+        );
+        assert_eq!(
+            check(&config, "((((a = 1))))").unwrap_err(),
+            QueryError::FilterTooDeep
+        );
+
+        let config = FilterComplexityConfig {
+            max_disjunction_width: Some(3),
+            ..Default::default()
+        };
+        assert!(check(&config, "a = 1 OR a = 2 OR a = 3").is_ok());
+        assert!(check(&config, "a = (1 OR 2 OR 3)").is_ok());
+        assert_eq!(
+            check(&config, "a = 1 OR a = 2 OR a = 3 OR a = 4").unwrap_err(),
+            QueryError::FilterDisjunctionTooWide
+        );
+        assert_eq!(
+            check(&config, "x AND a = (1 OR 2 OR 3 OR 4)").unwrap_err(),
+            QueryError::FilterDisjunctionTooWide
+        );
+
+        let config = FilterComplexityConfig {
+            max_function_calls: Some(2),
+            ..Default::default()
+        };
+        assert!(check(&config, "f() AND g(1)").is_ok());
+        assert_eq!(
+            check(&config, "f() AND g(h())").unwrap_err(),
+            QueryError::FilterTooManyFunctionCalls
+        );
+
+        let config = FilterComplexityConfig {
+            max_function_cost: Some(10),
+            function_costs: btree_map_into! {
+                "regex" => 8usize,
+            },
+            ..Default::default()
+        };
+        assert!(check(&config, "regex(a, \"x\") AND f() AND g()").is_ok());
+        assert_eq!(
+            check(&config, "regex(a, \"x\") AND f() AND g() AND h()").unwrap_err(),
+            QueryError::FilterTooExpensive
+        );
+        assert_eq!(
+            check(&config, "regex(a, \"x\") OR regex(b, \"y\")").unwrap_err(),
+            QueryError::FilterTooExpensive
+        );
+
+        assert!(
+            FilterComplexityConfig::default()
+                .check(&Filter::parse("NOT (a OR b OR c OR f(g(h())))").unwrap())
+                .is_ok()
+        );
+    }
+}
@@ -1,23 +1,53 @@
 use bomboni_proto::google::rpc::Code;
 use thiserror::Error;
 
-use crate::{error::GenericError, filter::error::FilterError, ordering::error::OrderingError};
+use crate::{
+    error::GenericError, filter::error::FilterError, ordering::error::OrderingError, span::Span,
+};
 
 /// Query processing errors.
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum QueryError {
-    /// Filter error.
-    #[error("filter error: {0}")]
-    FilterError(FilterError),
+    /// Filter error, located in the filter source if parsed from one.
+    #[error("filter error: {error}{}", get_span_suffix(*.span))]
+    FilterError {
+        /// Filter error, without its location.
+        error: FilterError,
+        /// Span of the offending part of the filter.
+        span: Option<Span>,
+    },
     /// Filter is too long.
     #[error("filter is too long")]
     FilterTooLong,
+    /// Filter is nested too deeply.
+    #[error("filter is too deeply nested")]
+    FilterTooDeep,
+    /// Filter contains a disjunction with too many alternatives.
+    #[error("filter disjunction is too wide")]
+    FilterDisjunctionTooWide,
+    /// Filter contains too many function calls.
+    #[error("filter contains too many function calls")]
+    FilterTooManyFunctionCalls,
+    /// Function calls of filter are too expensive.
+    #[error("filter is too expensive")]
+    FilterTooExpensive,
     /// Filter schema mismatch.
     #[error("filter schema mismatch")]
     FilterSchemaMismatch,
-    /// Ordering error.
-    #[error("ordering error: {0}")]
-    OrderingError(OrderingError),
+    /// Filter references a member forbidden by query policy.
+    #[error("filter references forbidden member `{0}`")]
+    FilterForbiddenMember(String),
+    /// Filter negates a member, or compares it by ordering, where query policy does not allow it.
+    #[error("filter negates member `{0}`")]
+    FilterNegatedMember(String),
+    /// Ordering error, located in the ordering source if parsed from one.
+    #[error("ordering error: {error}{}", get_span_suffix(*.span))]
+    OrderingError {
+        /// Ordering error, without its location.
+        error: OrderingError,
+        /// Span of the offending ordering term.
+        span: Option<Span>,
+    },
     /// Ordering is too long.
     #[error("ordering is too long")]
     OrderingTooLong,
@@ -45,8 +75,16 @@ impl QueryError {
     /// Gets the name of the field that caused the error.
     pub const fn get_violating_field_name(&self) -> &'static str {
         match self {
-            Self::FilterError(_) | Self::FilterTooLong | Self::FilterSchemaMismatch => "filter",
-            Self::OrderingError(_) | Self::OrderingTooLong | Self::OrderingSchemaMismatch => {
+            Self::FilterError { .. }
+            | Self::FilterTooLong
+            | Self::FilterTooDeep
+            | Self::FilterDisjunctionTooWide
+            | Self::FilterTooManyFunctionCalls
+            | Self::FilterTooExpensive
+            | Self::FilterSchemaMismatch
+            | Self::FilterForbiddenMember(_)
+            | Self::FilterNegatedMember(_) => "filter",
+            Self::OrderingError { .. } | Self::OrderingTooLong | Self::OrderingSchemaMismatch => {
                 "order_by"
             }
             Self::QueryTooLong => "query",
@@ -58,16 +96,32 @@ impl QueryError {
 
 impl From<FilterError> for QueryError {
     fn from(err: FilterError) -> Self {
-        Self::FilterError(err)
+        match err {
+            FilterError::Spanned { error, span } => Self::FilterError {
+                error: *error,
+                span: Some(span),
+            },
+            error => Self::FilterError { error, span: None },
+        }
     }
 }
 
 impl From<OrderingError> for QueryError {
     fn from(err: OrderingError) -> Self {
-        Self::OrderingError(err)
+        match err {
+            OrderingError::Spanned { error, span } => Self::OrderingError {
+                error: *error,
+                span: Some(span),
+            },
+            error => Self::OrderingError { error, span: None },
+        }
     }
 }
 
+fn get_span_suffix(span: Option<Span>) -> String {
+    span.map_or_else(String::new, |span| format!(" at {span}"))
+}
+
 impl GenericError for QueryError {
     fn as_any(&self) -> &dyn std::any::Any {
         self
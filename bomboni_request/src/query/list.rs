@@ -4,15 +4,19 @@
 //!
 //! [1]: https://google.aip.dev/132
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     filter::Filter,
     ordering::{Ordering, OrderingTerm},
     query::{
+        complexity::FilterComplexityConfig,
         error::{QueryError, QueryResult},
         page_token::{
             FilterPageToken, PageTokenBuilder, aes256::Aes256PageTokenBuilder,
             base64::Base64PageTokenBuilder, plain::PlainPageTokenBuilder, rsa::RsaPageTokenBuilder,
         },
+        policy::QueryPolicy,
         utility::{parse_query_filter, parse_query_ordering},
     },
     schema::{FunctionSchemaMap, Schema, SchemaMapped},
@@ -20,7 +24,8 @@ use crate::{
 
 /// Represents a list query.
 /// List queries list paged, filtered and ordered items.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListQuery<T: Clone + ToString = FilterPageToken> {
     /// Page size.
     pub page_size: i32,
@@ -50,6 +55,8 @@ pub struct ListQueryConfig {
     pub max_filter_length: Option<usize>,
     /// Maximum ordering length.
     pub max_ordering_length: Option<usize>,
+    /// Filter complexity limits.
+    pub filter_complexity: FilterComplexityConfig,
 }
 
 /// Builder for list queries.
@@ -85,6 +92,7 @@ impl Default for ListQueryConfig {
             primary_ordering_term: None,
             max_filter_length: None,
             max_ordering_length: None,
+            filter_complexity: FilterComplexityConfig::default(),
         }
     }
 }
@@ -111,6 +119,8 @@ impl<P: PageTokenBuilder> ListQueryBuilder<P> {
     ///
     /// Will return [`QueryError::FilterTooLong`] if filter exceeds maximum length.
     /// Will return [`QueryError::FilterError`] if filter cannot be parsed or validated.
+    /// Will return [`QueryError::FilterTooDeep`], [`QueryError::FilterDisjunctionTooWide`], [`QueryError::FilterTooManyFunctionCalls`]
+    /// or [`QueryError::FilterTooExpensive`] if filter exceeds complexity limits.
     /// Will return [`QueryError::OrderingTooLong`] if ordering exceeds maximum length.
     /// Will return [`QueryError::OrderingError`] if ordering cannot be parsed or validated.
     /// Will return [`QueryError::InvalidPageSize`] if page size is negative.
@@ -121,6 +131,38 @@ impl<P: PageTokenBuilder> ListQueryBuilder<P> {
         page_token: Option<&str>,
         filter: Option<&str>,
         ordering: Option<&str>,
+    ) -> QueryResult<ListQuery<P::PageToken>> {
+        self.build_with_policy(
+            &QueryPolicy::default(),
+            page_size,
+            page_token,
+            filter,
+            ordering,
+        )
+    }
+
+    /// Builds a list query, applying the policy to its filter.
+    ///
+    /// Filter of the built query is restricted by the policy, see [`QueryPolicy::apply`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`QueryError::FilterTooLong`] if filter exceeds maximum length.
+    /// Will return [`QueryError::FilterError`] if filter cannot be parsed or validated.
+    /// Will return [`QueryError::FilterTooDeep`], [`QueryError::FilterDisjunctionTooWide`], [`QueryError::FilterTooManyFunctionCalls`]
+    /// or [`QueryError::FilterTooExpensive`] if filter exceeds complexity limits.
+    /// Will return [`QueryError::FilterForbiddenMember`] or [`QueryError::FilterNegatedMember`] if filter violates policy member access.
+    /// Will return [`QueryError::OrderingTooLong`] if ordering exceeds maximum length.
+    /// Will return [`QueryError::OrderingError`] if ordering cannot be parsed or validated.
+    /// Will return [`QueryError::InvalidPageSize`] if page size is negative.
+    /// Will return page token parsing errors from the underlying page token builder.
+    pub fn build_with_policy(
+        &self,
+        policy: &QueryPolicy,
+        page_size: Option<i32>,
+        page_token: Option<&str>,
+        filter: Option<&str>,
+        ordering: Option<&str>,
     ) -> QueryResult<ListQuery<P::PageToken>> {
         let filter = parse_query_filter(
             filter,
@@ -128,6 +170,8 @@ impl<P: PageTokenBuilder> ListQueryBuilder<P> {
             Some(&self.schema_functions),
             self.options.max_filter_length,
         )?;
+        self.options.filter_complexity.check(&filter)?;
+        let filter = policy.apply(filter)?;
         let mut ordering =
             parse_query_ordering(ordering, &self.schema, self.options.max_ordering_length)?;
 
@@ -184,6 +228,7 @@ impl<P: PageTokenBuilder> ListQueryBuilder<P> {
         next_item: &T,
     ) -> QueryResult<String> {
         self.page_token_builder.build_next(
+            &self.schema,
             &query.filter,
             &query.ordering,
             &ListQuery::make_salt(query.page_size),
@@ -202,7 +247,8 @@ mod tests {
     use crate::{
         filter::error::FilterError,
         ordering::{OrderingDirection, error::OrderingError},
-        query::page_token::plain::PlainPageTokenBuilder,
+        query::{page_token::plain::PlainPageTokenBuilder, policy::PolicyMemberAccess},
+        span::Span,
         testing::schema::UserItem,
     };
 
@@ -237,7 +283,7 @@ mod tests {
         ));
         assert!(matches!(
             q.build(None, None, Some("f!"), None).unwrap_err(),
-            QueryError::FilterError(FilterError::Parse { start, end })
+            QueryError::FilterError { error: FilterError::Parse { start, end }, span: None }
             if start == 1 && end == 1
         ));
         assert_eq!(
@@ -245,9 +291,17 @@ mod tests {
                 .unwrap_err(),
             QueryError::FilterTooLong
         );
+        assert_eq!(
+            q.build(None, None, Some("age = (1 OR 2 OR 3)"), None)
+                .unwrap_err(),
+            QueryError::FilterDisjunctionTooWide
+        );
         assert_eq!(
             q.build(None, None, Some("lol"), None).unwrap_err(),
-            QueryError::FilterError(FilterError::UnknownMember("lol".into()))
+            QueryError::FilterError {
+                error: FilterError::UnknownMember("lol".into()),
+                span: Some(Span::locate("lol", 0, 3)),
+            }
         );
         assert_eq!(
             q.build(None, None, None, Some(&("a".repeat(100))))
@@ -256,7 +310,10 @@ mod tests {
         );
         assert_eq!(
             q.build(None, None, None, Some("lol")).unwrap_err(),
-            QueryError::OrderingError(OrderingError::UnknownMember("lol".into()))
+            QueryError::OrderingError {
+                error: OrderingError::UnknownMember("lol".into()),
+                span: Some(Span::locate("lol", 0, 3)),
+            }
         );
     }
 
@@ -274,15 +331,20 @@ mod tests {
                 let first_page = qb.build(Some(3), None, $filter1, $ordering1).unwrap();
                 let next_page_token = qb
                     .page_token_builder
-                    .build_next(&first_page.filter, &first_page.ordering, &[], &last_item)
+                    .build_next(
+                        &qb.schema,
+                        &first_page.filter,
+                        &first_page.ordering,
+                        &[],
+                        &last_item,
+                    )
                     .unwrap();
                 let next_page: ListQuery = qb
                     .build(Some(3), Some(&next_page_token), $filter2, $ordering2)
                     .unwrap();
-                assert_eq!(
-                    next_page.page_token.unwrap().filter.to_string(),
-                    $expected_token
-                );
+                let page_filter = next_page.page_token.unwrap().filter;
+                page_filter.validate(&qb.schema, None).unwrap();
+                assert_eq!(page_filter.to_string(), $expected_token);
             }};
         }
 
@@ -298,15 +360,53 @@ mod tests {
             Some("id desc, age desc"),
             None,
             Some("id desc, age desc"),
-            r#"id <= "1337" AND age <= 14000"#,
+            r#"id < "1337" OR (id = "1337" AND age <= 14000)"#,
         );
         assert_page_token!(
             None,
             Some("id desc, age asc"),
             None,
             Some("id desc, age desc"),
-            r#"id <= "1337" AND age >= 14000"#,
+            r#"id < "1337" OR (id = "1337" AND age >= 14000)"#,
+        );
+    }
+
+    #[test]
+    fn policy() {
+        let qb = get_query_builder();
+        let mut policy = QueryPolicy::new();
+        policy
+            .restrict(Filter::parse(r#"id = "1""#).unwrap())
+            .protect("id", PolicyMemberAccess::Forbidden);
+
+        let query = qb
+            .build_with_policy(&policy, None, None, Some("age > 3 OR age < 1"), None)
+            .unwrap();
+        assert_eq!(
+            query.filter.to_string(),
+            r#"id = "1" AND (age > 3 OR age < 1)"#
         );
+        let query = qb
+            .build_with_policy(&policy, None, None, None, None)
+            .unwrap();
+        assert_eq!(query.filter.to_string(), r#"id = "1""#);
+
+        assert_eq!(
+            qb.build_with_policy(&policy, None, None, Some(r#"age > 3 OR id = "2""#), None)
+                .unwrap_err(),
+            QueryError::FilterForbiddenMember("id".into())
+        );
+
+        // Page filters can only restrict members to literals
+        assert_eq!(
+            qb.build_with_policy(&policy, None, Some(r#"NOT id = "1""#), None, None)
+                .unwrap_err(),
+            QueryError::InvalidPageToken
+        );
+        let query = qb
+            .build_with_policy(&policy, None, Some(r#"id <= "3""#), None, None)
+            .unwrap();
+        assert_eq!(query.page_token.unwrap().filter.to_string(), r#"id <= "3""#);
     }
 
     fn get_query_builder() -> ListQueryBuilder<PlainPageTokenBuilder> {
@@ -322,6 +422,10 @@ mod tests {
                 }),
                 max_filter_length: Some(50),
                 max_ordering_length: Some(50),
+                filter_complexity: FilterComplexityConfig {
+                    max_disjunction_width: Some(2),
+                    ..Default::default()
+                },
             },
             PlainPageTokenBuilder {},
         )
@@ -0,0 +1,436 @@
+//! # In-memory query execution.
+//!
+//! Reference semantics of list and search queries, executed over in-memory items.
+//! Useful for tests and small services, and for checking query backends such as SQL against.
+
+use std::cmp;
+
+use crate::{
+    filter::{Filter, function::FunctionRegistry},
+    ordering::Ordering,
+    query::{
+        error::QueryResult,
+        list::ListQuery,
+        page_token::{FilterPageToken, PageTokenBuilder},
+        search::SearchQuery,
+    },
+    schema::{Schema, SchemaMapped},
+    value::Value,
+};
+
+/// Page of items returned by in-memory query execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryQueryPage<T> {
+    /// Items of the page.
+    pub items: Vec<T>,
+    /// Token of the next page, if there are more items.
+    pub next_page_token: Option<String>,
+    /// Total number of items matching the query filter, across all pages.
+    pub total_size: usize,
+}
+
+/// Executor of queries over in-memory items.
+///
+/// Items are filtered by the query filter and the page token filter, sorted by the query ordering,
+/// and truncated to the page size.
+/// One more item than the page size is taken to build the token of the next page.
+#[derive(Debug)]
+pub struct MemoryQueryExecutor<'a, P: PageTokenBuilder<PageToken = FilterPageToken>> {
+    schema: &'a Schema,
+    page_token_builder: &'a P,
+    functions: Option<&'a FunctionRegistry>,
+}
+
+impl<'a, P: PageTokenBuilder<PageToken = FilterPageToken>> MemoryQueryExecutor<'a, P> {
+    /// Creates a new executor of queries over items of the schema,
+    /// building next page tokens with the page token builder.
+    pub const fn new(schema: &'a Schema, page_token_builder: &'a P) -> Self {
+        Self {
+            schema,
+            page_token_builder,
+            functions: None,
+        }
+    }
+
+    /// Sets the registry of functions called by filters.
+    pub const fn set_functions(&mut self, functions: &'a FunctionRegistry) -> &mut Self {
+        self.functions = Some(functions);
+        self
+    }
+
+    /// Executes a list query.
+    ///
+    /// # Errors
+    ///
+    /// Will return page token building errors from the underlying page token builder.
+    pub fn execute_list<I>(
+        &self,
+        query: &ListQuery,
+        items: I,
+    ) -> QueryResult<MemoryQueryPage<I::Item>>
+    where
+        I: IntoIterator,
+        I::Item: SchemaMapped,
+    {
+        self.execute(
+            &query.filter,
+            &query.ordering,
+            query.page_size,
+            query.page_token.as_ref(),
+            &ListQuery::make_salt(query.page_size),
+            items,
+        )
+    }
+
+    /// Executes a search query.
+    ///
+    /// Items are sorted by the query ordering, see [`SearchQuery::rank`] for ranking by relevance.
+    ///
+    /// # Errors
+    ///
+    /// Will return page token building errors from the underlying page token builder.
+    pub fn execute_search<I>(
+        &self,
+        query: &SearchQuery,
+        items: I,
+    ) -> QueryResult<MemoryQueryPage<I::Item>>
+    where
+        I: IntoIterator,
+        I::Item: SchemaMapped,
+    {
+        self.execute(
+            &query.filter,
+            &query.ordering,
+            query.page_size,
+            query.page_token.as_ref(),
+            &SearchQuery::make_salt(&query.query, query.page_size),
+            items,
+        )
+    }
+
+    fn execute<I>(
+        &self,
+        filter: &Filter,
+        ordering: &Ordering,
+        page_size: i32,
+        page_token: Option<&FilterPageToken>,
+        salt: &[u8],
+        items: I,
+    ) -> QueryResult<MemoryQueryPage<I::Item>>
+    where
+        I: IntoIterator,
+        I::Item: SchemaMapped,
+    {
+        let mut items: Vec<_> = items
+            .into_iter()
+            .filter(|item| self.matches(filter, item))
+            .collect();
+        let total_size = items.len();
+
+        if let Some(page_token) = page_token {
+            items.retain(|item| self.matches(&page_token.filter, item));
+        }
+        // Items are ordered in a total order, with nulls last
+        items.sort_by(|a, b| ordering.evaluate(a, b).unwrap_or(cmp::Ordering::Equal));
+
+        let page_size = usize::try_from(page_size).unwrap_or_default();
+        items.truncate(page_size + 1);
+        let next_page_token = if items.len() > page_size {
+            let next_item = items.pop().unwrap();
+            Some(self.page_token_builder.build_next(
+                self.schema,
+                filter,
+                ordering,
+                salt,
+                &next_item,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(MemoryQueryPage {
+            items,
+            next_page_token,
+            total_size,
+        })
+    }
+
+    fn matches<T: SchemaMapped>(&self, filter: &Filter, item: &T) -> bool {
+        if filter.is_empty() {
+            return true;
+        }
+        self.functions.map_or_else(
+            || filter.evaluate_with_schema(item, self.schema, &FunctionRegistry::new()),
+            |functions| filter.evaluate_with_schema(item, self.schema, functions),
+        ) == Some(Value::Boolean(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bomboni_macros::btree_map_into;
+
+    use crate::{
+        ordering::{OrderingDirection, OrderingTerm},
+        query::{
+            list::{ListQueryBuilder, ListQueryConfig},
+            page_token::plain::PlainPageTokenBuilder,
+            search::{SearchQueryBuilder, SearchQueryConfig},
+        },
+        schema::{FieldMemberSchema, FunctionSchemaMap, ValueType},
+        testing::schema::UserItem,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let qb = ListQueryBuilder::new(
+            UserItem::get_schema(),
+            FunctionSchemaMap::new(),
+            ListQueryConfig {
+                primary_ordering_term: Some(OrderingTerm {
+                    name: "id".into(),
+                    direction: OrderingDirection::Ascending,
+                }),
+                ..Default::default()
+            },
+            PlainPageTokenBuilder {},
+        );
+        let schema = UserItem::get_schema();
+        let executor = MemoryQueryExecutor::new(&schema, qb.page_token_builder());
+        let items = get_items();
+
+        let mut ids = Vec::new();
+        let mut page_token = None;
+        loop {
+            let query = qb
+                .build(Some(2), page_token.as_deref(), Some("age >= 20"), None)
+                .unwrap();
+            let page = executor.execute_list(&query, &items).unwrap();
+            assert_eq!(page.total_size, 5);
+            assert!(page.items.len() <= 2);
+            ids.extend(page.items.iter().map(|item| item.id.as_str()));
+            page_token = page.next_page_token;
+            if page_token.is_none() {
+                break;
+            }
+        }
+        assert_eq!(ids, ["1", "2", "4", "5", "7"]);
+
+        let query = qb
+            .build(Some(10), None, Some("age < 20"), Some("age desc"))
+            .unwrap();
+        let page = executor.execute_list(&query, &items).unwrap();
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|item| item.id.as_str())
+                .collect::<Vec<_>>(),
+            ["3", "6"]
+        );
+        assert_eq!(page.next_page_token, None);
+    }
+
+    #[test]
+    fn page_through() {
+        let qb = ListQueryBuilder::new(
+            UserItem::get_schema(),
+            FunctionSchemaMap::new(),
+            ListQueryConfig::default(),
+            PlainPageTokenBuilder {},
+        );
+        let schema = UserItem::get_schema();
+        let executor = MemoryQueryExecutor::new(&schema, qb.page_token_builder());
+        let mut items = get_items();
+        items.extend(
+            [("8", "Mallory", 30), ("0", "Trent", 30)].map(|(id, display_name, age)| UserItem {
+                id: id.into(),
+                display_name: display_name.into(),
+                age,
+            }),
+        );
+
+        for (ordering, expected_ids) in [
+            (
+                "age desc, id",
+                ["4", "7", "0", "5", "8", "2", "1", "6", "3"],
+            ),
+            (
+                "age, id desc",
+                ["3", "6", "1", "2", "8", "5", "0", "7", "4"],
+            ),
+        ] {
+            let mut ids = Vec::new();
+            let mut page_token = None;
+            loop {
+                let query = qb
+                    .build(Some(2), page_token.as_deref(), None, Some(ordering))
+                    .unwrap();
+                let page = executor.execute_list(&query, &items).unwrap();
+                ids.extend(page.items.iter().map(|item| item.id.clone()));
+                page_token = page.next_page_token;
+                if page_token.is_none() {
+                    break;
+                }
+            }
+            assert_eq!(ids, expected_ids);
+        }
+    }
+
+    #[test]
+    fn search() {
+        let qb = SearchQueryBuilder::new(
+            UserItem::get_schema(),
+            FunctionSchemaMap::new(),
+            SearchQueryConfig {
+                primary_ordering_term: Some(OrderingTerm {
+                    name: "id".into(),
+                    direction: OrderingDirection::Descending,
+                }),
+                ..Default::default()
+            },
+            PlainPageTokenBuilder {},
+        );
+        let schema = UserItem::get_schema();
+        let executor = MemoryQueryExecutor::new(&schema, qb.page_token_builder());
+        let items = get_items();
+
+        let query = qb
+            .build("j", Some(2), None, Some(r#"displayName:"J""#), None)
+            .unwrap();
+        let page = executor.execute_search(&query, &items).unwrap();
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|item| item.id.as_str())
+                .collect::<Vec<_>>(),
+            ["7", "5"]
+        );
+        assert_eq!(page.total_size, 3);
+
+        let query = qb
+            .build(
+                "j",
+                Some(2),
+                page.next_page_token.as_deref(),
+                Some(r#"displayName:"J""#),
+                None,
+            )
+            .unwrap();
+        let page = executor.execute_search(&query, &items).unwrap();
+        assert_eq!(
+            page.items
+                .iter()
+                .map(|item| item.id.as_str())
+                .collect::<Vec<_>>(),
+            ["1"]
+        );
+        assert_eq!(page.next_page_token, None);
+    }
+
+    #[test]
+    fn nulls() {
+        struct PriorityItem {
+            id: i64,
+            priority: Option<i64>,
+        }
+
+        impl SchemaMapped for PriorityItem {
+            fn get_field(&self, name: &str) -> Value {
+                match name {
+                    "id" => self.id.into(),
+                    "priority" => self.priority.map_or(Value::Null, Value::from),
+                    _ => unimplemented!("SchemaMapped for PriorityItem::{name}"),
+                }
+            }
+        }
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "id" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                "priority" => FieldMemberSchema::new_nullable(ValueType::Integer),
+            },
+        };
+        let page_token_builder = PlainPageTokenBuilder {};
+        let executor = MemoryQueryExecutor::new(&schema, &page_token_builder);
+        let items: Vec<_> = (0..40)
+            .map(|id| PriorityItem {
+                id,
+                priority: (id % 3 != 0).then_some(id % 5),
+            })
+            .collect();
+        let get_ids = |ordering: &str| {
+            let query = ListQuery {
+                filter: Filter::default(),
+                ordering: Ordering::parse(ordering).unwrap(),
+                page_size: 50,
+                page_token: None,
+            };
+            executor
+                .execute_list(&query, &items)
+                .unwrap()
+                .items
+                .iter()
+                .map(|item| (item.priority, item.id))
+                .collect::<Vec<_>>()
+        };
+
+        let get_paged_ids = |ordering: &str| {
+            let mut ids = Vec::new();
+            let mut query = ListQuery {
+                filter: Filter::default(),
+                ordering: Ordering::parse(ordering).unwrap(),
+                page_size: 3,
+                page_token: None,
+            };
+            loop {
+                let page = executor.execute_list(&query, &items).unwrap();
+                ids.extend(page.items.iter().map(|item| (item.priority, item.id)));
+                let Some(page_token) = page.next_page_token else {
+                    break;
+                };
+                query.page_token = Some(
+                    page_token_builder
+                        .parse(&query.filter, &query.ordering, &[], &page_token)
+                        .unwrap(),
+                );
+            }
+            ids
+        };
+
+        // Pages cover every item, across and within null values
+        for ordering in ["priority, id", "priority desc, id desc", "id, priority"] {
+            assert_eq!(get_paged_ids(ordering), get_ids(ordering));
+        }
+
+        let ids = get_ids("priority, id");
+        assert_eq!(ids.len(), 40);
+        assert!(ids.is_sorted_by_key(|(priority, id)| (priority.is_none(), *priority, *id)));
+        let ids = get_ids("priority desc, id desc");
+        assert!(ids.is_sorted_by_key(|(priority, id)| (
+            priority.is_none(),
+            priority.map(|priority| -priority),
+            -id
+        )));
+    }
+
+    fn get_items() -> Vec<UserItem> {
+        [
+            ("5", "Jane", 30),
+            ("1", "John", 20),
+            ("3", "Bob", 10),
+            ("7", "Jack", 40),
+            ("2", "Alice", 25),
+            ("6", "Eve", 15),
+            ("4", "Carol", 50),
+        ]
+        .into_iter()
+        .map(|(id, display_name, age)| UserItem {
+            id: id.into(),
+            display_name: display_name.into(),
+            age,
+        })
+        .collect()
+    }
+}
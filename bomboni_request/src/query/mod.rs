@@ -1,9 +1,15 @@
+/// Filter complexity limits.
+pub mod complexity;
 /// Query error types.
 pub mod error;
 /// List query builders.
 pub mod list;
+/// In-memory query execution.
+pub mod memory;
 /// Page token implementations.
 pub mod page_token;
+/// Query policies.
+pub mod policy;
 /// Search query builders.
 pub mod search;
 /// Query utility functions.
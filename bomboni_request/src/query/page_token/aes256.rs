@@ -5,10 +5,10 @@ use crate::{
         error::{QueryError, QueryResult},
         page_token::{
             FilterPageToken, PageTokenBuilder,
-            utility::{get_page_filter, make_page_key},
+            utility::{check_page_filter, get_page_filter, make_page_key},
         },
     },
-    schema::SchemaMapped,
+    schema::{Schema, SchemaMapped},
 };
 use aes_gcm::{
     AeadCore, Aes256Gcm, Key, KeyInit,
@@ -67,6 +67,7 @@ impl PageTokenBuilder for Aes256PageTokenBuilder {
             Filter::parse(&String::from_utf8(plaintext).map_err(|_| QueryError::InvalidPageToken)?)
                 .map_err(|_| QueryError::InvalidPageToken)?;
 
+        check_page_filter(&page_filter)?;
         Ok(Self::PageToken {
             filter: page_filter,
         })
@@ -74,12 +75,13 @@ impl PageTokenBuilder for Aes256PageTokenBuilder {
 
     fn build_next<T: SchemaMapped>(
         &self,
+        schema: &Schema,
         filter: &Filter,
         ordering: &Ordering,
         salt: &[u8],
         next_item: &T,
     ) -> QueryResult<String> {
-        let page_filter = get_page_filter(ordering, next_item);
+        let page_filter = get_page_filter(schema, ordering, next_item);
         if page_filter.is_empty() {
             return Err(QueryError::PageTokenFailure);
         }
@@ -122,6 +124,7 @@ mod tests {
         let ordering = Ordering::parse("id desc, age desc").unwrap();
         let page_token = b
             .build_next(
+                &UserItem::get_schema(),
                 &filter,
                 &ordering,
                 &[],
@@ -133,11 +136,11 @@ mod tests {
             )
             .unwrap();
         assert!(page_token.trim().len() > NONCE_LENGTH);
-        assert!(!page_token.contains(r#"id <= "1337""#));
+        assert!(!page_token.contains(r#"id < "1337""#));
         let parsed = b.parse(&filter, &ordering, &[], &page_token).unwrap();
         assert_eq!(
             parsed.filter.to_string(),
-            r#"id <= "1337" AND age <= 14000"#
+            r#"id < "1337" OR (id = "1337" AND age <= 14000)"#
         );
     }
 
@@ -151,6 +154,7 @@ mod tests {
         let salt = "salt".as_bytes();
         let page_token = b
             .build_next(
+                &UserItem::get_schema(),
                 &filter,
                 &ordering,
                 salt,
@@ -3,7 +3,10 @@ use crate::{
     ordering::Ordering,
     query::{
         error::{QueryError, QueryResult},
-        page_token::{FilterPageToken, PageTokenBuilder, utility::get_page_filter},
+        page_token::{
+            FilterPageToken, PageTokenBuilder,
+            utility::{check_page_filter, get_page_filter},
+        },
     },
 };
 use base64ct::{Base64, Base64Url, Encoding};
@@ -40,6 +43,7 @@ impl PageTokenBuilder for Base64PageTokenBuilder {
         };
         let page_filter =
             Filter::parse(&String::from_utf8(decoded).map_err(|_| QueryError::InvalidPageToken)?)?;
+        check_page_filter(&page_filter)?;
         Ok(Self::PageToken {
             filter: page_filter,
         })
@@ -47,12 +51,13 @@ impl PageTokenBuilder for Base64PageTokenBuilder {
 
     fn build_next<T: crate::schema::SchemaMapped>(
         &self,
+        schema: &crate::schema::Schema,
         _filter: &Filter,
         ordering: &Ordering,
         _salt: &[u8],
         next_item: &T,
     ) -> QueryResult<String> {
-        let page_filter = get_page_filter(ordering, next_item);
+        let page_filter = get_page_filter(schema, ordering, next_item);
         if page_filter.is_empty() {
             return Err(QueryError::PageTokenFailure);
         }
@@ -83,6 +88,7 @@ mod tests {
         let ordering = Ordering::parse("id desc, age desc").unwrap();
         let page_token = b
             .build_next(
+                &UserItem::get_schema(),
                 &filter,
                 &ordering,
                 &[],
@@ -93,11 +99,14 @@ mod tests {
                 },
             )
             .unwrap();
-        assert_eq!(page_token, "aWQgPD0gIjEzMzciIEFORCBhZ2UgPD0gMTQwMDA=");
+        assert_eq!(
+            page_token,
+            "aWQgPCAiMTMzNyIgT1IgKGlkID0gIjEzMzciIEFORCBhZ2UgPD0gMTQwMDAp"
+        );
         let parsed = b.parse(&filter, &ordering, &[], &page_token).unwrap();
         assert_eq!(
             parsed.filter.to_string(),
-            r#"id <= "1337" AND age <= 14000"#
+            r#"id < "1337" OR (id = "1337" AND age <= 14000)"#
         );
     }
 }
@@ -8,7 +8,13 @@
 
 use std::fmt::{self, Display, Formatter};
 
-use crate::{filter::Filter, ordering::Ordering, schema::SchemaMapped};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    filter::Filter,
+    ordering::Ordering,
+    schema::{Schema, SchemaMapped},
+};
 
 use super::error::QueryResult;
 /// AES256 page token encoding.
@@ -25,7 +31,7 @@ pub mod rsa;
 mod utility;
 
 /// A page token containing a filter.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FilterPageToken {
     /// Filter.
     pub filter: Filter,
@@ -59,6 +65,7 @@ pub trait PageTokenBuilder {
     /// Build a page token for the next page of results.
     ///
     /// Note that "last item" is not necessarily the last item of the page, but N+1th one.
+    /// The schema determines which ordering terms are nullable, and can be followed by nulls.
     /// We can fetch `page_size+1` items from the database to determine if there are more results.
     /// [`crate::query::error::QueryError::PageTokenFailure`] is returned if the page token could not be built.
     ///
@@ -67,6 +74,7 @@ pub trait PageTokenBuilder {
     /// Will return an error if the page token cannot be built.
     fn build_next<T: SchemaMapped>(
         &self,
+        schema: &Schema,
         filter: &Filter,
         ordering: &Ordering,
         salt: &[u8],
@@ -3,9 +3,12 @@ use crate::{
     ordering::Ordering,
     query::{
         error::{QueryError, QueryResult},
-        page_token::{FilterPageToken, PageTokenBuilder, utility::get_page_filter},
+        page_token::{
+            FilterPageToken, PageTokenBuilder,
+            utility::{check_page_filter, get_page_filter},
+        },
     },
-    schema::SchemaMapped,
+    schema::{Schema, SchemaMapped},
 };
 use std::fmt::{self, Debug, Formatter};
 
@@ -25,6 +28,7 @@ impl PageTokenBuilder for PlainPageTokenBuilder {
         page_token: &str,
     ) -> QueryResult<Self::PageToken> {
         let page_filter = Filter::parse(page_token)?;
+        check_page_filter(&page_filter)?;
         Ok(Self::PageToken {
             filter: page_filter,
         })
@@ -32,12 +36,13 @@ impl PageTokenBuilder for PlainPageTokenBuilder {
 
     fn build_next<T: SchemaMapped>(
         &self,
+        schema: &Schema,
         _filter: &Filter,
         ordering: &Ordering,
         _salt: &[u8],
         next_item: &T,
     ) -> QueryResult<String> {
-        let page_filter = get_page_filter(ordering, next_item);
+        let page_filter = get_page_filter(schema, ordering, next_item);
         if page_filter.is_empty() {
             return Err(QueryError::PageTokenFailure);
         }
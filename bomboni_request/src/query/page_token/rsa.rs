@@ -5,7 +5,7 @@ use crate::{
         error::{QueryError, QueryResult},
         page_token::{
             FilterPageToken, PageTokenBuilder,
-            utility::{get_page_filter, make_page_key},
+            utility::{check_page_filter, get_page_filter, make_page_key},
         },
     },
 };
@@ -67,6 +67,7 @@ impl PageTokenBuilder for RsaPageTokenBuilder {
         )
         .map_err(|_| QueryError::InvalidPageToken)?;
 
+        check_page_filter(&page_filter)?;
         Ok(Self::PageToken {
             filter: page_filter,
         })
@@ -74,12 +75,13 @@ impl PageTokenBuilder for RsaPageTokenBuilder {
 
     fn build_next<T: crate::schema::SchemaMapped>(
         &self,
+        schema: &crate::schema::Schema,
         filter: &Filter,
         ordering: &Ordering,
         salt: &[u8],
         next_item: &T,
     ) -> QueryResult<String> {
-        let page_filter = get_page_filter(ordering, next_item);
+        let page_filter = get_page_filter(schema, ordering, next_item);
         if page_filter.is_empty() {
             return Err(QueryError::PageTokenFailure);
         }
@@ -92,7 +94,7 @@ impl PageTokenBuilder for RsaPageTokenBuilder {
         let encrypted = self
             .public_key
             .encrypt(&mut rng, Pkcs1v15Encrypt, &plaintext)
-            .unwrap();
+            .map_err(|_| QueryError::PageTokenFailure)?;
 
         if self.url_safe {
             Ok(Base64Url::encode_string(&encrypted))
@@ -123,6 +125,7 @@ mod tests {
         let ordering = Ordering::parse("id desc, age desc").unwrap();
         let page_token = b
             .build_next(
+                &UserItem::get_schema(),
                 &filter,
                 &ordering,
                 &[],
@@ -134,11 +137,11 @@ mod tests {
             )
             .unwrap();
         assert!(page_token.trim().len() > 16);
-        assert!(!page_token.contains(r#"id <= "1337""#));
+        assert!(!page_token.contains(r#"id < "1337""#));
         let parsed = b.parse(&filter, &ordering, &[], &page_token).unwrap();
         assert_eq!(
             parsed.filter.to_string(),
-            r#"id <= "1337" AND age <= 14000"#
+            r#"id < "1337" OR (id = "1337" AND age <= 14000)"#
         );
     }
 
@@ -152,6 +155,7 @@ mod tests {
         let salt = "salt".as_bytes();
         let page_token = b
             .build_next(
+                &UserItem::get_schema(),
                 &filter,
                 &ordering,
                 salt,
@@ -194,7 +198,7 @@ mod tests {
         static SINGLETON: OnceLock<RsaPageTokenBuilder> = OnceLock::new();
         SINGLETON.get_or_init(|| {
             let mut rng = OsRng;
-            let private_key = RsaPrivateKey::new(&mut rng, 720).unwrap();
+            let private_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
             let public_key = RsaPublicKey::from(&private_key);
             RsaPageTokenBuilder::new(private_key, public_key, true)
         })
@@ -3,48 +3,120 @@ use blake2::Blake2s256;
 use crate::filter::FilterComparator;
 use crate::ordering::OrderingDirection;
 use crate::value::Value;
-use crate::{filter::Filter, schema::SchemaMapped};
+use crate::{
+    filter::Filter,
+    schema::{Schema, SchemaMapped},
+};
 use blake2::Digest;
 
 use crate::ordering::Ordering;
+use crate::query::error::{QueryError, QueryResult};
 
-/// Constructs a filter that selects items greater than `next_item` based on ordering.
-/// For example, if the ordering is "age desc", then the filter will be `age <= next_item.age`.
-/// "Equals" (>=, <=) is used to ensure that the next item is included in the results.
-pub fn get_page_filter<T: SchemaMapped>(ordering: &Ordering, next_item: &T) -> Filter {
+/// Constructs a filter that selects items from `next_item` onwards, based on ordering.
+/// For example, if the ordering is "age desc, id", then the filter will be
+/// `age < next_item.age OR (age = next_item.age AND id >= next_item.id)`.
+/// Only the last term is compared with "equals" (>=, <=), to ensure that the next item is included in the results.
+///
+/// Null values are ordered last, as in [`Ordering::evaluate`].
+/// Nulls of nullable fields follow every value, such as `age < 30 OR age = null`,
+/// and only nulls follow a null value.
+pub fn get_page_filter<T: SchemaMapped>(
+    schema: &Schema,
+    ordering: &Ordering,
+    next_item: &T,
+) -> Filter {
+    let mut equalities = Vec::new();
     let mut filters = Vec::new();
-
-    for term in ordering.iter() {
-        let term_argument = match next_item.get_field(&term.name) {
-            Value::Integer(value) => Filter::Value(value.into()),
-            Value::Float(value) => Filter::Value(value.into()),
-            Value::Boolean(value) => Filter::Value(value.into()),
-            Value::String(value) => Filter::Value(value.into()),
-            Value::Timestamp(value) => Filter::Value(value.into()),
-            Value::Repeated(value) => Filter::Value(value.into()),
-            Value::Any => Filter::Value(Value::Any),
+    for (i, term) in ordering.iter().enumerate() {
+        let value = next_item.get_field(&term.name);
+        let is_last = i + 1 == ordering.len();
+        let nullable = schema
+            .get_field(&term.name)
+            .is_some_and(|field| field.nullable);
+        let bound = if value == Value::Null {
+            is_last.then(|| get_restriction(&term.name, FilterComparator::Equal, Value::Null))
+        } else {
+            let comparator = match (term.direction, is_last) {
+                (OrderingDirection::Ascending, false) => FilterComparator::Greater,
+                (OrderingDirection::Ascending, true) => FilterComparator::GreaterOrEqual,
+                (OrderingDirection::Descending, false) => FilterComparator::Less,
+                (OrderingDirection::Descending, true) => FilterComparator::LessOrEqual,
+            };
+            let restriction = get_restriction(&term.name, comparator, value.clone());
+            Some(if nullable {
+                Filter::Disjunction(vec![
+                    restriction,
+                    get_restriction(&term.name, FilterComparator::Equal, Value::Null),
+                ])
+            } else {
+                restriction
+            })
         };
+        match bound {
+            Some(Filter::Disjunction(parts)) if equalities.is_empty() => filters.extend(parts),
+            Some(bound) => {
+                let mut parts = equalities.clone();
+                parts.push(if matches!(bound, Filter::Disjunction(_)) {
+                    Filter::Composite(Box::new(bound))
+                } else {
+                    bound
+                });
+                filters.push(if parts.len() == 1 {
+                    parts.pop().unwrap()
+                } else {
+                    Filter::Composite(Box::new(Filter::Conjunction(parts)))
+                });
+            }
+            None => {}
+        }
+        equalities.push(get_restriction(&term.name, FilterComparator::Equal, value));
+    }
 
-        filters.push(Filter::Restriction(
-            Box::new(Filter::Name(term.name.clone())),
-            match term.direction {
-                OrderingDirection::Ascending => FilterComparator::GreaterOrEqual,
-                OrderingDirection::Descending => FilterComparator::LessOrEqual,
-            },
-            Box::new(term_argument),
-        ));
+    match filters.len() {
+        0 => Filter::Conjunction(Vec::new()),
+        1 => filters.pop().unwrap(),
+        _ => Filter::Disjunction(filters),
     }
+}
 
-    // Disjunction?
-    Filter::Conjunction(filters)
+fn get_restriction(name: &str, comparator: FilterComparator, value: Value) -> Filter {
+    Filter::Restriction(
+        Box::new(Filter::Name(name.into())),
+        comparator,
+        Box::new(Filter::Value(value)),
+    )
+}
+
+/// Checks that a parsed page filter only combines literals and restrictions of members to literal values.
+/// Page tokens are user input, and page filters of other shapes, such as function calls or negations,
+/// could select items other than the ones following the previous page.
+/// Query backends combine page filters with the query filter such that they can only narrow it down.
+pub fn check_page_filter(page_filter: &Filter) -> QueryResult<()> {
+    let is_valid = match page_filter {
+        Filter::Conjunction(parts) | Filter::Disjunction(parts) => {
+            return parts.iter().try_for_each(check_page_filter);
+        }
+        Filter::Composite(composite) => return check_page_filter(composite),
+        Filter::Restriction(comparable, _, argument) => {
+            matches!(comparable.as_ref(), Filter::Name(_))
+                && matches!(argument.as_ref(), Filter::Value(_))
+        }
+        page_filter => matches!(page_filter, Filter::Value(_)),
+    };
+    if is_valid {
+        Ok(())
+    } else {
+        Err(QueryError::InvalidPageToken)
+    }
 }
 
 /// Constructs a page key from a filter and ordering.
 /// The key should be completely different for different filters and orderings.
+/// Filter is normalized first, so that equivalent filters produce the same key.
 pub fn make_page_key<const N: usize>(filter: &Filter, ordering: &Ordering, salt: &[u8]) -> [u8; N] {
     let mut hasher = Blake2s256::new();
 
-    hasher.update(filter.to_string().as_bytes());
+    hasher.update(filter.normalize().to_string().as_bytes());
     hasher.update(ordering.to_string().as_bytes());
     hasher.update(salt);
 
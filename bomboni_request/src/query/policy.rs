@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    filter::{Filter, FilterComparator, visit::FilterVisitor},
+    query::error::{QueryError, QueryResult},
+};
+
+/// Mandatory restrictions of queries, such as row-level security.
+///
+/// Policies are created by the service for each request context, such as for the tenant of an authenticated caller,
+/// and applied to user filters by query builders.
+/// The user filter is sandboxed in parentheses and combined with policy restrictions in a conjunction,
+/// such that no user filter can match items outside of the policy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryPolicy {
+    /// Restrictions that every matched item must satisfy.
+    pub filter: Filter,
+    /// Access of user filters to members, by member name.
+    ///
+    /// Access to a member also applies to its nested members, such as `user.id` of `user`.
+    pub members: BTreeMap<String, PolicyMemberAccess>,
+}
+
+/// Access of user filters to a policy member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyMemberAccess {
+    /// User filters may not reference the member.
+    Forbidden,
+    /// User filters may reference the member, but not in a negation or an inequality.
+    NonNegatable,
+}
+
+impl QueryPolicy {
+    /// Creates a new policy without restrictions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mandatory restriction.
+    pub fn restrict(&mut self, filter: Filter) -> &mut Self {
+        let filter = match filter {
+            Filter::Disjunction(_) => Filter::Composite(Box::new(filter)),
+            filter => filter,
+        };
+        if self.filter.is_empty() {
+            self.filter = filter;
+        } else {
+            self.filter.add_conjunction(filter);
+        }
+        self
+    }
+
+    /// Sets access of user filters to a member.
+    pub fn protect(&mut self, name: &str, access: PolicyMemberAccess) -> &mut Self {
+        self.members.insert(name.into(), access);
+        self
+    }
+
+    /// Checks a user filter against member access rules, and combines it with policy restrictions.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`QueryError::FilterForbiddenMember`] if filter references a forbidden member.
+    /// Will return [`QueryError::FilterNegatedMember`] if filter negates a non-negatable member, or compares it with `<`, `<=`, `>` or `>=`.
+    pub fn apply(&self, filter: Filter) -> QueryResult<Filter> {
+        if !self.members.is_empty() {
+            PolicyChecker {
+                policy: self,
+                negated: false,
+                ordered: false,
+            }
+            .visit_filter(&filter)?;
+        }
+
+        if self.filter.is_empty() {
+            return Ok(filter);
+        }
+        if filter.is_empty() {
+            return Ok(self.filter.clone());
+        }
+        let mut parts = match &self.filter {
+            Filter::Conjunction(parts) => parts.clone(),
+            policy => vec![policy.clone()],
+        };
+        parts.push(match filter {
+            Filter::Composite(_) => filter,
+            filter => Filter::Composite(Box::new(filter)),
+        });
+        Ok(Filter::Conjunction(parts))
+    }
+
+    fn get_member_access(&self, name: &str) -> Option<PolicyMemberAccess> {
+        self.members.iter().find_map(|(member, access)| {
+            name.strip_prefix(member.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+                .then_some(*access)
+        })
+    }
+}
+
+/// Checks references of protected members, tracking whether the current subtree is negated or ordered.
+struct PolicyChecker<'a> {
+    policy: &'a QueryPolicy,
+    negated: bool,
+    /// Whether the current subtree is an operand of an ordering comparator.
+    ordered: bool,
+}
+
+impl FilterVisitor for PolicyChecker<'_> {
+    type Error = QueryError;
+
+    fn visit_negate(&mut self, tree: &Filter) -> Result<(), Self::Error> {
+        self.negated = !self.negated;
+        self.visit_filter(tree)?;
+        self.negated = !self.negated;
+        Ok(())
+    }
+
+    fn visit_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> Result<(), Self::Error> {
+        let (negated, ordered) = (self.negated, self.ordered);
+        match comparator {
+            FilterComparator::NotEqual => self.negated = !self.negated,
+            // Ranges can express negations, such that `a < 1 OR a > 1` is `a != 1`
+            FilterComparator::Less
+            | FilterComparator::LessOrEqual
+            | FilterComparator::Greater
+            | FilterComparator::GreaterOrEqual => self.ordered = true,
+            FilterComparator::Equal | FilterComparator::Has => {}
+        }
+        let result = self
+            .visit_filter(comparable)
+            .and_then(|()| self.visit_filter(argument));
+        (self.negated, self.ordered) = (negated, ordered);
+        result
+    }
+
+    fn visit_name(&mut self, name: &str) -> Result<(), Self::Error> {
+        match self.policy.get_member_access(name) {
+            Some(PolicyMemberAccess::Forbidden) => {
+                Err(QueryError::FilterForbiddenMember(name.into()))
+            }
+            Some(PolicyMemberAccess::NonNegatable) if self.negated || self.ordered => {
+                Err(QueryError::FilterNegatedMember(name.into()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let mut policy = QueryPolicy::new();
+        policy
+            .restrict(Filter::parse(r#"tenant = "acme""#).unwrap())
+            .restrict(Filter::parse("visibility = 1 OR owner = 42").unwrap())
+            .protect("tenant", PolicyMemberAccess::Forbidden)
+            .protect("owner", PolicyMemberAccess::NonNegatable);
+        let apply = |source: &str| policy.apply(Filter::parse(source).unwrap());
+
+        assert_eq!(
+            apply("").unwrap().to_string(),
+            r#"tenant = "acme" AND (visibility = 1 OR owner = 42)"#
+        );
+        assert_eq!(
+            apply("a OR b").unwrap().to_string(),
+            r#"tenant = "acme" AND (visibility = 1 OR owner = 42) AND (a OR b)"#
+        );
+        assert_eq!(
+            apply("(a AND b)").unwrap().to_string(),
+            r#"tenant = "acme" AND (visibility = 1 OR owner = 42) AND (a AND b)"#
+        );
+        assert!(apply("owner = 1 AND NOT x").is_ok());
+        assert!(apply("NOT owner != 1").is_ok());
+
+        assert_eq!(
+            apply(r#"a OR tenant = "other""#).unwrap_err(),
+            QueryError::FilterForbiddenMember("tenant".into())
+        );
+        assert_eq!(
+            apply("f(tenant.id)").unwrap_err(),
+            QueryError::FilterForbiddenMember("tenant.id".into())
+        );
+        assert!(apply("tenantName = 1").is_ok());
+        assert_eq!(
+            apply("NOT (x AND owner = 1)").unwrap_err(),
+            QueryError::FilterNegatedMember("owner".into())
+        );
+        assert_eq!(
+            apply("x = owner OR owner != 1").unwrap_err(),
+            QueryError::FilterNegatedMember("owner".into())
+        );
+        assert_eq!(
+            apply("owner < 42 OR owner > 42").unwrap_err(),
+            QueryError::FilterNegatedMember("owner".into())
+        );
+        assert_eq!(
+            apply("NOT owner >= 42").unwrap_err(),
+            QueryError::FilterNegatedMember("owner".into())
+        );
+        assert_eq!(
+            apply("42 <= owner").unwrap_err(),
+            QueryError::FilterNegatedMember("owner".into())
+        );
+
+        assert_eq!(
+            QueryPolicy::new()
+                .apply(Filter::parse("a OR b").unwrap())
+                .unwrap()
+                .to_string(),
+            "a OR b"
+        );
+    }
+}
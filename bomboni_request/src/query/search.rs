@@ -3,14 +3,16 @@
 //! Utility for working with fuzzy search queries.
 
 use crate::{
-    filter::Filter,
+    filter::{Filter, score::FilterScore},
     ordering::{Ordering, OrderingTerm},
     query::{
+        complexity::FilterComplexityConfig,
         error::{QueryError, QueryResult},
         page_token::{
             FilterPageToken, PageTokenBuilder, aes256::Aes256PageTokenBuilder,
             base64::Base64PageTokenBuilder, plain::PlainPageTokenBuilder, rsa::RsaPageTokenBuilder,
         },
+        policy::QueryPolicy,
         utility::{parse_query_filter, parse_query_ordering},
     },
     schema::{FunctionSchemaMap, Schema, SchemaMapped},
@@ -48,6 +50,8 @@ pub struct SearchQueryConfig {
     pub max_filter_length: Option<usize>,
     /// Maximum ordering length.
     pub max_ordering_length: Option<usize>,
+    /// Filter complexity limits.
+    pub filter_complexity: FilterComplexityConfig,
 }
 
 /// Builder for search queries.
@@ -77,6 +81,28 @@ impl SearchQuery {
     }
 }
 
+impl<T> SearchQuery<T> {
+    /// Ranks items matching the filter by relevance, from the most relevant.
+    ///
+    /// Items with equal scores keep their order, see [`Filter::evaluate_score`].
+    pub fn rank<I>(&self, items: I, schema: &Schema) -> Vec<(I::Item, FilterScore)>
+    where
+        I: IntoIterator,
+        I::Item: SchemaMapped,
+    {
+        let mut ranked: Vec<_> = items
+            .into_iter()
+            .map(|item| {
+                let score = self.filter.evaluate_score(&item, schema);
+                (item, score)
+            })
+            .filter(|(_, score)| score.matched)
+            .collect();
+        ranked.sort_by_key(|(_, score)| std::cmp::Reverse(score.score));
+        ranked
+    }
+}
+
 impl Default for SearchQueryConfig {
     fn default() -> Self {
         Self {
@@ -86,6 +112,7 @@ impl Default for SearchQueryConfig {
             primary_ordering_term: None,
             max_filter_length: None,
             max_ordering_length: None,
+            filter_complexity: FilterComplexityConfig::default(),
         }
     }
 }
@@ -113,6 +140,8 @@ impl<P: PageTokenBuilder> SearchQueryBuilder<P> {
     /// Will return [`QueryError::QueryTooLong`] if query exceeds maximum length.
     /// Will return [`QueryError::FilterTooLong`] if filter exceeds maximum length.
     /// Will return [`QueryError::FilterError`] if filter cannot be parsed or validated.
+    /// Will return [`QueryError::FilterTooDeep`], [`QueryError::FilterDisjunctionTooWide`], [`QueryError::FilterTooManyFunctionCalls`]
+    /// or [`QueryError::FilterTooExpensive`] if filter exceeds complexity limits.
     /// Will return [`QueryError::OrderingTooLong`] if ordering exceeds maximum length.
     /// Will return [`QueryError::OrderingError`] if ordering cannot be parsed or validated.
     /// Will return [`QueryError::InvalidPageSize`] if page size is negative.
@@ -124,6 +153,41 @@ impl<P: PageTokenBuilder> SearchQueryBuilder<P> {
         page_token: Option<&str>,
         filter: Option<&str>,
         ordering: Option<&str>,
+    ) -> QueryResult<SearchQuery<P::PageToken>> {
+        self.build_with_policy(
+            &QueryPolicy::default(),
+            query,
+            page_size,
+            page_token,
+            filter,
+            ordering,
+        )
+    }
+
+    /// Builds a search query, applying the policy to its filter.
+    ///
+    /// Filter of the built query is restricted by the policy, see [`QueryPolicy::apply`].
+    ///
+    /// # Errors
+    ///
+    /// Will return [`QueryError::QueryTooLong`] if query exceeds maximum length.
+    /// Will return [`QueryError::FilterTooLong`] if filter exceeds maximum length.
+    /// Will return [`QueryError::FilterError`] if filter cannot be parsed or validated.
+    /// Will return [`QueryError::FilterTooDeep`], [`QueryError::FilterDisjunctionTooWide`], [`QueryError::FilterTooManyFunctionCalls`]
+    /// or [`QueryError::FilterTooExpensive`] if filter exceeds complexity limits.
+    /// Will return [`QueryError::FilterForbiddenMember`] or [`QueryError::FilterNegatedMember`] if filter violates policy member access.
+    /// Will return [`QueryError::OrderingTooLong`] if ordering exceeds maximum length.
+    /// Will return [`QueryError::OrderingError`] if ordering cannot be parsed or validated.
+    /// Will return [`QueryError::InvalidPageSize`] if page size is negative.
+    /// Will return page token parsing errors from the underlying page token builder.
+    pub fn build_with_policy(
+        &self,
+        policy: &QueryPolicy,
+        query: &str,
+        page_size: Option<i32>,
+        page_token: Option<&str>,
+        filter: Option<&str>,
+        ordering: Option<&str>,
     ) -> QueryResult<SearchQuery<P::PageToken>> {
         if matches!(self.options.max_query_length, Some(max) if query.len() > max) {
             return Err(QueryError::QueryTooLong);
@@ -135,6 +199,8 @@ impl<P: PageTokenBuilder> SearchQueryBuilder<P> {
             Some(&self.schema_functions),
             self.options.max_filter_length,
         )?;
+        self.options.filter_complexity.check(&filter)?;
+        let filter = policy.apply(filter)?;
         let mut ordering =
             parse_query_ordering(ordering, &self.schema, self.options.max_ordering_length)?;
 
@@ -192,6 +258,7 @@ impl<P: PageTokenBuilder> SearchQueryBuilder<P> {
         next_item: &T,
     ) -> QueryResult<String> {
         self.page_token_builder.build_next(
+            &self.schema,
             &query.filter,
             &query.ordering,
             &SearchQuery::make_salt(&query.query, query.page_size),
@@ -211,6 +278,7 @@ mod tests {
         filter::error::FilterError,
         ordering::{OrderingDirection, error::OrderingError},
         query::page_token::plain::PlainPageTokenBuilder,
+        span::Span,
         testing::schema::UserItem,
     };
 
@@ -233,6 +301,35 @@ mod tests {
         assert_eq!(query.ordering.to_string(), "id desc, age desc");
     }
 
+    #[test]
+    fn rank() {
+        let qb = get_query_builder();
+        let query = qb
+            .build(
+                "abc",
+                None,
+                None,
+                Some(r#"age > 10 (displayName:"J" OR age > 30)"#),
+                None,
+            )
+            .unwrap();
+        let items: Vec<_> = [("1", "John", 40), ("2", "Jane", 20), ("3", "Bob", 50)]
+            .into_iter()
+            .map(|(id, display_name, age)| UserItem {
+                id: id.into(),
+                display_name: display_name.into(),
+                age,
+            })
+            .collect();
+
+        let ranked: Vec<_> = query
+            .rank(&items, &UserItem::get_schema())
+            .into_iter()
+            .map(|(item, score)| (item.id.as_str(), score.score))
+            .collect();
+        assert_eq!(ranked, vec![("1", 3), ("2", 2), ("3", 2)]);
+    }
+
     #[test]
     fn errors() {
         let q = get_query_builder();
@@ -257,7 +354,7 @@ mod tests {
         ));
         assert!(matches!(
             q.build("abc", None, None, Some("f!"), None).unwrap_err(),
-            QueryError::FilterError(FilterError::Parse { start, end })
+            QueryError::FilterError { error: FilterError::Parse { start, end }, span: None }
             if start == 1 && end == 1
         ));
         assert_eq!(
@@ -267,7 +364,10 @@ mod tests {
         );
         assert_eq!(
             q.build("abc", None, None, Some("lol"), None).unwrap_err(),
-            QueryError::FilterError(FilterError::UnknownMember("lol".into()))
+            QueryError::FilterError {
+                error: FilterError::UnknownMember("lol".into()),
+                span: Some(Span::locate("lol", 0, 3)),
+            }
         );
         assert_eq!(
             q.build("abc", None, None, None, Some(&("a".repeat(100))))
@@ -276,7 +376,10 @@ mod tests {
         );
         assert_eq!(
             q.build("abc", None, None, None, Some("lol")).unwrap_err(),
-            QueryError::OrderingError(OrderingError::UnknownMember("lol".into()))
+            QueryError::OrderingError {
+                error: OrderingError::UnknownMember("lol".into()),
+                span: Some(Span::locate("lol", 0, 3)),
+            }
         );
     }
 
@@ -294,6 +397,7 @@ mod tests {
                 max_query_length: Some(50),
                 max_filter_length: Some(50),
                 max_ordering_length: Some(50),
+                ..Default::default()
             },
             PlainPageTokenBuilder {},
         )
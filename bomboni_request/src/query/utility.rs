@@ -7,6 +7,8 @@ use crate::{
 
 /// Parses a query filter.
 ///
+/// The filter is resolved against schema, see [`Filter::resolve`].
+///
 /// # Errors
 ///
 /// Will return [`QueryError::FilterTooLong`] if filter exceeds maximum length.
@@ -22,8 +24,9 @@ pub fn parse_query_filter(
         if matches!(max_filter_length, Some(max) if filter.len() > max) {
             return Err(QueryError::FilterTooLong);
         }
-        let filter = Filter::parse(filter)?;
-        filter.validate(schema, schema_functions)?;
+        let (filter, span) = Filter::parse_spanned(filter)?;
+        let filter = filter.resolve(schema);
+        filter.validate_spanned(&span, schema, schema_functions)?;
         Ok(filter)
     } else {
         Ok(Filter::default())
@@ -45,8 +48,8 @@ pub fn parse_query_ordering(
         if matches!(max_ordering_length, Some(max) if ordering.len() > max) {
             return Err(QueryError::OrderingTooLong);
         }
-        let ordering = Ordering::parse(ordering)?;
-        ordering.validate(schema)?;
+        let (ordering, spans) = Ordering::parse_spanned(ordering)?;
+        ordering.validate_spanned(&spans, schema)?;
         Ok(ordering)
     } else {
         Ok(Ordering::default())
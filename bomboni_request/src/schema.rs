@@ -19,6 +19,8 @@ pub enum MemberSchema {
     Resource(ResourceMemberSchema),
     /// Field member.
     Field(FieldMemberSchema),
+    /// Map member, whose entries are accessed by key, such as `labels.env`.
+    Map(MapMemberSchema),
 }
 
 /// Resource member schema.
@@ -26,6 +28,19 @@ pub enum MemberSchema {
 pub struct ResourceMemberSchema {
     /// Resource fields.
     pub fields: BTreeMap<String, MemberSchema>,
+    /// Whether resource is repeated.
+    ///
+    /// Restrictions on fields of repeated resources, such as `authors.country = "SI"`, match if any element matches.
+    pub repeated: bool,
+}
+
+/// Map member schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapMemberSchema {
+    /// Key type.
+    pub key_type: ValueType,
+    /// Schema of map values.
+    pub value: Box<MemberSchema>,
 }
 
 /// Field member schema.
@@ -39,6 +54,19 @@ pub struct FieldMemberSchema {
     pub ordered: bool,
     /// Whether field allows has operator.
     pub allow_has_operator: bool,
+    /// Whether field may be absent, in which case its value is [`Value::Null`].
+    pub nullable: bool,
+    /// Allowed values of an enum field.
+    pub enum_schema: Option<EnumSchema>,
+    /// Weight of matches on the field in relevance scoring, see [`crate::filter::Filter::evaluate_score`].
+    pub weight: u32,
+}
+
+/// Enum schema.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EnumSchema {
+    /// Symbolic names of allowed values, mapped to their optional numeric values.
+    pub values: BTreeMap<String, Option<i64>>,
 }
 
 /// Function schema.
@@ -66,6 +94,10 @@ pub enum ValueType {
     String,
     /// Timestamp value.
     Timestamp,
+    /// Duration value.
+    Duration,
+    /// Enum value.
+    Enum,
     /// Any value.
     Any,
     // ResourceName,
@@ -74,23 +106,34 @@ pub enum ValueType {
 /// Trait for types that can be mapped to schema values.
 pub trait SchemaMapped {
     /// Gets field value by name.
+    ///
+    /// Absent values of nullable fields are [`Value::Null`].
+    /// Map members are requested as a whole, such as `labels`, returning [`Value::Map`],
+    /// and by entry, such as `labels.env`, returning the value of the entry.
+    /// Fields of repeated resources, such as `authors.country`, are requested by full name,
+    /// returning [`Value::Repeated`] with the value of the field for each element.
     fn get_field(&self, name: &str) -> Value;
 }
 
+impl<T: SchemaMapped + ?Sized> SchemaMapped for &T {
+    fn get_field(&self, name: &str) -> Value {
+        (**self).get_field(name)
+    }
+}
+
 impl Schema {
     /// Gets member schema by name.
     pub fn get_member(&self, name: &str) -> Option<&MemberSchema> {
         let mut member: Option<&MemberSchema> = None;
         for step in name.split('.') {
             if let Some(upper_member) = member {
-                if let MemberSchema::Resource(resource) = upper_member {
-                    if let Some(resource_field) = resource.fields.get(step) {
-                        member = Some(resource_field);
-                    } else {
-                        return None;
+                match upper_member {
+                    MemberSchema::Resource(resource) => {
+                        member = Some(resource.fields.get(step)?);
                     }
-                } else {
-                    return None;
+                    // Any key resolves to the map value
+                    MemberSchema::Map(map) => member = Some(&map.value),
+                    MemberSchema::Field(_) => return None,
                 }
             } else if let Some(step_member) = self.members.get(step) {
                 member = Some(step_member);
@@ -101,6 +144,33 @@ impl Schema {
         member
     }
 
+    /// Splits name of a map entry, such as `labels.env`, into the map member name and the key.
+    pub fn split_map_entry<'n>(
+        &self,
+        name: &'n str,
+    ) -> Option<(&'n str, &'n str, &MapMemberSchema)> {
+        let (map_name, key) = name.rsplit_once('.')?;
+        if let MemberSchema::Map(map) = self.get_member(map_name)? {
+            Some((map_name, key, map))
+        } else {
+            None
+        }
+    }
+
+    /// Splits name of a member inside of a repeated resource, such as `authors.country`,
+    /// into the name of the repeated resource and the name of the member within each element.
+    pub fn split_repeated_resource<'n>(&self, name: &'n str) -> Option<(&'n str, &'n str)> {
+        name.match_indices('.').find_map(|(i, _)| {
+            let resource_name = &name[..i];
+            match self.get_member(resource_name)? {
+                MemberSchema::Resource(resource) if resource.repeated => {
+                    Some((resource_name, &name[i + 1..]))
+                }
+                _ => None,
+            }
+        })
+    }
+
     /// Gets field schema by name.
     pub fn get_field(&self, name: &str) -> Option<&FieldMemberSchema> {
         if let Some(MemberSchema::Field(field)) = self.get_member(name) {
@@ -119,6 +189,9 @@ impl FieldMemberSchema {
             repeated: false,
             ordered: false,
             allow_has_operator: true,
+            nullable: false,
+            enum_schema: None,
+            weight: 1,
         }
     }
 
@@ -129,6 +202,9 @@ impl FieldMemberSchema {
             repeated: false,
             ordered: true,
             allow_has_operator: true,
+            nullable: false,
+            enum_schema: None,
+            weight: 1,
         }
     }
 
@@ -139,8 +215,132 @@ impl FieldMemberSchema {
             repeated: true,
             ordered: false,
             allow_has_operator: true,
+            nullable: false,
+            enum_schema: None,
+            weight: 1,
+        }
+    }
+
+    /// Creates a new nullable field member schema.
+    pub const fn new_nullable(value_type: ValueType) -> Self {
+        Self {
+            value_type,
+            repeated: false,
+            ordered: false,
+            allow_has_operator: true,
+            nullable: true,
+            enum_schema: None,
+            weight: 1,
+        }
+    }
+
+    /// Creates a new enum field member schema.
+    pub const fn new_enum(enum_schema: EnumSchema) -> Self {
+        Self {
+            value_type: ValueType::Enum,
+            repeated: false,
+            ordered: false,
+            allow_has_operator: true,
+            nullable: false,
+            enum_schema: Some(enum_schema),
+            weight: 1,
+        }
+    }
+}
+
+impl ResourceMemberSchema {
+    /// Creates a new resource member schema.
+    pub const fn new(fields: BTreeMap<String, MemberSchema>) -> Self {
+        Self {
+            fields,
+            repeated: false,
+        }
+    }
+
+    /// Creates a new repeated resource member schema.
+    pub const fn new_repeated(fields: BTreeMap<String, MemberSchema>) -> Self {
+        Self {
+            fields,
+            repeated: true,
+        }
+    }
+}
+
+impl MapMemberSchema {
+    /// Creates a new map member schema.
+    ///
+    /// Values are nullable, since entries may be absent.
+    pub fn new(key_type: ValueType, value_type: ValueType) -> Self {
+        Self {
+            key_type,
+            value: Box::new(FieldMemberSchema::new_nullable(value_type).into()),
+        }
+    }
+}
+
+impl EnumSchema {
+    /// Creates an enum schema from symbolic names.
+    pub fn new<I, N>(names: I) -> Self
+    where
+        I: IntoIterator<Item = N>,
+        N: Into<String>,
+    {
+        Self {
+            values: names.into_iter().map(|name| (name.into(), None)).collect(),
+        }
+    }
+
+    /// Creates an enum schema from symbolic names and their numeric values.
+    pub fn new_numbered<I, N>(values: I) -> Self
+    where
+        I: IntoIterator<Item = (N, i64)>,
+        N: Into<String>,
+    {
+        Self {
+            values: values
+                .into_iter()
+                .map(|(name, number)| (name.into(), Some(number)))
+                .collect(),
+        }
+    }
+
+    /// Checks if the enum has a value with symbolic name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Gets the numeric value of a symbolic name.
+    pub fn get_number(&self, name: &str) -> Option<i64> {
+        self.values.get(name).copied().flatten()
+    }
+
+    /// Gets the symbolic name of a numeric value.
+    pub fn get_name(&self, number: i64) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(_, value)| **value == Some(number))
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+impl ValueType {
+    /// Gets the type that values of both types are compared as, if they are comparable.
+    ///
+    /// Numeric types are promoted, such that comparing `Integer` and `Float` values compares them as `Float`.
+    pub fn promote(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Integer, Self::Float) | (Self::Float, Self::Integer) => Some(Self::Float),
+            _ if self == other => Some(self),
+            _ => None,
         }
     }
+
+    /// Checks if literals of this type can be converted to the other type.
+    ///
+    /// Besides numeric promotion, string literals can be converted to timestamps, such as `"2024-01-01T00:00:00Z"`.
+    pub fn is_literal_coercible(self, other: Self) -> bool {
+        self.promote(other).is_some() || (self == Self::String && other == Self::Timestamp)
+    }
 }
 
 impl From<FieldMemberSchema> for MemberSchema {
@@ -155,6 +355,12 @@ impl From<ResourceMemberSchema> for MemberSchema {
     }
 }
 
+impl From<MapMemberSchema> for MemberSchema {
+    fn from(map: MapMemberSchema) -> Self {
+        Self::Map(map)
+    }
+}
+
 impl Display for ValueType {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{self:?}")
@@ -180,5 +386,68 @@ mod tests {
         ));
         assert!(schema.get_field("user.id").unwrap().ordered);
         assert!(schema.get_field("task.tags").unwrap().repeated);
+
+        let schema = Schema {
+            members: BTreeMap::from([(
+                "labels".into(),
+                MapMemberSchema::new(ValueType::String, ValueType::String).into(),
+            )]),
+        };
+        assert!(matches!(
+            schema.get_member("labels"),
+            Some(MemberSchema::Map(_))
+        ));
+        assert!(schema.get_field("labels.env").unwrap().nullable);
+        assert!(schema.get_member("labels.env.x").is_none());
+        assert_eq!(
+            schema
+                .split_map_entry("labels.env")
+                .map(|(name, key, _)| (name, key)),
+            Some(("labels", "env"))
+        );
+        assert!(schema.split_map_entry("labels").is_none());
+
+        let schema = Schema {
+            members: BTreeMap::from([(
+                "book".into(),
+                ResourceMemberSchema::new(BTreeMap::from([(
+                    "authors".into(),
+                    ResourceMemberSchema::new_repeated(BTreeMap::from([(
+                        "country".into(),
+                        FieldMemberSchema::new(ValueType::String).into(),
+                    )]))
+                    .into(),
+                )]))
+                .into(),
+            )]),
+        };
+        assert_eq!(
+            schema.get_field("book.authors.country").unwrap().value_type,
+            ValueType::String
+        );
+        assert_eq!(
+            schema.split_repeated_resource("book.authors.country"),
+            Some(("book.authors", "country"))
+        );
+        assert!(schema.split_repeated_resource("book.authors").is_none());
+    }
+
+    #[test]
+    fn promote() {
+        assert_eq!(
+            ValueType::Integer.promote(ValueType::Float),
+            Some(ValueType::Float)
+        );
+        assert_eq!(
+            ValueType::Float.promote(ValueType::Integer),
+            Some(ValueType::Float)
+        );
+        assert_eq!(
+            ValueType::String.promote(ValueType::String),
+            Some(ValueType::String)
+        );
+        assert_eq!(ValueType::Integer.promote(ValueType::String), None);
+        assert!(ValueType::String.is_literal_coercible(ValueType::Timestamp));
+        assert!(!ValueType::Timestamp.is_literal_coercible(ValueType::String));
     }
 }
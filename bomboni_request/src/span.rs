@@ -0,0 +1,105 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Location of a part of the source string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// Start byte offset.
+    pub start: usize,
+    /// End byte offset, exclusive.
+    pub end: usize,
+    /// Line number of the start, starting at 1.
+    pub line: usize,
+    /// Column of the start in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// Creates a span between byte offsets in the source string.
+    ///
+    /// Offsets are clamped to the source length.
+    pub fn locate(source: &str, start: usize, end: usize) -> Self {
+        let start = floor_char_boundary(source, start);
+        let end = floor_char_boundary(source, end).max(start);
+        let before = &source[..start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            start,
+            end,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Renders the source line containing the span, with carets under the spanned part followed by the message.
+    ///
+    /// ```text
+    /// a = 1 AND lol = 2
+    ///           ^^^ unknown filter member `lol`
+    /// ```
+    pub fn render<M: Display>(&self, source: &str, message: M) -> String {
+        let line_start = floor_char_boundary(source, self.start);
+        let line_start = source[..line_start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[line_start..]
+            .find('\n')
+            .map_or(source.len(), |i| line_start + i);
+        let line = &source[line_start..line_end];
+
+        let underlined = source
+            .get(self.start.min(line_end)..self.end.clamp(self.start.min(line_end), line_end))
+            .map_or(0, |part| part.chars().count())
+            .max(1);
+
+        format!(
+            "{line}\n{}{} {message}",
+            " ".repeat(self.column.saturating_sub(1)),
+            "^".repeat(underlined)
+        )
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.line > 1 {
+            write!(f, "line {}, column {}", self.line, self.column)
+        } else {
+            write!(f, "column {}", self.column)
+        }
+    }
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let source = "a = 1 AND lol = 2";
+        let span = Span::locate(source, 10, 13);
+        assert_eq!(span.column, 11);
+        assert_eq!(span.to_string(), "column 11");
+        assert_eq!(
+            span.render(source, "unknown member"),
+            "a = 1 AND lol = 2\n          ^^^ unknown member"
+        );
+
+        let source = "a = 1\nAND ü = 2";
+        let span = Span::locate(source, 10, 12);
+        assert_eq!((span.line, span.column), (2, 5));
+        assert_eq!(span.to_string(), "line 2, column 5");
+        assert_eq!(span.render(source, "x"), "AND ü = 2\n    ^ x");
+
+        let span = Span::locate(source, 100, 100);
+        assert_eq!((span.line, span.column), (2, 10));
+        assert_eq!(span.render(source, "x"), "AND ü = 2\n         ^ x");
+
+        assert_eq!(Span::default().render("a", "x"), "a\n^ x");
+    }
+}
@@ -1,12 +1,21 @@
+use std::fmt::Write;
+
 use crate::{
     filter::{
         Filter, FilterComparator,
         error::{FilterError, FilterResult},
+        split_wildcard,
+        visit::FilterVisitor,
     },
-    schema::{FunctionSchemaMap, Schema, ValueType},
+    schema::{EnumSchema, FunctionSchema, FunctionSchemaMap, MemberSchema, Schema, ValueType},
     sql::{
-        SqlArgumentStyle, SqlDialect, SqlRenameMap,
-        utility::{get_argument_parameter, get_identifier},
+        SqlArgumentStyle, SqlDialect, SqlRenameMap, SqlRepeatedResource, SqlRepeatedResourceMap,
+        SqliteTimestampFormat,
+        utility::{
+            escape_glob_pattern, escape_like_pattern, get_argument_parameter,
+            get_default_argument_style, get_identifier, get_map_entry, get_map_key_argument,
+            get_map_key_presence, get_sqlite_value,
+        },
     },
     value::Value,
 };
@@ -18,23 +27,45 @@ pub struct SqlFilterBuilder<'a> {
     schema: &'a Schema,
     schema_functions: Option<&'a FunctionSchemaMap>,
     rename_map: Option<&'a SqlRenameMap>,
+    repeated_resources: Option<&'a SqlRepeatedResourceMap>,
     argument_offset: usize,
     case_insensitive_like: bool,
+    sqlite_timestamp_format: SqliteTimestampFormat,
+    /// Enum of the comparable, while building a restriction.
+    enum_schema: Option<&'a EnumSchema>,
+    /// Type of the comparable, while building a restriction.
+    comparable_type: Option<ValueType>,
+    /// Element of a repeated resource, while building an `EXISTS` subquery.
+    repeated_element: Option<RepeatedElement>,
     arguments: Vec<Value>,
     result: String,
 }
 
+struct RepeatedElement {
+    /// Name of the repeated resource.
+    resource_name: String,
+    /// Expression of the element.
+    identifier: String,
+    /// Whether the element is a JSON value, rather than a table row.
+    json: bool,
+}
+
 impl<'a> SqlFilterBuilder<'a> {
     /// Creates a new SQL filter builder.
     pub fn new(dialect: SqlDialect, schema: &'a Schema) -> Self {
         Self {
             dialect,
-            argument_style: SqlArgumentStyle::Indexed { prefix: "$".into() },
+            argument_style: get_default_argument_style(dialect),
             schema,
             schema_functions: None,
             rename_map: None,
+            repeated_resources: None,
             argument_offset: 0,
             case_insensitive_like: false,
+            sqlite_timestamp_format: SqliteTimestampFormat::Text,
+            enum_schema: None,
+            comparable_type: None,
+            repeated_element: None,
             arguments: Vec::new(),
             result: String::new(),
         }
@@ -67,6 +98,26 @@ impl<'a> SqlFilterBuilder<'a> {
         self
     }
 
+    /// Sets representation of timestamps in `SQLite`.
+    pub const fn set_sqlite_timestamp_format(
+        &mut self,
+        timestamp_format: SqliteTimestampFormat,
+    ) -> &mut Self {
+        self.sqlite_timestamp_format = timestamp_format;
+        self
+    }
+
+    /// Sets storage of repeated resources.
+    ///
+    /// Repeated resources without storage are stored in JSON array columns.
+    pub const fn set_repeated_resources(
+        &mut self,
+        repeated_resources: &'a SqlRepeatedResourceMap,
+    ) -> &mut Self {
+        self.repeated_resources = Some(repeated_resources);
+        self
+    }
+
     /// Sets the argument style.
     pub fn set_argument_style(&mut self, argument_style: SqlArgumentStyle) -> &mut Self {
         self.argument_style = argument_style;
@@ -82,8 +133,10 @@ impl<'a> SqlFilterBuilder<'a> {
     /// Will return [`FilterError::UnknownMember`] if the filter contains an unknown field name.
     /// Will return [`FilterError::IncomparableType`] if trying to compare incomparable types.
     /// Will return [`FilterError::UnsuitableComparator`] if an unsuitable comparator is used.
+    /// Will return [`FilterError::PrecisionLoss`] if a numeric literal cannot be converted to the compared type exactly.
+    /// Will return [`FilterError::InvalidTimestamp`] if a string literal compared with a timestamp is not an RFC 3339 timestamp.
     pub fn build(&mut self, filter: &Filter) -> FilterResult<(String, Vec<Value>)> {
-        self.build_tree(filter)?;
+        self.visit_filter(filter)?;
 
         let result = self.result.clone();
         self.result.clear();
@@ -93,73 +146,19 @@ impl<'a> SqlFilterBuilder<'a> {
         Ok((result, arguments))
     }
 
-    fn build_tree(&mut self, tree: &Filter) -> FilterResult<()> {
-        match tree {
-            Filter::Conjunction(parts) => {
-                for (i, part) in parts.iter().enumerate() {
-                    let part_type =
-                        part.get_result_value_type(self.schema, self.schema_functions)?;
-                    if part_type != ValueType::Boolean {
-                        return Err(FilterError::InvalidType {
-                            actual: part_type,
-                            expected: ValueType::Boolean,
-                        });
-                    }
-
-                    self.build_tree(part)?;
-                    if i < parts.len() - 1 {
-                        self.result.push_str(" AND ");
-                    }
-                }
-            }
-            Filter::Disjunction(parts) => {
-                for (i, part) in parts.iter().enumerate() {
-                    let part_type =
-                        part.get_result_value_type(self.schema, self.schema_functions)?;
-                    if part_type != ValueType::Boolean {
-                        return Err(FilterError::InvalidType {
-                            actual: part_type,
-                            expected: ValueType::Boolean,
-                        });
-                    }
-
-                    self.build_tree(part)?;
-                    if i < parts.len() - 1 {
-                        self.result.push_str(" OR ");
-                    }
-                }
-            }
-            Filter::Negate(tree) => {
-                self.build_negate(tree)?;
-            }
-            Filter::Restriction(comparable, comparator, arg) => {
-                self.build_restriction(comparable, *comparator, arg)?;
-            }
-            Filter::Function(name, args) => {
-                self.build_function(name, args)?;
-            }
-            Filter::Composite(tree) => {
-                self.result.push('(');
-                self.build_tree(tree)?;
-                self.result.push(')');
-            }
-            Filter::Name(name) => {
-                if self.schema.get_member(name).is_none() {
-                    return Err(FilterError::UnknownMember(name.clone()));
-                }
-                if let Some(rename_map) = self.rename_map {
-                    self.result.push_str(&get_identifier(
-                        self.dialect,
-                        &rename_map.rename_member(name),
-                        true,
-                    ));
-                } else {
-                    self.result
-                        .push_str(&get_identifier(self.dialect, name, true));
-                }
+    fn build_connective(&mut self, parts: &[Filter], operator: &str) -> FilterResult<()> {
+        for (i, part) in parts.iter().enumerate() {
+            let part_type = part.get_result_value_type(self.schema, self.schema_functions)?;
+            if part_type != ValueType::Boolean {
+                return Err(FilterError::InvalidType {
+                    actual: part_type,
+                    expected: ValueType::Boolean,
+                });
             }
-            Filter::Value(value) => {
-                self.build_argument(value.clone());
+
+            self.visit_filter(part)?;
+            if i < parts.len() - 1 {
+                self.result.push_str(operator);
             }
         }
         Ok(())
@@ -175,7 +174,7 @@ impl<'a> SqlFilterBuilder<'a> {
         }
 
         self.result.push_str("NOT (");
-        self.build_tree(tree)?;
+        self.visit_filter(tree)?;
         self.result.push(')');
 
         Ok(())
@@ -187,151 +186,95 @@ impl<'a> SqlFilterBuilder<'a> {
         comparator: FilterComparator,
         argument: &Filter,
     ) -> FilterResult<()> {
+        if self.repeated_element.is_none()
+            && let Filter::Name(name) = comparable
+            && let Some((resource_name, _)) = self.schema.split_repeated_resource(name)
+        {
+            return self.build_exists(resource_name, comparable, comparator, argument);
+        }
+
+        if let Filter::Name(name) = comparable
+            && let Some(MemberSchema::Map(_)) = self.schema.get_member(name)
+        {
+            if comparator != FilterComparator::Has {
+                return Err(FilterError::UnsuitableComparator(comparator));
+            }
+            return self.build_map_key_presence(name, argument);
+        }
+
         let comparable_type =
             comparable.get_result_value_type(self.schema, self.schema_functions)?;
         let argument_type = argument.get_result_value_type(self.schema, self.schema_functions)?;
 
-        // let mut composite = argument;
-        // if let Filter::Composite(comp) = composite {
-        //     composite = comp;
-        // }
+        if let Some((parts, disjunction)) = argument.get_composite_argument() {
+            return self.build_composite_restriction(
+                comparable,
+                comparator,
+                parts,
+                disjunction,
+                comparable_type,
+                argument_type,
+            );
+        }
+
+        if matches!(argument, Filter::Value(Value::Null)) {
+            self.visit_filter(comparable)?;
+            match comparator {
+                FilterComparator::Equal => self.result.push_str(" IS NULL"),
+                FilterComparator::NotEqual => self.result.push_str(" IS NOT NULL"),
+                _ => return Err(FilterError::UnsuitableComparator(comparator)),
+            }
+            return Ok(());
+        }
 
         if comparator == FilterComparator::Has {
-            // TODO: decide how to generate SQL
-            return Err(FilterError::UnsuitableComparator(comparator));
-            //     match composite {
-            //         Filter::Conjunction(parts) => {
-            //             self.result.push('(');
-            //             for (i, part) in parts.iter().enumerate() {
-            //                 self.build_restriction(comparable, FilterComparator::Equal, part)?;
-            //                 if i < parts.len() - 1 {
-            //                     self.result.push_str(" AND ");
-            //                 }
-            //             }
-            //             self.result.push(')');
-            //         }
-            //         Filter::Disjunction(parts) => {
-            //             self.build_tree(comparable)?;
-            //             self.result.push_str(" = ANY(");
-
-            //             let mut values: Vec<Value> = Vec::new();
-            //             for part in parts {
-            //                 if let Filter::Value(value) = part {
-            //                     let value_type =
-            //                         value.value_type().ok_or(FilterError::ExpectedValue)?;
-            //                     if let Some(first_value) = values.first() {
-            //                         if let Some(first_value_type) = first_value.value_type() {
-            //                             if first_value_type != value_type {
-            //                                 return Err(FilterError::InvalidType {
-            //                                     actual: value_type,
-            //                                     expected: first_value_type,
-            //                                 });
-            //                             }
-            //                         } else {
-            //                             return Err(FilterError::InvalidResultValueType);
-            //                         }
-            //                     }
-            //                     values.push(value.clone());
-            //                 } else {
-            //                     return Err(FilterError::ExpectedValue);
-            //                 }
-            //             }
-            //             self.build_argument(Value::Repeated(values));
-
-            //             self.result.push(')');
-            //         }
-            //         _ => {
-            //             if comparable_type != argument_type && argument_type != ValueType::Any {
-            //                 return Err(FilterError::InvalidType {
-            //                     actual: argument_type,
-            //                     expected: comparable_type,
-            //                 });
-            //             }
-
-            //             if self.case_insensitive_like && matches!(&argument_type, ValueType::String) {
-            //                 self.result.push_str("LOWER(");
-            //                 self.build_tree(comparable)?;
-            //                 self.result.push(')');
-            //             } else {
-            //                 self.build_tree(comparable)?;
-            //             }
-
-            //             match argument_type {
-            //                 ValueType::Integer
-            //                 | ValueType::Float
-            //                 | ValueType::Boolean
-            //                 | ValueType::Timestamp => {
-            //                     self.result.push_str(" = ");
-            //                     self.build_tree(composite)?;
-            //                 }
-            //                 ValueType::String => {
-            //                     self.result.push_str(" LIKE ");
-            //                     if self.case_insensitive_like {
-            //                         self.result.push_str("LOWER(");
-            //                     }
-            //                     if let Filter::Value(value) = composite {
-            //                         self.result.push_str(&format!(
-            //                             "${}",
-            //                             self.arguments.len() + 1 + self.argument_offset
-            //                         ));
-            //                         match value {
-            //                             Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => {
-            //                                 self.arguments.push(format!("%{value}%").into());
-            //                             }
-            //                             Value::String(value) => {
-            //                                 self.arguments.push(format!("%{value}%").into());
-            //                             }
-            //                             Value::Timestamp(value) => {
-            //                                 self.arguments.push(format!("%{value}%").into());
-            //                             }
-            //                             _ => unreachable!(),
-            //                         }
-            //                     } else {
-            //                         unreachable!()
-            //                     }
-            //                     if self.case_insensitive_like {
-            //                         self.result.push(')');
-            //                     }
-            //                 }
-            //                 ValueType::Any => {
-            //                     self.result.push_str(" IS NOT NULL");
-            //                 }
-            //             }
-            //         }
-            //     }
-
-            //     return Ok(());
+            return self.build_has(comparable, comparable_type, argument, argument_type);
         }
 
-        if comparable_type != argument_type {
+        if !argument.is_comparable_argument(argument_type, comparable_type) {
             return Err(FilterError::InvalidType {
                 actual: argument_type,
                 expected: comparable_type,
             });
         }
 
-        self.build_tree(comparable)?;
+        if let Filter::Value(Value::String(value)) = argument
+            && matches!(
+                comparator,
+                FilterComparator::Equal | FilterComparator::NotEqual
+            )
+            && let Some((leading, literal, trailing)) = split_wildcard(value)
+        {
+            let pattern = self.get_pattern(leading, literal, trailing);
+            return self.build_like(
+                comparable,
+                pattern,
+                comparator == FilterComparator::NotEqual,
+            );
+        }
+
+        self.visit_filter(comparable)?;
         match comparator {
             FilterComparator::Less => {
-                if argument_type == ValueType::Boolean {
+                if matches!(argument_type, ValueType::Boolean | ValueType::Enum) {
                     return Err(FilterError::IncomparableType(argument_type));
                 }
                 self.result.push_str(" < ");
             }
             FilterComparator::LessOrEqual => {
-                if argument_type == ValueType::Boolean {
+                if matches!(argument_type, ValueType::Boolean | ValueType::Enum) {
                     return Err(FilterError::IncomparableType(argument_type));
                 }
                 self.result.push_str(" <= ");
             }
             FilterComparator::Greater => {
-                if argument_type == ValueType::Boolean {
+                if matches!(argument_type, ValueType::Boolean | ValueType::Enum) {
                     return Err(FilterError::IncomparableType(argument_type));
                 }
                 self.result.push_str(" > ");
             }
             FilterComparator::GreaterOrEqual => {
-                if argument_type == ValueType::Boolean {
+                if matches!(argument_type, ValueType::Boolean | ValueType::Enum) {
                     return Err(FilterError::IncomparableType(argument_type));
                 }
                 self.result.push_str(" >= ");
@@ -344,11 +287,309 @@ impl<'a> SqlFilterBuilder<'a> {
             }
             FilterComparator::Has => unreachable!(),
         }
-        self.build_tree(argument)?;
+        self.visit_filter(argument)?;
+
+        Ok(())
+    }
+
+    fn build_composite_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        parts: &[Filter],
+        disjunction: bool,
+        comparable_type: ValueType,
+        argument_type: ValueType,
+    ) -> FilterResult<()> {
+        let repeated = if let Filter::Name(name) = comparable {
+            self.schema
+                .get_field(name)
+                .is_some_and(|field| field.repeated)
+        } else {
+            false
+        };
+        let list_operator = match (comparator, disjunction) {
+            (FilterComparator::Equal, true) => Some(" IN ("),
+            (FilterComparator::NotEqual, false) => Some(" NOT IN ("),
+            _ => None,
+        };
+
+        if let Some(list_operator) = list_operator
+            && !repeated
+            && parts.iter().all(|part| match part {
+                // Wildcard patterns are matched with `LIKE`
+                Filter::Value(Value::String(value)) => split_wildcard(value).is_none(),
+                Filter::Value(value) => !matches!(value, Value::Any | Value::Null),
+                _ => false,
+            })
+        {
+            if !argument_type.is_literal_coercible(comparable_type) {
+                return Err(FilterError::InvalidType {
+                    actual: argument_type,
+                    expected: comparable_type,
+                });
+            }
+
+            self.visit_filter(comparable)?;
+            self.result.push_str(list_operator);
+            for (i, part) in parts.iter().enumerate() {
+                self.visit_filter(part)?;
+                if i < parts.len() - 1 {
+                    self.result.push_str(", ");
+                }
+            }
+            self.result.push(')');
+            return Ok(());
+        }
+
+        // Expand into separate restrictions, such that `a:(x OR y)` is `a:x OR a:y`
+        self.result.push('(');
+        for (i, part) in parts.iter().enumerate() {
+            self.build_restriction(comparable, comparator, part)?;
+            if i < parts.len() - 1 {
+                self.result
+                    .push_str(if disjunction { " OR " } else { " AND " });
+            }
+        }
+        self.result.push(')');
 
         Ok(())
     }
 
+    fn build_has(
+        &mut self,
+        comparable: &Filter,
+        comparable_type: ValueType,
+        argument: &Filter,
+        argument_type: ValueType,
+    ) -> FilterResult<()> {
+        let field = if let Filter::Name(name) = comparable {
+            self.schema.get_field(name)
+        } else {
+            None
+        };
+        if field.is_some_and(|field| !field.allow_has_operator) {
+            return Err(FilterError::UnsuitableComparator(FilterComparator::Has));
+        }
+
+        // Presence check
+        if matches!(argument, Filter::Value(Value::Any)) {
+            self.visit_filter(comparable)?;
+            self.result.push_str(" IS NOT NULL");
+            return Ok(());
+        }
+
+        if !argument.is_comparable_argument(argument_type, comparable_type) {
+            return Err(FilterError::InvalidType {
+                actual: argument_type,
+                expected: comparable_type,
+            });
+        }
+
+        if field.is_some_and(|field| field.repeated) {
+            match self.dialect {
+                SqlDialect::Postgres => {
+                    self.visit_filter(argument)?;
+                    self.result.push_str(" = ANY(");
+                    self.visit_filter(comparable)?;
+                    self.result.push(')');
+                }
+                SqlDialect::MySql => {
+                    self.result.push_str("JSON_CONTAINS(");
+                    self.visit_filter(comparable)?;
+                    self.result.push_str(", JSON_ARRAY(");
+                    self.visit_filter(argument)?;
+                    self.result.push_str("))");
+                }
+                SqlDialect::Sqlite => {
+                    self.visit_filter(argument)?;
+                    self.result.push_str(" IN (SELECT value FROM json_each(");
+                    self.visit_filter(comparable)?;
+                    self.result.push_str("))");
+                }
+            }
+            return Ok(());
+        }
+
+        if let Filter::Value(Value::String(value)) = argument {
+            let pattern = self.get_pattern(true, value, true);
+            self.build_like(comparable, pattern, false)?;
+        } else {
+            self.visit_filter(comparable)?;
+            self.result.push_str(" = ");
+            self.visit_filter(argument)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds a restriction on fields of a repeated resource, matching if any element matches.
+    fn build_exists(
+        &mut self,
+        resource_name: &str,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<()> {
+        let storage = self
+            .repeated_resources
+            .and_then(|repeated_resources| repeated_resources.get(resource_name))
+            .cloned()
+            .unwrap_or_default();
+        let repeated_element = match storage {
+            SqlRepeatedResource::JsonArray => {
+                let column = self.get_member_identifier(resource_name);
+                let alias = get_identifier(self.dialect, "element", true);
+                match self.dialect {
+                    SqlDialect::Postgres => write!(
+                        self.result,
+                        "EXISTS (SELECT 1 FROM jsonb_array_elements({column}) AS {alias} WHERE "
+                    ),
+                    SqlDialect::MySql => write!(
+                        self.result,
+                        "EXISTS (SELECT 1 FROM JSON_TABLE({column}, '$[*]' COLUMNS (`value` JSON PATH '$')) AS {alias} WHERE "
+                    ),
+                    SqlDialect::Sqlite => write!(
+                        self.result,
+                        "EXISTS (SELECT 1 FROM json_each({column}) AS {alias} WHERE "
+                    ),
+                }
+                .unwrap();
+                RepeatedElement {
+                    resource_name: resource_name.into(),
+                    identifier: get_identifier(self.dialect, "element.value", true),
+                    json: true,
+                }
+            }
+            SqlRepeatedResource::Table {
+                table,
+                foreign_key,
+                parent_key,
+            } => {
+                let table = get_identifier(self.dialect, &table, true);
+                write!(
+                    self.result,
+                    "EXISTS (SELECT 1 FROM {table} WHERE {table}.{} = {} AND ",
+                    get_identifier(self.dialect, &foreign_key, true),
+                    get_identifier(self.dialect, &parent_key, true),
+                )
+                .unwrap();
+                RepeatedElement {
+                    resource_name: resource_name.into(),
+                    identifier: table,
+                    json: false,
+                }
+            }
+        };
+
+        self.repeated_element = Some(repeated_element);
+        let result = self.build_restriction(comparable, comparator, argument);
+        self.repeated_element = None;
+        result?;
+        self.result.push(')');
+        Ok(())
+    }
+
+    fn build_map_key_presence(&mut self, name: &str, key: &Filter) -> FilterResult<()> {
+        if let Some((parts, disjunction)) = key.get_composite_argument() {
+            self.result.push('(');
+            for (i, part) in parts.iter().enumerate() {
+                self.build_map_key_presence(name, part)?;
+                if i < parts.len() - 1 {
+                    self.result
+                        .push_str(if disjunction { " OR " } else { " AND " });
+                }
+            }
+            self.result.push(')');
+            return Ok(());
+        }
+
+        let identifier = self.get_member_identifier(name);
+        match key {
+            // Presence of the map itself
+            Filter::Value(Value::Any) => {
+                self.result.push_str(&identifier);
+                self.result.push_str(" IS NOT NULL");
+            }
+            Filter::Name(key) | Filter::Value(Value::String(key)) => {
+                let key_parameter = self.bind_map_key(key)?;
+                self.result.push_str(&get_map_key_presence(
+                    self.dialect,
+                    &identifier,
+                    &key_parameter,
+                ));
+            }
+            Filter::Value(key) => {
+                let key_parameter = self.bind_map_key(&key.to_string())?;
+                self.result.push_str(&get_map_key_presence(
+                    self.dialect,
+                    &identifier,
+                    &key_parameter,
+                ));
+            }
+            _ => return Err(FilterError::InvalidResultValueType),
+        }
+        Ok(())
+    }
+
+    fn get_member_identifier(&self, name: &str) -> String {
+        let name = self
+            .rename_map
+            .map_or_else(|| name.into(), |rename_map| rename_map.rename_member(name));
+        get_identifier(self.dialect, &name, true)
+    }
+
+    /// Gets a pattern matching strings that contain the literal, optionally preceded or followed by anything.
+    fn get_pattern(&self, leading: bool, literal: &str, trailing: bool) -> String {
+        let (wildcard, literal) = if self.is_glob() {
+            ("*", escape_glob_pattern(literal))
+        } else {
+            ("%", escape_like_pattern(literal))
+        };
+        format!(
+            "{}{literal}{}",
+            if leading { wildcard } else { "" },
+            if trailing { wildcard } else { "" },
+        )
+    }
+
+    /// Checks if patterns are matched with `GLOB`, since `LIKE` is case insensitive in `SQLite`.
+    fn is_glob(&self) -> bool {
+        self.dialect == SqlDialect::Sqlite && !self.case_insensitive_like
+    }
+
+    fn build_like(
+        &mut self,
+        comparable: &Filter,
+        pattern: String,
+        negate: bool,
+    ) -> FilterResult<()> {
+        let operator = match (self.is_glob(), negate) {
+            (true, false) => " GLOB ",
+            (true, true) => " NOT GLOB ",
+            (false, false) => " LIKE ",
+            (false, true) => " NOT LIKE ",
+        };
+        if self.case_insensitive_like {
+            self.result.push_str("LOWER(");
+            self.visit_filter(comparable)?;
+            self.result.push(')');
+            self.result.push_str(operator);
+            self.result.push_str("LOWER(");
+            self.build_argument(pattern.into())?;
+            self.result.push(')');
+        } else {
+            self.visit_filter(comparable)?;
+            self.result.push_str(operator);
+            self.build_argument(pattern.into())?;
+        }
+        // `SQLite` has no default escape character
+        if self.dialect == SqlDialect::Sqlite && !self.is_glob() {
+            self.result.push_str(r" ESCAPE '\'");
+        }
+        Ok(())
+    }
+
     fn build_function(&mut self, name: &str, arguments: &[Filter]) -> FilterResult<()> {
         let function = self
             .schema_functions
@@ -367,18 +608,30 @@ impl<'a> SqlFilterBuilder<'a> {
                 .push_str(&get_identifier(self.dialect, name, false));
         }
 
+        // Arguments are not compared with the comparable of an enclosing restriction
+        let (enum_schema, comparable_type) = (self.enum_schema.take(), self.comparable_type.take());
+        let result = self.build_function_arguments(function, arguments);
+        (self.enum_schema, self.comparable_type) = (enum_schema, comparable_type);
+        result
+    }
+
+    fn build_function_arguments(
+        &mut self,
+        function: &FunctionSchema,
+        arguments: &[Filter],
+    ) -> FilterResult<()> {
         self.result.push('(');
         for (i, arg) in arguments.iter().enumerate() {
             let expected_type = function.argument_value_types[i];
             let arg_type = arg.get_result_value_type(self.schema, self.schema_functions)?;
-            if arg_type != expected_type {
+            if expected_type != ValueType::Any && arg_type != expected_type {
                 return Err(FilterError::InvalidType {
                     actual: arg_type,
                     expected: expected_type,
                 });
             }
 
-            self.build_tree(arg)?;
+            self.visit_filter(arg)?;
             if i < arguments.len() - 1 {
                 self.result.push_str(", ");
             }
@@ -388,12 +641,141 @@ impl<'a> SqlFilterBuilder<'a> {
         Ok(())
     }
 
-    fn build_argument(&mut self, value: Value) {
-        self.result.push_str(&get_argument_parameter(
+    fn build_argument(&mut self, value: Value) -> FilterResult<()> {
+        let parameter = self.bind_argument(value)?;
+        self.result.push_str(&parameter);
+        Ok(())
+    }
+
+    /// Binds an argument, returning its parameter.
+    fn bind_argument(&mut self, value: Value) -> FilterResult<String> {
+        let parameter = get_argument_parameter(
             &self.argument_style,
             self.arguments.len() + 1 + self.argument_offset,
-        ));
-        self.arguments.push(value);
+        );
+        self.arguments.push(if self.dialect == SqlDialect::Sqlite {
+            get_sqlite_value(value, self.sqlite_timestamp_format)?
+        } else {
+            value
+        });
+        Ok(parameter)
+    }
+
+    /// Binds a key of a map entry, returning its parameter.
+    fn bind_map_key(&mut self, key: &str) -> FilterResult<String> {
+        self.bind_argument(get_map_key_argument(self.dialect, key))
+    }
+}
+
+impl FilterVisitor for SqlFilterBuilder<'_> {
+    type Error = FilterError;
+
+    fn visit_conjunction(&mut self, parts: &[Filter]) -> FilterResult<()> {
+        self.build_connective(parts, " AND ")
+    }
+
+    fn visit_disjunction(&mut self, parts: &[Filter]) -> FilterResult<()> {
+        self.build_connective(parts, " OR ")
+    }
+
+    fn visit_negate(&mut self, tree: &Filter) -> FilterResult<()> {
+        self.build_negate(tree)
+    }
+
+    fn visit_restriction(
+        &mut self,
+        comparable: &Filter,
+        comparator: FilterComparator,
+        argument: &Filter,
+    ) -> FilterResult<()> {
+        let (enum_schema, comparable_type) = (self.enum_schema, self.comparable_type);
+        if let Filter::Name(name) = comparable {
+            self.enum_schema = self
+                .schema
+                .get_field(name)
+                .and_then(|field| field.enum_schema.as_ref());
+        }
+        self.comparable_type = comparable
+            .get_result_value_type(self.schema, self.schema_functions)
+            .ok();
+        let result = self.build_restriction(comparable, comparator, argument);
+        (self.enum_schema, self.comparable_type) = (enum_schema, comparable_type);
+        result
+    }
+
+    fn visit_function(&mut self, name: &str, arguments: &[Filter]) -> FilterResult<()> {
+        self.build_function(name, arguments)
+    }
+
+    fn visit_composite(&mut self, tree: &Filter) -> FilterResult<()> {
+        self.result.push('(');
+        self.visit_filter(tree)?;
+        self.result.push(')');
+        Ok(())
+    }
+
+    fn visit_name(&mut self, name: &str) -> FilterResult<()> {
+        let Some(member) = self.schema.get_member(name) else {
+            return Err(FilterError::UnknownMember(name.into()));
+        };
+        let value_type = match member {
+            MemberSchema::Field(field) => field.value_type,
+            _ => ValueType::Any,
+        };
+        if let Some(repeated_element) = &self.repeated_element
+            && let Some(field_name) = name
+                .strip_prefix(repeated_element.resource_name.as_str())
+                .and_then(|field_name| field_name.strip_prefix('.'))
+        {
+            let field = if repeated_element.json {
+                let identifier = repeated_element.identifier.clone();
+                let key_parameter = self.bind_map_key(field_name)?;
+                get_map_entry(self.dialect, &identifier, &key_parameter, value_type)
+            } else {
+                format!(
+                    "{}.{}",
+                    repeated_element.identifier,
+                    get_identifier(self.dialect, field_name, true)
+                )
+            };
+            self.result.push_str(&field);
+        } else if let Some((map_name, key, _)) = self.schema.split_map_entry(name) {
+            // Map entries are looked up in JSON columns
+            let identifier = self.get_member_identifier(map_name);
+            let key_parameter = self.bind_map_key(key)?;
+            self.result.push_str(&get_map_entry(
+                self.dialect,
+                &identifier,
+                &key_parameter,
+                value_type,
+            ));
+        } else {
+            let identifier = self.get_member_identifier(name);
+            self.result.push_str(&identifier);
+        }
+        Ok(())
+    }
+
+    fn visit_value(&mut self, value: &Value) -> FilterResult<()> {
+        // Enums are bound by their numeric value, if any
+        let value = match value {
+            Value::Enum(name) => self
+                .enum_schema
+                .and_then(|enum_schema| enum_schema.get_number(name))
+                .map_or_else(|| Value::String(name.clone()), Value::Integer),
+            // Literals are converted to the type of the comparable
+            value => match self.comparable_type {
+                Some(value_type)
+                    if value.value_type().is_some_and(|literal_type| {
+                        literal_type != value_type && literal_type.is_literal_coercible(value_type)
+                    }) =>
+                {
+                    value.coerce_literal(value_type)?
+                }
+                _ => value.clone(),
+            },
+        };
+        self.build_argument(value)
     }
 }
 
@@ -401,7 +783,7 @@ impl<'a> SqlFilterBuilder<'a> {
 mod tests {
     use std::collections::BTreeMap;
 
-    use crate::{schema::FunctionSchema, testing::schema::RequestItem};
+    use crate::testing::schema::{RequestItem, UserItem};
     use bomboni_macros::btree_map_into;
 
     use super::*;
@@ -452,6 +834,18 @@ mod tests {
         assert_eq!(sql, r#"REGEX("user"."displayName", $1)"#);
         assert_eq!(args[0], Value::String("a".into()));
 
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(
+                &Filter::parse(r#"NOT task.deleted AND task.deleted = true OR user.age >= 30"#)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"NOT ("task"."deleted") AND "task"."deleted" = ?1 OR "user"."age" >= ?2"#
+        );
+        assert_eq!(args, vec![Value::Integer(1), Value::Integer(30)]);
+
         assert!(
             SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
                 .build(&Filter::parse("logs").unwrap())
@@ -473,4 +867,556 @@ mod tests {
                 .is_err()
         );
     }
+
+    #[test]
+    fn has() {
+        let schema = RequestItem::get_schema();
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse(r#"task.tags:"a" AND task.content:"50%_off""#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"$1 = ANY("task"."tags") AND "task"."content" LIKE $2"#
+        );
+        assert_eq!(args, vec!["a".into(), r"%50\%\_off%".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::MySql, &schema)
+            .set_argument_style(SqlArgumentStyle::Positional { symbol: "?".into() })
+            .case_insensitive_like()
+            .build(&Filter::parse(r#"task.tags:"a" AND task.content:"b""#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            "JSON_CONTAINS(`task`.`tags`, JSON_ARRAY(?)) AND LOWER(`task`.`content`) LIKE LOWER(?)"
+        );
+        assert_eq!(args, vec!["a".into(), "%b%".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(&Filter::parse(r#"task.tags:"a" AND task.content:"50%_off*""#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"?1 IN (SELECT value FROM json_each("task"."tags")) AND "task"."content" GLOB ?2"#
+        );
+        assert_eq!(args, vec!["a".into(), "*50%_off[*]*".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse("task.content:* AND user.age:30").unwrap())
+            .unwrap();
+        assert_eq!(sql, r#""task"."content" IS NOT NULL AND "user"."age" = $1"#);
+        assert_eq!(args, vec![30.into()]);
+
+        assert!(matches!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse("task.tags:42").unwrap())
+                .unwrap_err(),
+            FilterError::InvalidType { .. }
+        ));
+        assert!(matches!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &UserItem::get_schema())
+                .build(&Filter::parse(r#"id:"42""#).unwrap())
+                .unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Has)
+        ));
+    }
+
+    #[test]
+    fn composite_arguments() {
+        let schema = RequestItem::get_schema();
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse("user.age = (30 OR 31) AND user.age != (1 AND 2)").unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#""user"."age" IN ($1, $2) AND "user"."age" NOT IN ($3, $4)"#
+        );
+        assert_eq!(args, vec![30.into(), 31.into(), 1.into(), 2.into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse(r#"task.tags:("a" OR "b") AND user.age > (18 30)"#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"($1 = ANY("task"."tags") OR $2 = ANY("task"."tags")) AND ("user"."age" > $3 AND "user"."age" > $4)"#
+        );
+        assert_eq!(args, vec!["a".into(), "b".into(), 18.into(), 30.into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::MySql, &schema)
+            .set_argument_style(SqlArgumentStyle::Positional { symbol: "?".into() })
+            .build(&Filter::parse(r#"task.content:("a" "b") AND user.id = ("1" OR "2")"#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            "(`task`.`content` LIKE ? AND `task`.`content` LIKE ?) AND `user`.`id` IN (?, ?)"
+        );
+        assert_eq!(
+            args,
+            vec!["%a%".into(), "%b%".into(), "1".into(), "2".into()]
+        );
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(
+                &Filter::parse(r#"task.tags:("a" OR "b") AND user.id != ("1" AND "2")"#).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"(?1 IN (SELECT value FROM json_each("task"."tags")) OR ?2 IN (SELECT value FROM json_each("task"."tags"))) AND "user"."id" NOT IN (?3, ?4)"#
+        );
+        assert_eq!(args, vec!["a".into(), "b".into(), "1".into(), "2".into()]);
+
+        assert!(matches!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse(r#"user.age = (30 OR "31")"#).unwrap())
+                .unwrap_err(),
+            FilterError::InvalidType { .. }
+        ));
+        assert!(matches!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse(r#"user.id = (30 OR 31)"#).unwrap())
+                .unwrap_err(),
+            FilterError::InvalidType { .. }
+        ));
+    }
+
+    #[test]
+    fn wildcards() {
+        let schema = RequestItem::get_schema();
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(
+                &Filter::parse(
+                    r#"user.displayName = "*Tolkien" AND user.id = "978*" AND task.content != "*100%*""#,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#""user"."displayName" LIKE $1 AND "user"."id" LIKE $2 AND "task"."content" NOT LIKE $3"#
+        );
+        assert_eq!(
+            args,
+            vec!["%Tolkien".into(), "978%".into(), r"%100\%%".into()]
+        );
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::MySql, &schema)
+            .set_argument_style(SqlArgumentStyle::Positional { symbol: "?".into() })
+            .case_insensitive_like()
+            .build(&Filter::parse(r#"user.displayName = "j_r*" AND user.id = "42""#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            "LOWER(`user`.`displayName`) LIKE LOWER(?) AND `user`.`id` = ?"
+        );
+        assert_eq!(args, vec![r"j\_r%".into(), "42".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(
+                &Filter::parse(r#"user.displayName = "*Tolk?en" AND task.content != "[1]*""#)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#""user"."displayName" GLOB ?1 AND "task"."content" NOT GLOB ?2"#
+        );
+        assert_eq!(args, vec!["*Tolk[?]en".into(), "[[]1]*".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .case_insensitive_like()
+            .build(&Filter::parse(r#"user.displayName = "j_r*""#).unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"LOWER("user"."displayName") LIKE LOWER(?1) ESCAPE '\'"#
+        );
+        assert_eq!(args, vec![r"j\_r%".into()]);
+
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(
+                &Filter::parse(r#"user.id = ("a*" OR "b") AND user.displayName != ("*x" AND "y")"#)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#"("user"."id" LIKE $1 OR "user"."id" = $2) AND ("user"."displayName" NOT LIKE $3 AND "user"."displayName" != $4)"#
+        );
+        assert_eq!(args, vec!["a%".into(), "b".into(), "%x".into(), "y".into()]);
+    }
+
+    #[test]
+    fn enums() {
+        use crate::schema::{EnumSchema, FieldMemberSchema};
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "state" => FieldMemberSchema::new_enum(
+                    EnumSchema::new_numbered([("DRAFT", 1), ("PUBLISHED", 2)]),
+                ),
+                "genre" => FieldMemberSchema::new_enum(EnumSchema::new(["FICTION", "POETRY"])),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr, $expected_sql:expr, $expected_args:expr) => {{
+                let filter = Filter::parse($filter).unwrap().resolve(&schema);
+                let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                    .build(&filter)
+                    .unwrap();
+                assert_eq!(sql, $expected_sql);
+                assert_eq!(args, $expected_args);
+            }};
+        }
+
+        check!(
+            "state = PUBLISHED AND genre != POETRY",
+            r#""state" = $1 AND "genre" != $2"#,
+            vec![Value::Integer(2), Value::String("POETRY".into())]
+        );
+        check!(
+            r#"state = (DRAFT OR "PUBLISHED")"#,
+            r#""state" IN ($1, $2)"#,
+            vec![Value::Integer(1), Value::Integer(2)]
+        );
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse("state < DRAFT").unwrap().resolve(&schema))
+                .unwrap_err(),
+            FilterError::IncomparableType(ValueType::Enum)
+        );
+    }
+
+    #[test]
+    fn nulls() {
+        use crate::schema::FieldMemberSchema;
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "deleteTime" => FieldMemberSchema::new_nullable(ValueType::Timestamp),
+                "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+            },
+        };
+        macro_rules! check {
+            ($filter:expr, $expected_sql:expr) => {{
+                let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                    .build(&Filter::parse($filter).unwrap())
+                    .unwrap();
+                assert_eq!(sql, $expected_sql);
+                assert!(args.is_empty());
+            }};
+        }
+
+        check!("deleteTime:*", r#""deleteTime" IS NOT NULL"#);
+        check!("NOT deleteTime:*", r#"NOT ("deleteTime" IS NOT NULL)"#);
+        check!("deleteTime = null", r#""deleteTime" IS NULL"#);
+        check!("deleteTime != null", r#""deleteTime" IS NOT NULL"#);
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse("age > null").unwrap())
+                .unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Greater)
+        );
+    }
+
+    #[test]
+    fn maps() {
+        use crate::schema::MapMemberSchema;
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "labels" => MapMemberSchema::new(ValueType::String, ValueType::String),
+                "counts" => MapMemberSchema::new(ValueType::String, ValueType::Integer),
+            },
+        };
+        macro_rules! check {
+            ($dialect:expr, $filter:expr, $expected_sql:expr, $expected_args:expr) => {{
+                let (sql, args) = SqlFilterBuilder::new($dialect, &schema)
+                    .build(&Filter::parse($filter).unwrap())
+                    .unwrap();
+                assert_eq!(sql, $expected_sql);
+                assert_eq!(args, $expected_args);
+            }};
+        }
+
+        check!(
+            SqlDialect::Postgres,
+            r#"labels.env = "prod" AND counts.hits > 10"#,
+            r#""labels"->>$1::TEXT = $2 AND ("counts"->>$3::TEXT)::BIGINT > $4"#,
+            vec![
+                "env".into(),
+                "prod".into(),
+                "hits".into(),
+                Value::Integer(10)
+            ]
+        );
+        check!(
+            SqlDialect::Postgres,
+            r#"labels:env AND NOT labels:("a" OR "b")"#,
+            r#""labels"->$1::TEXT IS NOT NULL AND NOT (("labels"->$2::TEXT IS NOT NULL OR "labels"->$3::TEXT IS NOT NULL))"#,
+            vec![Value::from("env"), "a".into(), "b".into()]
+        );
+        check!(
+            SqlDialect::MySql,
+            r#"labels.env = "prod" AND counts.hits > 10"#,
+            "JSON_UNQUOTE(JSON_EXTRACT(`labels`, $1)) = $2 AND JSON_EXTRACT(`counts`, $3) > $4",
+            vec![
+                r#"$."env""#.into(),
+                "prod".into(),
+                r#"$."hits""#.into(),
+                Value::Integer(10)
+            ]
+        );
+        check!(
+            SqlDialect::MySql,
+            "labels:env AND labels:*",
+            "JSON_CONTAINS_PATH(`labels`, 'one', $1) AND `labels` IS NOT NULL",
+            vec![Value::from(r#"$."env""#)]
+        );
+        check!(
+            SqlDialect::Sqlite,
+            r#"labels.env = "prod" AND counts.hits > 10 AND labels:team"#,
+            r#"json_extract("labels", ?1) = ?2 AND json_extract("counts", ?3) > ?4 AND json_type("labels", ?5) IS NOT NULL"#,
+            vec![
+                r#"$."env""#.into(),
+                "prod".into(),
+                r#"$."hits""#.into(),
+                Value::Integer(10),
+                r#"$."team""#.into()
+            ]
+        );
+        // Keys are bound, rather than quoted in the statement
+        check!(
+            SqlDialect::MySql,
+            r#"labels:"a') OR 1=1 OR ('\"b""#,
+            "JSON_CONTAINS_PATH(`labels`, 'one', $1)",
+            vec![Value::from(r#"$."a') OR 1=1 OR ('\\\"b""#)]
+        );
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse("labels = 1").unwrap())
+                .unwrap_err(),
+            FilterError::UnsuitableComparator(FilterComparator::Equal)
+        );
+    }
+
+    #[test]
+    fn repeated_resources() {
+        use crate::{
+            schema::{FieldMemberSchema, ResourceMemberSchema},
+            sql::SqlRepeatedResource,
+        };
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "title" => FieldMemberSchema::new(ValueType::String),
+                "authors" => ResourceMemberSchema::new_repeated(btree_map_into! {
+                    "country" => FieldMemberSchema::new(ValueType::String),
+                    "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
+                }),
+            },
+        };
+        let tables = btree_map_into! {
+            "authors" => SqlRepeatedResource::Table {
+                table: "book_authors".into(),
+                foreign_key: "book_id".into(),
+                parent_key: "books.id".into(),
+            },
+        };
+        macro_rules! check {
+            ($dialect:expr, $repeated_resources:expr, $filter:expr, $expected_sql:expr) => {{
+                let (sql, _) = SqlFilterBuilder::new($dialect, &schema)
+                    .set_repeated_resources($repeated_resources)
+                    .build(&Filter::parse($filter).unwrap())
+                    .unwrap();
+                assert_eq!(sql, $expected_sql);
+            }};
+        }
+
+        check!(
+            SqlDialect::Postgres,
+            &BTreeMap::new(),
+            r#"title = "a" AND authors.country = ("SI" OR "HR")"#,
+            r#""title" = $1 AND EXISTS (SELECT 1 FROM jsonb_array_elements("authors") AS "element" WHERE "element"."value"->>$2::TEXT IN ($3, $4))"#
+        );
+        check!(
+            SqlDialect::MySql,
+            &BTreeMap::new(),
+            "NOT authors.age > 30",
+            r#"NOT (EXISTS (SELECT 1 FROM JSON_TABLE(`authors`, '$[*]' COLUMNS (`value` JSON PATH '$')) AS `element` WHERE JSON_EXTRACT(`element`.`value`, $1) > $2))"#
+        );
+        check!(
+            SqlDialect::Sqlite,
+            &BTreeMap::new(),
+            r#"authors.country = "SI""#,
+            r#"EXISTS (SELECT 1 FROM json_each("authors") AS "element" WHERE json_extract("element"."value", ?1) = ?2)"#
+        );
+        check!(
+            SqlDialect::Sqlite,
+            &tables,
+            "authors.age > 30",
+            r#"EXISTS (SELECT 1 FROM "book_authors" WHERE "book_authors"."book_id" = "books"."id" AND "book_authors"."age" > ?1)"#
+        );
+        check!(
+            SqlDialect::Postgres,
+            &tables,
+            r#"authors.country = "SI" OR authors.age > 30"#,
+            r#"EXISTS (SELECT 1 FROM "book_authors" WHERE "book_authors"."book_id" = "books"."id" AND "book_authors"."country" = $1) OR EXISTS (SELECT 1 FROM "book_authors" WHERE "book_authors"."book_id" = "books"."id" AND "book_authors"."age" > $2)"#
+        );
+    }
+
+    #[test]
+    fn repeated_fields() {
+        let schema = RequestItem::get_schema();
+        macro_rules! check {
+            ($dialect:expr, $filter:expr, $expected_sql:expr) => {{
+                let (sql, _) = SqlFilterBuilder::new($dialect, &schema)
+                    .build(&Filter::parse($filter).unwrap())
+                    .unwrap();
+                assert_eq!(sql, $expected_sql);
+            }};
+        }
+
+        // Repeated fields are compared as columns, only repeated resources match any element
+        check!(
+            SqlDialect::Postgres,
+            r#"task.tags = "a" AND task.tags != "b""#,
+            r#""task"."tags" = $1 AND "task"."tags" != $2"#
+        );
+        check!(
+            SqlDialect::Postgres,
+            r#"task.tags = ("a" AND "b*")"#,
+            r#"("task"."tags" = $1 AND "task"."tags" LIKE $2)"#
+        );
+        check!(
+            SqlDialect::MySql,
+            r#"task.tags > "a""#,
+            r#"`task`.`tags` > $1"#
+        );
+        check!(
+            SqlDialect::Sqlite,
+            r#"NOT task.tags = "a""#,
+            r#"NOT ("task"."tags" = ?1)"#
+        );
+        check!(
+            SqlDialect::Sqlite,
+            r#"task.tags:"a" AND task.tags = null"#,
+            r#"?1 IN (SELECT value FROM json_each("task"."tags")) AND "task"."tags" IS NULL"#
+        );
+    }
+
+    #[test]
+    fn numeric_coercion() {
+        use crate::schema::FieldMemberSchema;
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "price" => FieldMemberSchema::new_ordered(ValueType::Float),
+                "stock" => FieldMemberSchema::new_ordered(ValueType::Integer),
+            },
+        };
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse("price > 10 AND stock = (1 OR 2.0) AND price < stock").unwrap())
+            .unwrap();
+        assert_eq!(
+            sql,
+            r#""price" > $1 AND "stock" IN ($2, $3) AND "price" < "stock""#
+        );
+        assert_eq!(
+            args,
+            vec![Value::Float(10.0), Value::Integer(1), Value::Integer(2)]
+        );
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse("stock > 2.5").unwrap())
+                .unwrap_err(),
+            FilterError::PrecisionLoss {
+                value: "2.5".into(),
+                expected: ValueType::Integer,
+            }
+        );
+
+        // Function arguments are bound in the types of function parameters
+        let schema_functions = btree_map_into! {
+            "round" => FunctionSchema {
+                argument_value_types: vec![ValueType::Float],
+                return_value_type: ValueType::Integer,
+            },
+        };
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .set_schema_functions(&schema_functions)
+            .build(&Filter::parse("round(2.5) = stock AND round(2.0) = 2").unwrap())
+            .unwrap();
+        assert_eq!(sql, r#"round($1) = "stock" AND round($2) = $3"#);
+        assert_eq!(
+            args,
+            vec![Value::Float(2.5), Value::Float(2.0), Value::Integer(2)]
+        );
+    }
+    #[test]
+    fn timestamps() {
+        use bomboni_common::date_time::UtcDateTime;
+
+        use crate::schema::FieldMemberSchema;
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "createTime" => FieldMemberSchema::new_ordered(ValueType::Timestamp),
+            },
+        };
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+            .build(&Filter::parse(r#"createTime >= "2024-01-01T00:00:00Z""#).unwrap())
+            .unwrap();
+        assert_eq!(sql, r#""createTime" >= $1"#);
+        assert_eq!(
+            args,
+            vec![Value::Timestamp(
+                UtcDateTime::parse_rfc3339("2024-01-01T00:00:00Z").unwrap()
+            )]
+        );
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&Filter::parse(r#"createTime < "2024-01-01""#).unwrap())
+                .unwrap_err(),
+            FilterError::InvalidTimestamp("2024-01-01".into())
+        );
+
+        let filter = Filter::parse(r#"createTime >= "2024-01-01T00:00:00.5Z""#).unwrap();
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(&filter)
+            .unwrap();
+        assert_eq!(sql, r#""createTime" >= ?1"#);
+        assert_eq!(args, vec![Value::String("2024-01-01T00:00:00.5Z".into())]);
+        let (_, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .set_sqlite_timestamp_format(SqliteTimestampFormat::UnixSeconds)
+            .build(&filter)
+            .unwrap();
+        assert_eq!(args, vec![Value::Integer(1_704_067_200)]);
+    }
+
+    #[test]
+    fn durations() {
+        use bomboni_proto::google::protobuf::Duration;
+
+        use crate::schema::FieldMemberSchema;
+
+        let schema = Schema {
+            members: btree_map_into! {
+                "timeout" => FieldMemberSchema::new_ordered(ValueType::Duration),
+            },
+        };
+        let (sql, args) = SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+            .build(&Filter::parse("timeout < 1.5s").unwrap())
+            .unwrap();
+        assert_eq!(sql, r#""timeout" < ?1"#);
+        assert_eq!(args, vec![Value::Integer(1_500_000_000)]);
+        assert_eq!(
+            SqlFilterBuilder::new(SqlDialect::Sqlite, &schema)
+                .build(&Filter::parse("timeout < 3000000h").unwrap())
+                .unwrap_err(),
+            FilterError::InvalidDuration(Duration::new(10_800_000_000, 0).to_string())
+        );
+    }
 }
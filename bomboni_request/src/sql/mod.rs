@@ -17,6 +17,21 @@ pub enum SqlDialect {
     Postgres,
     /// `MySQL` dialect.
     MySql,
+    /// `SQLite` dialect.
+    ///
+    /// Arguments are bound as `SQLite` storage classes, such that booleans and durations are integers,
+    /// and timestamps are represented according to [`SqliteTimestampFormat`].
+    Sqlite,
+}
+
+/// Representation of timestamps in `SQLite`, which has no timestamp type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SqliteTimestampFormat {
+    /// RFC 3339 text, such as `2024-01-01T00:00:00Z`.
+    #[default]
+    Text,
+    /// Seconds since the Unix epoch, truncating fractional seconds.
+    UnixSeconds,
 }
 
 /// SQL argument style.
@@ -43,6 +58,26 @@ pub struct SqlRenameMap {
     pub functions: BTreeMap<String, String>,
 }
 
+/// Storage of a repeated nested resource, whose elements are matched with `EXISTS` subqueries.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SqlRepeatedResource {
+    /// Elements of a JSON array column, named after the resource.
+    #[default]
+    JsonArray,
+    /// Rows of a child table.
+    Table {
+        /// Child table name.
+        table: String,
+        /// Column of the child table that references the parent row.
+        foreign_key: String,
+        /// Column of the parent row, such as `books.id`.
+        parent_key: String,
+    },
+}
+
+/// Map of repeated resource storage by member name.
+pub type SqlRepeatedResourceMap = BTreeMap<String, SqlRepeatedResource>;
+
 impl SqlRenameMap {
     /// Creates a new SQL rename map.
     pub const fn new(
@@ -44,16 +44,21 @@ impl<'a> SqlOrderingBuilder<'a> {
                 return Err(OrderingError::UnknownMember(term.name.clone()));
             }
 
-            if let Some(rename_map) = self.rename_map {
-                self.result.push_str(&get_identifier(
-                    self.dialect,
-                    &rename_map.rename_function(&term.name),
-                    true,
-                ));
+            let identifier = if let Some(rename_map) = self.rename_map {
+                get_identifier(self.dialect, &rename_map.rename_function(&term.name), true)
             } else {
-                self.result
-                    .push_str(&get_identifier(self.dialect, &term.name, true));
+                get_identifier(self.dialect, &term.name, true)
+            };
+            // Nulls are ordered last in both directions, as in `Ordering::evaluate`
+            let nullable = self
+                .schema
+                .get_field(&term.name)
+                .is_some_and(|field| field.nullable);
+            if nullable && self.dialect == SqlDialect::MySql {
+                self.result.push_str(&identifier);
+                self.result.push_str(" IS NULL, ");
             }
+            self.result.push_str(&identifier);
 
             self.result.push(' ');
 
@@ -66,6 +71,10 @@ impl<'a> SqlOrderingBuilder<'a> {
                 }
             }
 
+            if nullable && self.dialect != SqlDialect::MySql {
+                self.result.push_str(" NULLS LAST");
+            }
+
             if i < ordering.len() - 1 {
                 self.result.push_str(", ");
             }
@@ -79,7 +88,12 @@ impl<'a> SqlOrderingBuilder<'a> {
 
 #[cfg(test)]
 mod tests {
-    use crate::testing::schema::RequestItem;
+    use bomboni_macros::btree_map_into;
+
+    use crate::{
+        schema::{FieldMemberSchema, ValueType},
+        testing::schema::RequestItem,
+    };
 
     use super::*;
 
@@ -93,5 +107,50 @@ mod tests {
                 .unwrap(),
             r#""user"."age" DESC, "user"."displayName" ASC"#
         );
+        assert_eq!(
+            SqlOrderingBuilder::new(SqlDialect::MySql, &schema)
+                .build(&Ordering::parse("user.age desc").unwrap())
+                .unwrap(),
+            "`user`.`age` DESC"
+        );
+        assert_eq!(
+            SqlOrderingBuilder::new(SqlDialect::Sqlite, &schema)
+                .build(&Ordering::parse("user.age desc, user.displayName").unwrap())
+                .unwrap(),
+            r#""user"."age" DESC, "user"."displayName" ASC"#
+        );
+    }
+
+    #[test]
+    fn nulls() {
+        let schema = Schema {
+            members: btree_map_into! {
+                "id" => FieldMemberSchema::new_ordered(ValueType::String),
+                "priority" => FieldMemberSchema {
+                    ordered: true,
+                    ..FieldMemberSchema::new_nullable(ValueType::Integer)
+                },
+            },
+        };
+        let ordering = Ordering::parse("priority desc, id").unwrap();
+
+        assert_eq!(
+            SqlOrderingBuilder::new(SqlDialect::Postgres, &schema)
+                .build(&ordering)
+                .unwrap(),
+            r#""priority" DESC NULLS LAST, "id" ASC"#
+        );
+        assert_eq!(
+            SqlOrderingBuilder::new(SqlDialect::MySql, &schema)
+                .build(&ordering)
+                .unwrap(),
+            "`priority` IS NULL, `priority` DESC, `id` ASC"
+        );
+        assert_eq!(
+            SqlOrderingBuilder::new(SqlDialect::Sqlite, &schema)
+                .build(&ordering)
+                .unwrap(),
+            r#""priority" DESC NULLS LAST, "id" ASC"#
+        );
     }
 }
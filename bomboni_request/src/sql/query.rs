@@ -4,7 +4,8 @@ use crate::query::{error::QueryResult, list::ListQuery, search::SearchQuery};
 use crate::schema::{FunctionSchemaMap, Schema};
 use crate::sql::{
     SqlArgumentStyle, SqlDialect, SqlFilterBuilder, SqlOrderingBuilder, SqlRenameMap,
-    utility::get_argument_parameter,
+    SqlRepeatedResourceMap, SqliteTimestampFormat,
+    utility::{get_argument_parameter, get_default_argument_style},
 };
 use crate::value::Value;
 
@@ -16,7 +17,9 @@ pub struct QuerySqlBuilder {
     schema: Schema,
     schema_functions: FunctionSchemaMap,
     rename_map: SqlRenameMap,
+    repeated_resources: SqlRepeatedResourceMap,
     case_insensitive_like: bool,
+    sqlite_timestamp_format: SqliteTimestampFormat,
     query_next_page: bool,
 }
 
@@ -42,11 +45,13 @@ impl QuerySqlBuilder {
     pub fn new(dialect: SqlDialect, schema: Schema) -> Self {
         Self {
             dialect,
-            argument_style: SqlArgumentStyle::Indexed { prefix: "$".into() },
+            argument_style: get_default_argument_style(dialect),
             schema,
             schema_functions: FunctionSchemaMap::new(),
             rename_map: SqlRenameMap::default(),
+            repeated_resources: SqlRepeatedResourceMap::new(),
             case_insensitive_like: false,
+            sqlite_timestamp_format: SqliteTimestampFormat::Text,
             query_next_page: false,
         }
     }
@@ -63,12 +68,30 @@ impl QuerySqlBuilder {
         self
     }
 
+    /// Sets storage of repeated resources.
+    pub fn set_repeated_resources(
+        &mut self,
+        repeated_resources: SqlRepeatedResourceMap,
+    ) -> &mut Self {
+        self.repeated_resources = repeated_resources;
+        self
+    }
+
     /// Enables case insensitive like.
     pub const fn case_insensitive_like(&mut self) -> &mut Self {
         self.case_insensitive_like = true;
         self
     }
 
+    /// Sets representation of timestamps in `SQLite`.
+    pub const fn set_sqlite_timestamp_format(
+        &mut self,
+        timestamp_format: SqliteTimestampFormat,
+    ) -> &mut Self {
+        self.sqlite_timestamp_format = timestamp_format;
+        self
+    }
+
     /// Enables query next page.
     pub const fn query_next_page(&mut self) -> &mut Self {
         self.query_next_page = true;
@@ -137,6 +160,8 @@ impl QuerySqlBuilder {
             filter_builder
                 .set_schema_functions(&self.schema_functions)
                 .set_rename_map(&self.rename_map)
+                .set_repeated_resources(&self.repeated_resources)
+                .set_sqlite_timestamp_format(self.sqlite_timestamp_format)
                 .set_argument_style(self.argument_style.clone());
             if self.case_insensitive_like {
                 filter_builder.case_insensitive_like();
@@ -147,15 +172,22 @@ impl QuerySqlBuilder {
 
         let (paged_where_clause, mut paged_arguments) =
             if let Some(page_token) = page_token.filter(|page_token| !page_token.is_empty()) {
+                // Page filter is sandboxed in parentheses, such that it can only narrow down the filter
+                let page_token = Filter::Composite(Box::new(page_token.clone()));
                 let filter = if filter.is_empty() {
-                    page_token.clone()
+                    page_token
                 } else {
-                    Filter::Conjunction(vec![filter.clone(), page_token.clone()])
+                    Filter::Conjunction(vec![
+                        Filter::Composite(Box::new(filter.clone())),
+                        page_token,
+                    ])
                 };
                 let mut filter_builder = SqlFilterBuilder::new(self.dialect, &self.schema);
                 filter_builder
                     .set_schema_functions(&self.schema_functions)
                     .set_rename_map(&self.rename_map)
+                    .set_repeated_resources(&self.repeated_resources)
+                    .set_sqlite_timestamp_format(self.sqlite_timestamp_format)
                     .set_argument_style(self.argument_style.clone());
                 if self.case_insensitive_like {
                     filter_builder.case_insensitive_like();
@@ -266,10 +298,33 @@ mod tests {
         assert_eq!(s.arguments.len(), 1);
         assert_eq!(
             &s.paged_where_clause.unwrap(),
-            r#"NOT ("task"."deleted") AND "user"."id" = $1 AND "task"."id" < $2"#,
+            r#"(NOT ("task"."deleted") AND "user"."id" = $1) AND ("task"."id" < $2)"#,
         );
         assert_eq!(s.paged_limit_clause, "LIMIT $3");
         assert_eq!(s.paged_arguments.len(), 3);
         assert_eq!(s.order_by_clause.unwrap(), r#""task"."id" DESC"#);
+
+        let mut builder = QuerySqlBuilder::new(SqlDialect::Sqlite, RequestItem::get_schema());
+        builder.query_next_page();
+        let s = builder
+            .build_list(&ListQuery {
+                filter: Filter::parse(r#"NOT task.deleted AND task.tags:"a""#).unwrap(),
+                ordering: Ordering::parse("task.id desc").unwrap(),
+                page_size: 5,
+                page_token: Some(FilterPageToken::new(
+                    Filter::parse(r#"task.id < "10""#).unwrap(),
+                )),
+            })
+            .unwrap();
+        assert_eq!(
+            &s.paged_where_clause.unwrap(),
+            r#"(NOT ("task"."deleted") AND ?1 IN (SELECT value FROM json_each("task"."tags"))) AND ("task"."id" < ?2)"#,
+        );
+        assert_eq!(s.paged_limit_clause, "LIMIT ?3");
+        assert_eq!(
+            s.paged_arguments,
+            vec!["a".into(), "10".into(), Value::Integer(6)]
+        );
+        assert_eq!(s.order_by_clause.unwrap(), r#""task"."id" DESC"#);
     }
 }
@@ -1,9 +1,14 @@
-use super::{SqlArgumentStyle, SqlDialect};
+use super::{SqlArgumentStyle, SqlDialect, SqliteTimestampFormat};
+use crate::{
+    filter::error::FilterResult,
+    schema::ValueType,
+    value::{Value, duration_nanos_i64},
+};
 
 pub fn get_identifier(dialect: SqlDialect, name: &str, escape: bool) -> String {
     use std::fmt::Write;
     match dialect {
-        SqlDialect::Postgres => {
+        SqlDialect::Postgres | SqlDialect::Sqlite => {
             if escape {
                 let mut parts = name.split('.');
                 let mut result = String::new();
@@ -38,6 +43,16 @@ pub fn get_identifier(dialect: SqlDialect, name: &str, escape: bool) -> String {
     }
 }
 
+/// Gets the argument style of a dialect, used unless set explicitly.
+pub fn get_default_argument_style(dialect: SqlDialect) -> SqlArgumentStyle {
+    match dialect {
+        SqlDialect::Postgres | SqlDialect::MySql => {
+            SqlArgumentStyle::Indexed { prefix: "$".into() }
+        }
+        SqlDialect::Sqlite => SqlArgumentStyle::Indexed { prefix: "?".into() },
+    }
+}
+
 pub fn get_argument_parameter(style: &SqlArgumentStyle, argument: usize) -> String {
     match style {
         SqlArgumentStyle::Indexed { prefix } => {
@@ -46,3 +61,111 @@ pub fn get_argument_parameter(style: &SqlArgumentStyle, argument: usize) -> Stri
         SqlArgumentStyle::Positional { symbol } => symbol.clone(),
     }
 }
+
+/// Escapes `LIKE` pattern wildcards, using backslash as the escape character.
+pub fn escape_like_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes `GLOB` pattern wildcards, using character classes.
+pub fn escape_glob_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '*' | '?' | '[') {
+            escaped.push('[');
+            escaped.push(c);
+            escaped.push(']');
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Gets the argument that selects a map key in JSON functions of a dialect.
+///
+/// Keys are looked up by name in `PostgreSQL`, and by JSON path in `MySQL` and `SQLite`.
+/// Keys are user input, and are bound as arguments rather than quoted in the statement.
+pub fn get_map_key_argument(dialect: SqlDialect, key: &str) -> Value {
+    match dialect {
+        SqlDialect::Postgres => key.into(),
+        SqlDialect::MySql | SqlDialect::Sqlite => {
+            format!("$.\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\"")).into()
+        }
+    }
+}
+
+/// Gets an expression for the value of a map entry, stored in a JSON column.
+///
+/// The key is selected by a parameter bound to [`get_map_key_argument`].
+pub fn get_map_entry(
+    dialect: SqlDialect,
+    identifier: &str,
+    key_parameter: &str,
+    value_type: ValueType,
+) -> String {
+    match dialect {
+        SqlDialect::Postgres => {
+            let entry = format!("{identifier}->>{key_parameter}::TEXT");
+            match value_type {
+                ValueType::Integer => format!("({entry})::BIGINT"),
+                ValueType::Float => format!("({entry})::DOUBLE PRECISION"),
+                ValueType::Boolean => format!("({entry})::BOOLEAN"),
+                _ => entry,
+            }
+        }
+        SqlDialect::MySql => {
+            let entry = format!("JSON_EXTRACT({identifier}, {key_parameter})");
+            if matches!(
+                value_type,
+                ValueType::Integer | ValueType::Float | ValueType::Boolean
+            ) {
+                entry
+            } else {
+                format!("JSON_UNQUOTE({entry})")
+            }
+        }
+        // Values are extracted as SQL values, with booleans as integers
+        SqlDialect::Sqlite => format!("json_extract({identifier}, {key_parameter})"),
+    }
+}
+
+/// Gets a condition that a map, stored in a JSON column, contains a key.
+///
+/// The key is selected by a parameter bound to [`get_map_key_argument`].
+pub fn get_map_key_presence(dialect: SqlDialect, identifier: &str, key_parameter: &str) -> String {
+    match dialect {
+        SqlDialect::Postgres => format!("{identifier}->{key_parameter}::TEXT IS NOT NULL"),
+        SqlDialect::MySql => format!("JSON_CONTAINS_PATH({identifier}, 'one', {key_parameter})"),
+        SqlDialect::Sqlite => format!("json_type({identifier}, {key_parameter}) IS NOT NULL"),
+    }
+}
+
+/// Converts an argument to a `SQLite` storage class.
+///
+/// # Errors
+///
+/// Will return [`crate::filter::error::FilterError::InvalidDuration`] if a duration does not fit
+/// in 64-bit nanoseconds.
+pub fn get_sqlite_value(
+    value: Value,
+    timestamp_format: SqliteTimestampFormat,
+) -> FilterResult<Value> {
+    Ok(match value {
+        Value::Boolean(value) => Value::Integer(value.into()),
+        Value::Timestamp(value) => match timestamp_format {
+            SqliteTimestampFormat::Text => Value::String(value.format_rfc3339().unwrap()),
+            SqliteTimestampFormat::UnixSeconds => Value::Integer(value.timestamp().0),
+        },
+        // Durations are stored as nanoseconds
+        Value::Duration(value) => Value::Integer(duration_nanos_i64(&value)?),
+        value => value,
+    })
+}
@@ -48,6 +48,7 @@ impl RequestItem {
                         "displayName" => FieldMemberSchema::new_ordered(ValueType::String),
                         "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
                     },
+                    repeated: false,
                 }),
                 "task" => MemberSchema::Resource(ResourceMemberSchema {
                     fields: btree_map_into!{
@@ -57,6 +58,7 @@ impl RequestItem {
                         "deleted" => FieldMemberSchema::new(ValueType::Boolean),
                         "tags" => FieldMemberSchema::new_repeated(ValueType::String),
                     },
+                    repeated: false,
                 }),
             },
         }
@@ -84,6 +86,9 @@ impl UserItem {
                     repeated: false,
                     ordered: true,
                     allow_has_operator: false,
+                    nullable: false,
+                    enum_schema: None,
+                    weight: 1,
                 },
                 "displayName" => FieldMemberSchema::new_ordered(ValueType::String),
                 "age" => FieldMemberSchema::new_ordered(ValueType::Integer),
@@ -1,10 +1,13 @@
 use pest::iterators::Pair;
 use std::{
     cmp::Ordering,
+    collections::BTreeMap,
     fmt::{self, Display, Formatter, Write},
 };
 
 use bomboni_common::date_time::UtcDateTime;
+use bomboni_proto::google::protobuf::Duration;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     filter::{
@@ -15,7 +18,11 @@ use crate::{
 };
 
 /// Query value.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Values are serialized as objects tagged by type, such as `{"type": "integer", "value": 42}`.
+/// Timestamps are serialized as RFC 3339 strings, and durations as strings such as `"1.5s"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
 pub enum Value {
     /// Integer value.
     Integer(i64),
@@ -27,10 +34,18 @@ pub enum Value {
     String(String),
     /// Timestamp value.
     Timestamp(UtcDateTime),
+    /// Duration value.
+    Duration(Duration),
+    /// Enum value, by symbolic name.
+    Enum(String),
     /// Repeated value.
     Repeated(Vec<Self>),
+    /// Map value, keyed by string representation of keys.
+    Map(BTreeMap<String, Self>),
     /// Any value.
     Any,
+    /// Absent value of a nullable field.
+    Null,
 }
 
 impl Value {
@@ -42,11 +57,62 @@ impl Value {
             Self::Boolean(_) => Some(ValueType::Boolean),
             Self::String(_) => Some(ValueType::String),
             Self::Timestamp(_) => Some(ValueType::Timestamp),
-            Self::Repeated(_) => None,
-            Self::Any => Some(ValueType::Any),
+            Self::Duration(_) => Some(ValueType::Duration),
+            Self::Enum(_) => Some(ValueType::Enum),
+            Self::Repeated(_) | Self::Map(_) => None,
+            Self::Any | Self::Null => Some(ValueType::Any),
         }
     }
 
+    /// Converts the value to a value type without losing precision.
+    ///
+    /// `Integer` and `Float` values are converted to each other only if the conversion is exact.
+    /// `String` values are converted to `Timestamp` values if they are RFC 3339 timestamps.
+    /// Values of other types are converted only to their own type.
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn coerce(&self, value_type: ValueType) -> Option<Self> {
+        match (self, value_type) {
+            (Self::Integer(value), ValueType::Float) => (value.unsigned_abs()
+                <= MAX_EXACT_FLOAT_INTEGER)
+                .then_some(Self::Float(*value as f64)),
+            (Self::Float(value), ValueType::Integer) => (value.fract() == 0.0
+                && value.abs() <= MAX_EXACT_FLOAT_INTEGER as f64)
+                .then_some(Self::Integer(*value as i64)),
+            (Self::String(value), ValueType::Timestamp) => {
+                UtcDateTime::parse_rfc3339(value).ok().map(Self::Timestamp)
+            }
+            (value, value_type) if value.value_type() == Some(value_type) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    /// Converts a literal to the type of the member it is compared with.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`FilterError::InvalidTimestamp`] if a string is not an RFC 3339 timestamp.
+    /// Will return [`FilterError::PrecisionLoss`] if a number cannot be converted exactly.
+    /// Will return [`FilterError::InvalidType`] if the value cannot be converted to the value type.
+    pub fn coerce_literal(&self, value_type: ValueType) -> FilterResult<Self> {
+        self.coerce(value_type).ok_or_else(|| match self {
+            Self::String(value) if value_type == ValueType::Timestamp => {
+                FilterError::InvalidTimestamp(value.clone())
+            }
+            Self::Integer(_) | Self::Float(_)
+                if matches!(value_type, ValueType::Integer | ValueType::Float) =>
+            {
+                FilterError::PrecisionLoss {
+                    value: self.to_string(),
+                    expected: value_type,
+                }
+            }
+            _ => FilterError::InvalidType {
+                expected: value_type,
+                actual: self.value_type().unwrap_or(ValueType::Any),
+            },
+        })
+    }
+
     /// Parses a value from a pest pair.
     ///
     /// # Errors
@@ -55,17 +121,15 @@ impl Value {
     /// Will return [`FilterError::ExpectedValue`] if the pair rule is not a valid value type.
     pub fn parse(pair: &Pair<'_, Rule>) -> FilterResult<Self> {
         match pair.as_rule() {
+            // Strings are converted to other types, such as timestamps, by the schema of the compared member
             Rule::String => {
-                let value = pair.as_str();
-                UtcDateTime::parse_rfc3339(value).map_or_else(
-                    |_| {
-                        let lexeme = pair.as_str();
-                        Ok(Self::String(lexeme[1..lexeme.len() - 1].into()))
-                    },
-                    |value| Ok(value.into()),
-                )
+                let lexeme = pair.as_str();
+                Ok(Self::String(lexeme[1..lexeme.len() - 1].into()))
             }
             Rule::Boolean => Ok(Self::Boolean(pair.as_str() == "true")),
+            Rule::Duration => Self::parse_duration(pair.as_str())
+                .map(Self::Duration)
+                .ok_or_else(|| FilterError::InvalidDuration(pair.as_str().into())),
             Rule::Number => pair.as_str().parse::<i64>().map_or_else(
                 |_| {
                     pair.as_str().parse::<f64>().map_or_else(
@@ -76,9 +140,68 @@ impl Value {
                 |value| Ok(Self::Integer(value)),
             ),
             Rule::Any => Ok(Self::Any),
+            Rule::Null => Ok(Self::Null),
             _ => Err(FilterError::ExpectedValue),
         }
     }
+
+    /// Parses a duration literal, such as `1.5h`, exactly to nanoseconds.
+    fn parse_duration(lexeme: &str) -> Option<Duration> {
+        let unit_index = lexeme.find(|c: char| c.is_ascii_alphabetic())?;
+        let unit_nanos: i128 = match &lexeme[unit_index..] {
+            "h" => 3_600 * NANOS_PER_SECOND,
+            "m" => 60 * NANOS_PER_SECOND,
+            "s" => NANOS_PER_SECOND,
+            "ms" => 1_000_000,
+            "us" => 1_000,
+            "ns" => 1,
+            _ => return None,
+        };
+
+        let number = &lexeme[..unit_index];
+        let (negative, number) = number
+            .strip_prefix('-')
+            .map_or((false, number), |number| (true, number));
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        let mut nanos = integer.parse::<i128>().ok()?.checked_mul(unit_nanos)?;
+        // Digits beyond nanosecond precision are truncated
+        let fraction = &fraction[..fraction.len().min(18)];
+        if !fraction.is_empty() {
+            let scale = 10i128.pow(u32::try_from(fraction.len()).ok()?);
+            nanos += fraction.parse::<i128>().ok()? * unit_nanos / scale;
+        }
+        if negative {
+            nanos = -nanos;
+        }
+
+        if nanos.unsigned_abs() > MAX_DURATION_SECONDS as u128 * NANOS_PER_SECOND as u128 {
+            return None;
+        }
+        Some(Duration::new(
+            i64::try_from(nanos / NANOS_PER_SECOND).ok()?,
+            i32::try_from(nanos % NANOS_PER_SECOND).ok()?,
+        ))
+    }
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Largest integer up to which all integers are exactly representable as `f64`.
+const MAX_EXACT_FLOAT_INTEGER: u64 = 1 << f64::MANTISSA_DIGITS;
+
+/// Maximum duration supported by protobuf, about 10,000 years.
+const MAX_DURATION_SECONDS: i128 = 315_576_000_000;
+
+/// Gets the total number of nanoseconds in a duration.
+pub(crate) fn duration_nanos(duration: &Duration) -> i128 {
+    i128::from(duration.seconds) * NANOS_PER_SECOND + i128::from(duration.nanos)
+}
+
+/// Gets nanoseconds of a duration, as stored by query backends.
+pub(crate) fn duration_nanos_i64(duration: &Duration) -> FilterResult<i64> {
+    i64::try_from(duration_nanos(duration))
+        .map_err(|_| FilterError::InvalidDuration(duration.to_string()))
 }
 
 impl Display for Value {
@@ -97,6 +220,24 @@ impl Display for Value {
                 value.format_rfc3339().unwrap().fmt(f)?;
                 f.write_char('"')
             }
+            Self::Duration(value) => {
+                let nanos = duration_nanos(value);
+                if nanos < 0 {
+                    f.write_char('-')?;
+                }
+                let nanos = nanos.unsigned_abs();
+                let (seconds, fraction) = (
+                    nanos / NANOS_PER_SECOND as u128,
+                    nanos % NANOS_PER_SECOND as u128,
+                );
+                if fraction == 0 {
+                    write!(f, "{seconds}s")
+                } else {
+                    let fraction = format!("{fraction:09}");
+                    write!(f, "{seconds}.{}s", fraction.trim_end_matches('0'))
+                }
+            }
+            Self::Enum(value) => value.fmt(f),
             Self::Repeated(values) => {
                 write!(
                     f,
@@ -108,7 +249,19 @@ impl Display for Value {
                         .join(", ")
                 )
             }
+            Self::Map(entries) => {
+                write!(
+                    f,
+                    "{{{}}}",
+                    entries
+                        .iter()
+                        .map(|(key, value)| format!("{key:?}: {value}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
             Self::Any => f.write_char('*'),
+            Self::Null => f.write_str("null"),
         }
     }
 }
@@ -151,6 +304,13 @@ impl PartialOrd<Self> for Value {
                     None
                 }
             }
+            Self::Duration(lhs) => {
+                if let Self::Duration(rhs) = other {
+                    duration_nanos(lhs).partial_cmp(&duration_nanos(rhs))
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
@@ -204,15 +364,27 @@ impl From<UtcDateTime> for Value {
     }
 }
 
+impl From<Duration> for Value {
+    fn from(value: Duration) -> Self {
+        Self::Duration(value)
+    }
+}
+
 impl From<Vec<Self>> for Value {
     fn from(values: Vec<Self>) -> Self {
         Self::Repeated(values)
     }
 }
 
+impl From<BTreeMap<String, Self>> for Value {
+    fn from(entries: BTreeMap<String, Self>) -> Self {
+        Self::Map(entries)
+    }
+}
+
 #[cfg(feature = "postgres")]
 const _: () = {
-    use bytes::BytesMut;
+    use bytes::{BufMut, BytesMut};
     use postgres_types::{IsNull, ToSql, Type, to_sql_checked};
 
     impl ToSql for Value {
@@ -228,10 +400,24 @@ const _: () = {
                 Self::Integer(value) => value.to_sql(ty, out),
                 Self::Float(value) => value.to_sql(ty, out),
                 Self::Boolean(value) => value.to_sql(ty, out),
-                Self::String(value) => value.to_sql(ty, out),
+                Self::String(value) | Self::Enum(value) => value.to_sql(ty, out),
                 Self::Timestamp(value) => value.to_sql(ty, out),
+                // Durations are stored as nanoseconds in integer columns
+                Self::Duration(value) if *ty == Type::INT8 => {
+                    duration_nanos_i64(value)?.to_sql(ty, out)
+                }
+                Self::Duration(value) => {
+                    // Binary interval format of microseconds, days and months
+                    let micros = i64::try_from(duration_nanos(value) / 1_000)?;
+                    out.put_i64(micros);
+                    out.put_i32(0);
+                    out.put_i32(0);
+                    Ok(IsNull::No)
+                }
                 Self::Repeated(values) => values.to_sql(ty, out),
+                Self::Map(_) => Err("map values cannot be bound".into()),
                 Self::Any => Ok(IsNull::No),
+                Self::Null => Ok(IsNull::Yes),
             }
         }
 
@@ -244,6 +430,7 @@ const _: () = {
                     | &Type::VARCHAR
                     | &Type::TEXT
                     | &Type::TIMESTAMPTZ
+                    | &Type::INTERVAL
             )
         }
 
@@ -266,19 +453,38 @@ const _: () = {
     use mysql_common::Value as MySqlValue;
     use time::PrimitiveDateTime;
 
+    impl Value {
+        /// Converts the value to a MySQL value.
+        ///
+        /// # Errors
+        ///
+        /// Will return [`FilterError::UnsupportedArgument`] for repeated and map values, which cannot be bound.
+        /// Will return [`FilterError::InvalidDuration`] if a duration does not fit in 64-bit nanoseconds.
+        pub fn try_into_mysql_value(self) -> FilterResult<MySqlValue> {
+            Ok(match self {
+                Self::Integer(value) => value.into(),
+                Self::Float(value) => value.into(),
+                Self::Boolean(value) => value.into(),
+                Self::String(value) | Self::Enum(value) => value.into(),
+                Self::Timestamp(value) => PrimitiveDateTime::from(value).into(),
+                // Durations are stored as nanoseconds
+                Self::Duration(value) => duration_nanos_i64(&value)?.into(),
+                Self::Repeated(_) | Self::Map(_) => {
+                    return Err(FilterError::UnsupportedArgument(self.to_string()));
+                }
+                Self::Any | Self::Null => MySqlValue::NULL,
+            })
+        }
+    }
+
     impl From<Value> for MySqlValue {
+        /// Converts the value to a MySQL value.
+        ///
+        /// # Panics
+        ///
+        /// Will panic if the value cannot be converted, see [`Value::try_into_mysql_value`].
         fn from(value: Value) -> Self {
-            match value {
-                Value::Integer(value) => value.into(),
-                Value::Float(value) => value.into(),
-                Value::Boolean(value) => value.into(),
-                Value::String(value) => value.into(),
-                Value::Timestamp(value) => PrimitiveDateTime::from(value).into(),
-                Value::Repeated(_values) => {
-                    todo!("repeated values are not yet supported for mysql")
-                }
-                Value::Any => Self::NULL,
-            }
+            value.try_into_mysql_value().unwrap()
         }
     }
 };
@@ -298,5 +504,132 @@ mod tests {
             Value::Repeated(vec![Value::Integer(1), 2.into(), 3.into()]).to_string(),
             "[1, 2, 3]"
         );
+        assert_eq!(Value::Enum("PUBLISHED".into()).to_string(), "PUBLISHED");
+        assert_eq!(Value::Null.to_string(), "null");
+        assert_eq!(
+            Value::Map(BTreeMap::from([
+                ("env".into(), "prod".into()),
+                ("tier".into(), 1.into()),
+            ]))
+            .to_string(),
+            r#"{"env": "prod", "tier": 1}"#
+        );
+        assert_eq!(Value::Duration(Duration::new(90, 0)).to_string(), "90s");
+        assert_eq!(
+            Value::Duration(Duration::new(-1, -500_000_000)).to_string(),
+            "-1.5s"
+        );
+        assert_eq!(
+            Value::Duration(Duration::new(0, 1_000)).to_string(),
+            "0.000001s"
+        );
+    }
+
+    #[test]
+    fn coerce() {
+        assert_eq!(
+            Value::Integer(10).coerce(ValueType::Float),
+            Some(Value::Float(10.0))
+        );
+        assert_eq!(Value::Integer(i64::MAX).coerce(ValueType::Float), None);
+        assert_eq!(
+            Value::Float(-3.0).coerce(ValueType::Integer),
+            Some(Value::Integer(-3))
+        );
+        assert_eq!(Value::Float(2.5).coerce(ValueType::Integer), None);
+        assert_eq!(Value::Float(1e300).coerce(ValueType::Integer), None);
+        assert_eq!(
+            Value::String("a".into()).coerce(ValueType::String),
+            Some("a".into())
+        );
+        assert_eq!(Value::Integer(1).coerce(ValueType::String), None);
+        assert_eq!(
+            Value::String("2024-01-01T00:00:00Z".into()).coerce(ValueType::Timestamp),
+            Some(Value::Timestamp(
+                UtcDateTime::parse_rfc3339("2024-01-01T00:00:00Z").unwrap()
+            ))
+        );
+        assert_eq!(
+            Value::String("2024-01-01".into()).coerce_literal(ValueType::Timestamp),
+            Err(FilterError::InvalidTimestamp("2024-01-01".into()))
+        );
+        assert_eq!(
+            Value::Boolean(true).coerce_literal(ValueType::Timestamp),
+            Err(FilterError::InvalidType {
+                expected: ValueType::Timestamp,
+                actual: ValueType::Boolean,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_duration() {
+        assert_eq!(Value::parse_duration("30s"), Some(Duration::new(30, 0)));
+        assert_eq!(Value::parse_duration("1.5h"), Some(Duration::new(5_400, 0)));
+        assert_eq!(Value::parse_duration("2m"), Some(Duration::new(120, 0)));
+        assert_eq!(
+            Value::parse_duration("-250ms"),
+            Some(Duration::new(0, -250_000_000))
+        );
+        assert_eq!(
+            Value::parse_duration("1.5us"),
+            Some(Duration::new(0, 1_500))
+        );
+        assert_eq!(Value::parse_duration("7ns"), Some(Duration::new(0, 7)));
+        assert_eq!(
+            Value::parse_duration("0.0000000019s"),
+            Some(Duration::new(0, 1))
+        );
+        assert_eq!(Value::parse_duration("1000000000h"), None);
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn postgres() {
+        use bytes::BytesMut;
+        use postgres_types::{ToSql, Type};
+
+        let value = Value::Duration(Duration::new(1, 500_000_000));
+        let mut out = BytesMut::new();
+        value.to_sql(&Type::INT8, &mut out).unwrap();
+        assert_eq!(out.as_ref(), 1_500_000_000i64.to_be_bytes());
+
+        out.clear();
+        value.to_sql(&Type::INTERVAL, &mut out).unwrap();
+        assert_eq!(
+            out.as_ref(),
+            [1_500_000i64.to_be_bytes().as_slice(), &[0; 8]].concat()
+        );
+
+        assert!(
+            Value::Duration(Duration::new(i64::MAX, 0))
+                .to_sql(&Type::INT8, &mut out)
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn mysql() {
+        use mysql_common::Value as MySqlValue;
+
+        assert_eq!(MySqlValue::from(Value::Integer(42)), MySqlValue::Int(42));
+        assert_eq!(Value::Null.try_into_mysql_value(), Ok(MySqlValue::NULL));
+        assert_eq!(
+            Value::Duration(Duration::new(10, 0)).try_into_mysql_value(),
+            Ok(MySqlValue::Int(10_000_000_000))
+        );
+        assert_eq!(
+            Value::Duration(Duration::new(i64::MAX, 0)).try_into_mysql_value(),
+            Err(FilterError::InvalidDuration(
+                Duration::new(i64::MAX, 0).to_string()
+            ))
+        );
+        assert_eq!(
+            Value::Map(BTreeMap::from([("env".into(), "prod".into())])).try_into_mysql_value(),
+            Err(FilterError::UnsupportedArgument(
+                r#"{"env": "prod"}"#.into()
+            ))
+        );
     }
 }
@@ -5,8 +5,10 @@ use parse::{
     parse_resource_name::{self, ParseResourceName},
 };
 use proc_macro::TokenStream;
+use query::QueryMacro;
 
 mod parse;
+mod query;
 
 use syn::{DeriveInput, parse_macro_input};
 
@@ -37,6 +39,47 @@ pub fn parse_resource_name(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Parses an AIP-160 filter at compile time, expanding to construction of a `Filter`.
+///
+/// Syntax errors are reported at compile time, pointing into the filter literal.
+/// Rust expressions are interpolated with `#{expr}`, and converted to values with `Value::from`.
+/// The crate path can be customized with `bomboni_crate`, `bomboni_proto_crate` and `bomboni_request_crate` options.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let min_age = 18;
+/// let filter = filter!(r#"age >= #{min_age} AND displayName = "John""#);
+/// assert_eq!(filter, Filter::parse(r#"age >= 18 AND displayName = "John""#).unwrap());
+///
+/// let filter = filter!(bomboni_request_crate = crate, "deleted = false");
+/// ```
+#[proc_macro]
+pub fn filter(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as QueryMacro);
+    query::filter::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Parses an AIP-132 ordering at compile time, expanding to construction of an `Ordering`.
+///
+/// Accepts the same options as [`filter!`].
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let ordering = order_by!("age desc, displayName");
+/// assert_eq!(ordering, Ordering::parse("age desc, displayName").unwrap());
+/// ```
+#[proc_macro]
+pub fn order_by(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as QueryMacro);
+    query::ordering::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// Derive macro for creating derived map types.
 #[proc_macro]
 pub fn derived_map(input: TokenStream) -> TokenStream {
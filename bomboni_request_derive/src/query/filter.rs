@@ -0,0 +1,319 @@
+//! Compile-time parsing of filters.
+//!
+//! Filters are parsed with the AIP-160 grammar of `bomboni_request`, such that the macro accepts the same filters as `Filter::parse`.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use parser::{FilterParser, Rule};
+use pest::Parser;
+use pest::error::InputLocation;
+use pest::iterators::Pair;
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::Expr;
+
+use super::QueryMacro;
+
+#[allow(clippy::upper_case_acronyms)]
+mod parser {
+    use pest_derive::Parser;
+
+    #[derive(Parser)]
+    #[grammar = "../bomboni_request/src/filter/grammar.pest"]
+    pub struct FilterParser;
+}
+
+pub fn expand(input: QueryMacro) -> syn::Result<TokenStream> {
+    let source = input.source.value();
+    let (replaced_source, interpolations) = input.replace_interpolations(&source)?;
+    let filter = FilterParser::parse(Rule::Filter, &replaced_source)
+        .map_err(|err| {
+            let (start, end) = match err.location {
+                InputLocation::Pos(position) => (position, (position + 1).min(source.len())),
+                InputLocation::Span(span) => span,
+            };
+            syn::Error::new(
+                input.source_span(start, end),
+                format!(
+                    "failed to parse filter at position {start}: {}",
+                    err.variant.message()
+                ),
+            )
+        })?
+        .next()
+        .unwrap();
+    FilterExpander {
+        input: &input,
+        source: &source,
+        interpolations,
+    }
+    .expand_filter(filter)
+}
+
+impl QueryMacro {
+    /// Replaces interpolations, such as `#{min_age}`, with string literals of the same length,
+    /// such that the source can be parsed with the filter grammar.
+    ///
+    /// Returns the replaced source and ranges of interpolated expressions, keyed by the position of their placeholders.
+    fn replace_interpolations(
+        &self,
+        source: &str,
+    ) -> syn::Result<(String, BTreeMap<usize, Range<usize>>)> {
+        let mut replaced_source = String::with_capacity(source.len());
+        let mut interpolations = BTreeMap::new();
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut position = 0;
+        while let Some(c) = source[position..].chars().next() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+            } else if c == '"' {
+                in_string = true;
+            } else if source[position..].starts_with("#{") {
+                let end = find_interpolation_end(&source[position + 2..])
+                    .map(|end| position + 2 + end)
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            self.source_span(position, position + 2),
+                            format!("unterminated interpolation at position {position}"),
+                        )
+                    })?;
+                interpolations.insert(position, position + 2..end);
+                replaced_source.push('"');
+                replaced_source.push_str(&" ".repeat(end - position - 1));
+                replaced_source.push('"');
+                position = end + 1;
+                continue;
+            }
+            replaced_source.push(c);
+            position += c.len_utf8();
+        }
+        Ok((replaced_source, interpolations))
+    }
+}
+
+/// Finds the position of the closing brace of an interpolation, skipping nested braces.
+fn find_interpolation_end(source: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in source.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Expands parsed filters to construction of `Filter`, following `Filter::parse`.
+struct FilterExpander<'a> {
+    input: &'a QueryMacro,
+    /// Source string, before interpolations were replaced.
+    source: &'a str,
+    interpolations: BTreeMap<usize, Range<usize>>,
+}
+
+impl FilterExpander<'_> {
+    fn expand_filter(&self, pair: Pair<Rule>) -> syn::Result<TokenStream> {
+        let request_crate = &self.input.request_crate;
+        let expand_all = |pairs: Vec<Pair<Rule>>| {
+            pairs
+                .into_iter()
+                .map(|pair| self.expand_filter(pair))
+                .collect::<syn::Result<Vec<_>>>()
+        };
+        Ok(match pair.as_rule() {
+            Rule::Filter | Rule::Expression => {
+                let mut parts: Vec<_> = pair
+                    .into_inner()
+                    .filter(|pair| pair.as_rule() != Rule::EOI)
+                    .collect();
+                if parts.len() == 1 {
+                    return self.expand_filter(parts.pop().unwrap());
+                }
+                let parts = expand_all(parts)?;
+                quote!(#request_crate::filter::Filter::Conjunction(::std::vec![#(#parts),*]))
+            }
+            Rule::Factor => {
+                let mut parts: Vec<_> = pair.into_inner().collect();
+                if parts.len() == 1 {
+                    return self.expand_filter(parts.pop().unwrap());
+                }
+                let parts = expand_all(parts)?;
+                quote!(#request_crate::filter::Filter::Disjunction(::std::vec![#(#parts),*]))
+            }
+            Rule::Term => {
+                let lexeme = pair.as_str().trim();
+                let negated = lexeme.starts_with("NOT") || lexeme.starts_with('-');
+                let tree = self.expand_filter(pair.into_inner().next().unwrap())?;
+                if negated {
+                    quote!(#request_crate::filter::Filter::Negate(::std::boxed::Box::new(#tree)))
+                } else {
+                    tree
+                }
+            }
+            Rule::Restriction => {
+                let mut inner_pairs = pair.into_inner();
+                let comparable = self.expand_filter(inner_pairs.next().unwrap())?;
+                let Some(comparator) = inner_pairs.next() else {
+                    return Ok(comparable);
+                };
+                let comparator = match comparator.as_str() {
+                    "<" => "Less",
+                    "<=" => "LessOrEqual",
+                    ">" => "Greater",
+                    ">=" => "GreaterOrEqual",
+                    "=" => "Equal",
+                    "!=" => "NotEqual",
+                    ":" => "Has",
+                    _ => unreachable!(),
+                };
+                let comparator = syn::Ident::new(comparator, self.input.source.span());
+                let argument = self.expand_filter(inner_pairs.next().unwrap())?;
+                quote! {
+                    #request_crate::filter::Filter::Restriction(
+                        ::std::boxed::Box::new(#comparable),
+                        #request_crate::filter::FilterComparator::#comparator,
+                        ::std::boxed::Box::new(#argument),
+                    )
+                }
+            }
+            Rule::Comparable => self.expand_filter(pair.into_inner().next().unwrap())?,
+            Rule::Function => {
+                let mut inner_pairs = pair.into_inner();
+                let name = inner_pairs.next().unwrap().as_str();
+                let arguments = expand_all(inner_pairs.collect())?;
+                quote! {
+                    #request_crate::filter::Filter::Function(
+                        ::std::string::String::from(#name),
+                        ::std::vec![#(#arguments),*],
+                    )
+                }
+            }
+            Rule::Composite => {
+                let tree = self.expand_filter(pair.into_inner().next().unwrap())?;
+                quote!(#request_crate::filter::Filter::Composite(::std::boxed::Box::new(#tree)))
+            }
+            Rule::Name => {
+                let name = pair.as_str();
+                quote!(#request_crate::filter::Filter::Name(::std::string::String::from(#name)))
+            }
+            Rule::String
+            | Rule::Boolean
+            | Rule::Null
+            | Rule::Duration
+            | Rule::Number
+            | Rule::Any => {
+                let value = self.expand_value(&pair)?;
+                quote!(#request_crate::filter::Filter::Value(#value))
+            }
+            _ => unreachable!("{pair:?}"),
+        })
+    }
+
+    fn expand_value(&self, pair: &Pair<Rule>) -> syn::Result<TokenStream> {
+        let request_crate = &self.input.request_crate;
+        let proto_crate = &self.input.proto_crate;
+        let lexeme = pair.as_str();
+        Ok(match pair.as_rule() {
+            Rule::String => {
+                if let Some(range) = self.interpolations.get(&pair.as_span().start()) {
+                    let span = self.input.source_span(range.start, range.end);
+                    let expr: Expr = syn::parse_str(&self.source[range.clone()])
+                        .map_err(|err| syn::Error::new(span, err))?;
+                    return Ok(quote!(#request_crate::value::Value::from(#expr)));
+                }
+                let value = &lexeme[1..lexeme.len() - 1];
+                quote!(#request_crate::value::Value::String(::std::string::String::from(#value)))
+            }
+            Rule::Boolean => {
+                let value = lexeme == "true";
+                quote!(#request_crate::value::Value::Boolean(#value))
+            }
+            Rule::Duration => {
+                let (seconds, nanos) = parse_duration(lexeme).ok_or_else(|| {
+                    syn::Error::new(
+                        self.input.source.span(),
+                        format!("invalid duration `{lexeme}`"),
+                    )
+                })?;
+                quote! {
+                    #request_crate::value::Value::Duration(
+                        #proto_crate::google::protobuf::Duration::new(#seconds, #nanos)
+                    )
+                }
+            }
+            Rule::Number => {
+                if let Ok(value) = lexeme.parse::<i64>() {
+                    return Ok(quote!(#request_crate::value::Value::Integer(#value)));
+                }
+                let value = lexeme
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| {
+                        syn::Error::new(
+                            self.input.source.span(),
+                            format!("number `{lexeme}` is out of range"),
+                        )
+                    })?;
+                let value = Literal::f64_suffixed(value);
+                quote!(#request_crate::value::Value::Float(#value))
+            }
+            Rule::Any => quote!(#request_crate::value::Value::Any),
+            Rule::Null => quote!(#request_crate::value::Value::Null),
+            _ => unreachable!("{pair:?}"),
+        })
+    }
+}
+
+/// Parses a duration literal, such as `1.5h`, exactly to seconds and nanoseconds.
+fn parse_duration(lexeme: &str) -> Option<(i64, i32)> {
+    const NANOS_PER_SECOND: i128 = 1_000_000_000;
+    /// Maximum duration supported by protobuf, about 10,000 years.
+    const MAX_DURATION_SECONDS: i128 = 315_576_000_000;
+
+    let unit_index = lexeme.find(|c: char| c.is_ascii_alphabetic())?;
+    let unit_nanos: i128 = match &lexeme[unit_index..] {
+        "h" => 3_600 * NANOS_PER_SECOND,
+        "m" => 60 * NANOS_PER_SECOND,
+        "s" => NANOS_PER_SECOND,
+        "ms" => 1_000_000,
+        "us" => 1_000,
+        "ns" => 1,
+        _ => return None,
+    };
+
+    let number = &lexeme[..unit_index];
+    let (negative, number) = number
+        .strip_prefix('-')
+        .map_or((false, number), |number| (true, number));
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+    let mut nanos = integer.parse::<i128>().ok()?.checked_mul(unit_nanos)?;
+    // Digits beyond nanosecond precision are truncated
+    let fraction = &fraction[..fraction.len().min(18)];
+    if !fraction.is_empty() {
+        let scale = 10i128.pow(u32::try_from(fraction.len()).ok()?);
+        nanos += fraction.parse::<i128>().ok()? * unit_nanos / scale;
+    }
+    if negative {
+        nanos = -nanos;
+    }
+
+    if nanos.unsigned_abs() > MAX_DURATION_SECONDS.unsigned_abs() * NANOS_PER_SECOND.unsigned_abs()
+    {
+        return None;
+    }
+    Some((
+        i64::try_from(nanos / NANOS_PER_SECOND).ok()?,
+        i32::try_from(nanos % NANOS_PER_SECOND).ok()?,
+    ))
+}
@@ -0,0 +1,68 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    Ident, LitStr, Path, Token,
+    parse::{Parse, ParseStream},
+};
+
+pub mod filter;
+pub mod ordering;
+
+/// Input of query macros, such as `filter!(bomboni_request_crate = crate, "a = 1")`.
+pub struct QueryMacro {
+    proto_crate: TokenStream,
+    request_crate: TokenStream,
+    source: LitStr,
+}
+
+impl QueryMacro {
+    /// Gets the span of a range of the source string.
+    ///
+    /// Falls back to the span of the whole literal if the range cannot be located,
+    /// such as when the literal contains escapes or the compiler does not support subspans.
+    fn source_span(&self, start: usize, end: usize) -> Span {
+        let token = self.source.token();
+        let literal = token.to_string();
+        let value = self.source.value();
+        literal
+            .find('"')
+            .map(|quote| quote + 1)
+            .filter(|&offset| literal[offset..].starts_with(&value))
+            .and_then(|offset| token.subspan(offset + start..offset + end))
+            .unwrap_or_else(|| self.source.span())
+    }
+}
+
+impl Parse for QueryMacro {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let (mut proto_crate, mut request_crate) = if cfg!(feature = "root-crate") {
+            (quote!(bomboni::proto), quote!(bomboni::request))
+        } else {
+            (quote!(bomboni_proto), quote!(bomboni_request))
+        };
+
+        while input.peek(Ident) && input.peek2(Token![=]) {
+            let option: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let path: Path = input.parse()?;
+            input.parse::<Token![,]>()?;
+            match option.to_string().as_str() {
+                "bomboni_crate" => {
+                    proto_crate = quote!(#path::proto);
+                    request_crate = quote!(#path::request);
+                }
+                "bomboni_proto_crate" => proto_crate = quote!(#path),
+                "bomboni_request_crate" => request_crate = quote!(#path),
+                _ => return Err(syn::Error::new_spanned(option, "unknown option")),
+            }
+        }
+
+        let source = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(Self {
+            proto_crate,
+            request_crate,
+            source,
+        })
+    }
+}
@@ -0,0 +1,62 @@
+//! Compile-time parsing of orderings.
+
+use std::collections::BTreeSet;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use super::QueryMacro;
+
+pub fn expand(input: QueryMacro) -> syn::Result<TokenStream> {
+    let request_crate = &input.request_crate;
+    let source = input.source.value();
+
+    let mut terms = Vec::new();
+    let mut term_names = BTreeSet::<&str>::new();
+    let mut offset = 0;
+    for part in source.split(',') {
+        let part_start = offset;
+        offset += part.len() + 1;
+
+        let parts: Vec<_> = part.split_whitespace().collect();
+        if parts.is_empty() {
+            continue;
+        }
+        let error = |message: String| {
+            syn::Error::new(
+                input.source_span(
+                    part_start + part.len() - part.trim_start().len(),
+                    part_start + part.trim_end().len(),
+                ),
+                message,
+            )
+        };
+
+        let (name, direction) = match parts.as_slice() {
+            [name] | [name, "asc"] => (*name, quote!(Ascending)),
+            [name, "desc"] => (*name, quote!(Descending)),
+            [_, direction] => {
+                return Err(error(format!("invalid ordering direction `{direction}`")));
+            }
+            _ => {
+                return Err(error(format!(
+                    "invalid ordering term `{}`",
+                    parts.join(" ")
+                )));
+            }
+        };
+        if !term_names.insert(name) {
+            return Err(error(format!("duplicate ordering field `{name}`")));
+        }
+        terms.push(quote! {
+            #request_crate::ordering::OrderingTerm {
+                name: ::std::string::String::from(#name),
+                direction: #request_crate::ordering::OrderingDirection::#direction,
+            }
+        });
+    }
+
+    Ok(quote! {
+        #request_crate::ordering::Ordering::new(::std::vec![#(#terms),*])
+    })
+}
@@ -0,0 +1,6 @@
+use bomboni_request_derive::filter;
+
+fn main() {
+    let _ = filter!("a = 1 AND b = #{1 +}");
+    let _ = filter!("a = #{1");
+}
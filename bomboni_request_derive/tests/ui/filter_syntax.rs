@@ -0,0 +1,5 @@
+use bomboni_request_derive::filter;
+
+fn main() {
+    let _ = filter!("a = 1 AND b != AND c");
+}
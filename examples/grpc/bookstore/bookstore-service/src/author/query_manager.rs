@@ -57,6 +57,7 @@ impl AuthorQueryManager {
                     }),
                     max_filter_length: Some(100),
                     max_ordering_length: Some(100),
+                    ..Default::default()
                 },
                 PlainPageTokenBuilder {},
             ),
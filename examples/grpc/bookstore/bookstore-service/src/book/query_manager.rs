@@ -55,6 +55,7 @@ impl BookQueryManager {
                     }),
                     max_filter_length: Some(100),
                     max_ordering_length: Some(100),
+                    ..Default::default()
                 },
                 PlainPageTokenBuilder {},
             ),